@@ -3,7 +3,7 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use coinflip::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ConfigResponse};
+use bet::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ConfigResponse};
 
 fn main() {
   let mut out_dir = current_dir().unwrap();
@@ -0,0 +1,368 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Addr, CosmosMsg, DepsMut, Env, Event, Int128, MessageInfo, Response, Storage, Uint128
+};
+use crate::error::ContractError;
+use crate::msg::{AutoBetResponse, AutoBetStop, AutoStrategy, BatchBetResponse, BatchGame, BatchRound, BHistory, DHistory, FHistory, HashObj, RHistory};
+use crate::state::{Config, BHISTORY, CONFIG, DHISTORY, FHISTORY, RHISTORY};
+use crate::util;
+use crate::screening;
+use crate::jackpot;
+use crate::ledger;
+use crate::limits;
+use crate::rate_limit;
+use crate::payout_cap;
+use crate::settlement::{self, Bet};
+use crate::constants;
+
+pub fn check_selection(game: BatchGame, level: u64) -> Result<(), ContractError> {
+    let max = match game {
+        BatchGame::Flip | BatchGame::Dice => 1,
+        BatchGame::Rps => 2,
+        BatchGame::Roulette => 48,
+    };
+    if level > max {
+        return Err(ContractError::InvalidBet {});
+    }
+    Ok(())
+}
+
+/// Largest payout of a round, every batch game pays `REWARD_RATE` times the stake less the fee
+pub fn max_reward(amount: Uint128) -> Uint128 {
+    let owner_amount = amount * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
+    amount * Uint128::from(constants::REWARD_RATE) - owner_amount
+}
+
+/// Plays one round with the rules of the single bet and writes it to the game history,
+/// the caller settles it once paid.
+/// The round's count enters the hash, so every round of a batch draws fresh randomness.
+/// Only rounds with `jackpot` set contribute to the jackpot and can win it.
+#[allow(clippy::too_many_arguments)]
+pub fn play_round(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: &Addr,
+    cfg: &mut Config,
+    game: BatchGame,
+    level: u64,
+    amount: Uint128,
+    jackpot: bool
+) -> Result<BatchRound, ContractError> {
+
+    let count = match game {
+        BatchGame::Flip => cfg.flip_count,
+        BatchGame::Rps => cfg.rps_count,
+        BatchGame::Dice => cfg.dice_count,
+        BatchGame::Roulette => cfg.roulette_count,
+    };
+
+    let obj = HashObj {
+        time: env.block.time.seconds(),
+        address: sender.clone(),
+        level,
+        count
+    };
+    let hash = util::calculate_hash(&obj);
+
+    let (result, win) = match game {
+        BatchGame::Flip => (hash % 2, if hash % 2 == 0 { 0 } else { 1 }),
+        BatchGame::Rps => (hash % 3, util::get_winner(level, hash % 3).unwrap_or(1)),
+        BatchGame::Dice => (hash % 6, if hash % 6 % 2 == level { 0 } else { 1 }),
+        BatchGame::Roulette => (hash % 37, if util::roulette_hit(level, hash % 37) { 0 } else { 1 }),
+    };
+
+    let owner_amount = amount * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
+    let payout = match (game, win) {
+        (_, 0) => max_reward(amount),
+        (BatchGame::Rps, 1) => amount - owner_amount,
+        _ => Uint128::zero(),
+    };
+
+    let jackpot_amount = if jackpot {
+        jackpot::settle_jackpot(storage, &obj, game.as_str(), amount, sender, env.block.time.seconds())?
+    } else {
+        Uint128::zero()
+    };
+
+    let id = count + 1;
+    let timestamp = env.block.time.seconds();
+    match game {
+        BatchGame::Flip => {
+            FHISTORY.save(storage, count, &FHistory { id, address: sender.clone(), level, win: Some(win), bet_amount: amount, timestamp })?;
+            cfg.flip_count += 1;
+        }
+        BatchGame::Rps => {
+            RHISTORY.save(storage, count, &RHistory { id, address: sender.clone(), level, win: Some(win), bet_amount: amount, timestamp })?;
+            cfg.rps_count += 1;
+        }
+        BatchGame::Dice => {
+            DHISTORY.save(storage, count, &DHistory { id, address: sender.clone(), level, win: Some(win), bet_amount: amount, timestamp })?;
+            cfg.dice_count += 1;
+        }
+        BatchGame::Roulette => {
+            BHISTORY.save(storage, count, &BHistory { id, address: sender.clone(), level, win: Some(win), bet_amount: amount, timestamp })?;
+            cfg.roulette_count += 1;
+        }
+    }
+
+    Ok(BatchRound {
+        id,
+        result,
+        win,
+        bet_amount: amount,
+        payout,
+        jackpot: jackpot_amount
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_batch_bet(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    game: BatchGame,
+    selection: u64,
+    rounds: u32,
+    stake_per_round: Uint128,
+    from_balance: Option<Uint128>
+) -> Result<Response, ContractError> {
+
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    if rounds == 0 || rounds > constants::BATCH_MAX_ROUNDS || stake_per_round.is_zero() {
+        return Err(ContractError::InvalidInput {});
+    }
+    check_selection(game, selection)?;
+
+    let total_stake = stake_per_round * Uint128::from(rounds);
+    let amount = ledger::take_stake(deps.storage, &info, cfg.denom.clone(), from_balance)?;
+    if amount != total_stake {
+        return Err(ContractError::StakeMismatch { expected: total_stake });
+    }
+    limits::check_bet(deps.storage, &env, &info.sender, total_stake)?;
+    rate_limit::check(deps.storage, &env, &info.sender, rounds)?;
+
+    // Every round could win, so the bankroll must cover all of them up-front
+    let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+    if contract_amount < max_reward(stake_per_round) * Uint128::from(rounds) {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    payout_cap::check(deps.storage, &env, &info.sender, max_reward(stake_per_round) * Uint128::from(rounds))?;
+
+    let mut list: Vec<BatchRound> = vec![];
+    for _ in 0..rounds {
+        list.push(play_round(deps.storage, &env, &info.sender, &mut cfg, game, selection, stake_per_round, true)?);
+    }
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let round_fee = stake_per_round * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
+    let owner_amount = round_fee * Uint128::from(rounds);
+    let total_payout: Uint128 = list.iter().map(|r| r.payout + r.jackpot).sum();
+    let wins = list.iter().filter(|r| r.win == 0).count();
+
+    let mut messages: Vec<CosmosMsg> = vec![
+        util::fee_message(deps.storage, deps.querier, deps.api, cfg.denom.clone(), &info.sender, total_stake, owner_amount)?
+    ];
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, total_payout, from_balance.is_some(), &mut messages)?;
+
+    let mut breaker: Vec<Event> = vec![];
+    for round in list.iter() {
+        breaker.extend(settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+            player: &info.sender,
+            game: game.as_str(),
+            count: round.id - 1,
+            win: Some(round.win),
+            wagered: stake_per_round,
+            fee: round_fee,
+            paid: round.payout,
+            jackpot: round.jackpot
+        })?);
+    }
+
+    let data = BatchBetResponse {
+        game,
+        rounds: list,
+        total_stake,
+        total_payout
+    };
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_events(breaker)
+        .set_data(to_json_binary(&data)?)
+        .add_attributes(vec![
+            attr("action", "batch_bet"),
+            attr("address", info.sender.clone()),
+            attr("game", game.as_str()),
+            attr("rounds", rounds.to_string()),
+            attr("amount", total_stake),
+            attr("wins", wins.to_string()),
+            attr("payout", total_payout),
+        ]))
+}
+
+/// Stake of the round after one staked `stake`, a tie keeps the stake
+fn next_stake(strategy: AutoStrategy, base: Uint128, stake: Uint128, won: bool, tie: bool) -> Uint128 {
+    if tie {
+        return stake;
+    }
+    match (strategy, won) {
+        (AutoStrategy::Flat, _) => base,
+        (AutoStrategy::Martingale, true) | (AutoStrategy::AntiMartingale, false) => base,
+        (AutoStrategy::Martingale, false) | (AutoStrategy::AntiMartingale, true) => stake * Uint128::from(2u8),
+        (AutoStrategy::DAlembert, true) => stake.saturating_sub(base).max(base),
+        (AutoStrategy::DAlembert, false) => stake + base,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_auto_bet(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    game: BatchGame,
+    selection: u64,
+    strategy: AutoStrategy,
+    base_stake: Uint128,
+    rounds: u32,
+    stop_loss: Option<Uint128>,
+    take_profit: Option<Uint128>,
+    from_balance: Option<Uint128>
+) -> Result<Response, ContractError> {
+
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    if rounds == 0
+        || rounds > constants::BATCH_MAX_ROUNDS
+        || base_stake.is_zero()
+        || stop_loss.is_some_and(|l| l.is_zero())
+        || take_profit.is_some_and(|p| p.is_zero())
+    {
+        return Err(ContractError::InvalidInput {});
+    }
+    check_selection(game, selection)?;
+
+    // The attached funds or debited amount are the budget the stakes are taken from
+    let budget = ledger::take_stake(deps.storage, &info, cfg.denom.clone(), from_balance)?;
+    if budget < base_stake {
+        return Err(ContractError::StakeMismatch { expected: base_stake });
+    }
+
+    // What the player has left stays reserved, so the bankroll check of every round
+    // only counts the house's own funds. Fees are reserved until they are sent.
+    util::reserve(deps.storage, budget)?;
+
+    let mut funds = budget;
+    let mut total_stake = Uint128::zero();
+    let mut total_payout = Uint128::zero();
+    let mut fees = Uint128::zero();
+    let mut stake = base_stake;
+    let mut stop = AutoBetStop::Rounds;
+    let mut list: Vec<BatchRound> = vec![];
+    let mut breaker: Vec<Event> = vec![];
+
+    for _ in 0..rounds {
+        if stake > funds {
+            stop = AutoBetStop::Budget;
+            break;
+        }
+        let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+        if contract_amount < max_reward(stake) {
+            stop = AutoBetStop::Bankroll;
+            break;
+        }
+        // The first stake is admitted like any bet, later ones stop the run once a limit is reached
+        if list.is_empty() {
+            limits::check_bet(deps.storage, &env, &info.sender, stake)?;
+            rate_limit::check(deps.storage, &env, &info.sender, 1)?;
+            payout_cap::check(deps.storage, &env, &info.sender, max_reward(stake))?;
+        } else if limits::check_wager(deps.storage, &env, &info.sender, stake).is_err()
+            || payout_cap::check(deps.storage, &env, &info.sender, max_reward(stake)).is_err()
+            || rate_limit::check(deps.storage, &env, &info.sender, 1).is_err()
+        {
+            stop = AutoBetStop::Limit;
+            break;
+        }
+
+        let owner_amount = stake * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
+        let round = play_round(deps.storage, &env, &info.sender, &mut cfg, game, selection, stake, true)?;
+        let returned = round.payout + round.jackpot;
+        breaker.extend(settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+            player: &info.sender,
+            game: game.as_str(),
+            count: round.id - 1,
+            win: Some(round.win),
+            wagered: stake,
+            fee: owner_amount,
+            paid: round.payout,
+            jackpot: round.jackpot
+        })?);
+
+        util::release(deps.storage, stake)?;
+        util::reserve(deps.storage, owner_amount + returned)?;
+        funds = funds - stake + returned;
+        total_stake += stake;
+        total_payout += returned;
+        fees += owner_amount;
+
+        let won = round.win == 0;
+        let tie = game == BatchGame::Rps && round.win == 1;
+        list.push(round);
+
+        if stop_loss.is_some_and(|l| total_stake.saturating_sub(total_payout) >= l) {
+            stop = AutoBetStop::StopLoss;
+            break;
+        }
+        if take_profit.is_some_and(|p| total_payout.saturating_sub(total_stake) >= p) {
+            stop = AutoBetStop::TakeProfit;
+            break;
+        }
+
+        stake = next_stake(strategy, base_stake, stake, won, tie);
+    }
+
+    util::release(deps.storage, funds + fees)?;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !fees.is_zero() {
+        messages.push(util::fee_message(deps.storage, deps.querier, deps.api, cfg.denom.clone(), &info.sender, total_stake, fees)?);
+    }
+    // Winnings and the unspent budget go back together
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, funds, from_balance.is_some(), &mut messages)?;
+
+    let net_profit = Int128::new(total_payout.u128() as i128 - total_stake.u128() as i128);
+    let rounds_played = list.len() as u32;
+
+    let data = AutoBetResponse {
+        game,
+        strategy,
+        rounds: list,
+        rounds_played,
+        total_stake,
+        total_payout,
+        net_profit,
+        stop
+    };
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_events(breaker)
+        .set_data(to_json_binary(&data)?)
+        .add_attributes(vec![
+            attr("action", "auto_bet"),
+            attr("address", info.sender.clone()),
+            attr("game", game.as_str()),
+            attr("strategy", strategy.as_str()),
+            attr("rounds_played", rounds_played.to_string()),
+            attr("amount", total_stake),
+            attr("payout", total_payout),
+            attr("net_profit", net_profit.to_string()),
+            attr("stop", stop.as_str()),
+        ]))
+}
@@ -0,0 +1,471 @@
+use cosmwasm_std::{
+    attr, Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Uint128
+};
+use cw20::Balance;
+use crate::error::ContractError;
+use crate::msg::{BlackjackGame, BlackjackGameResponse, BlackjackHand, HashObj, JHistory, JHistoryResponse};
+use crate::state::{Config, BLACKJACK_GAMES, CONFIG, JHISTORY};
+use crate::util;
+use crate::screening;
+use crate::jackpot;
+use crate::ledger;
+use crate::limits;
+use crate::rate_limit;
+use crate::payout_cap;
+use crate::settlement::{self, Bet};
+use crate::history;
+use crate::constants;
+
+fn hand_value(cards: &[u8]) -> u8 {
+    let mut total = 0u8;
+    let mut ace = false;
+    for card in cards {
+        let rank = util::card_rank(*card);
+        ace |= rank == 1;
+        total += rank.min(10);
+    }
+    // One ace can count as 11
+    if ace && total + 10 <= 21 { total + 10 } else { total }
+}
+
+fn is_blackjack(cards: &[u8]) -> bool {
+    cards.len() == 2 && hand_value(cards) == 21
+}
+
+fn cards_to_string(cards: &[u8]) -> String {
+    cards.iter().map(|c| c.to_string()).collect::<Vec<String>>().join(",")
+}
+
+fn draw(game: &mut BlackjackGame, entropy: u64) -> u8 {
+    let card = util::draw_card(game.seed, &game.drawn, entropy);
+    game.drawn.push(card);
+    card
+}
+
+/// Skips to the next hand still in play, hands reaching 21 or more stand automatically
+fn advance(game: &mut BlackjackGame) {
+    while let Some(hand) = game.hands.get_mut(game.active as usize) {
+        if !hand.done && hand_value(&hand.cards) < 21 {
+            break;
+        }
+        hand.done = true;
+        game.active += 1;
+    }
+}
+
+fn is_finished(game: &BlackjackGame) -> bool {
+    game.active as usize >= game.hands.len()
+}
+
+/// Largest amount the open hands can still return, naturals are settled at the deal
+fn max_payout(game: &BlackjackGame) -> Uint128 {
+    game.hands.iter().map(|h| h.stake * Uint128::from(2u128)).sum()
+}
+
+fn load_game(deps: &DepsMut, env: &Env, address: &Addr) -> Result<BlackjackGame, ContractError> {
+    let game = BLACKJACK_GAMES.may_load(deps.storage, address)?
+        .ok_or(ContractError::NoActiveGame {})?;
+    if env.block.time.seconds() >= game.expires_at {
+        return Err(ContractError::GameExpired {});
+    }
+    Ok(game)
+}
+
+/// Takes the extra stake of a double or split the same way the deal was paid
+fn take_extra_stake(storage: &mut dyn Storage, info: &MessageInfo, cfg: &Config, game: &BlackjackGame, expected: Uint128) -> Result<(), ContractError> {
+    if game.from_balance {
+        ledger::take_stake(storage, info, cfg.denom.clone(), Some(expected))?;
+        return Ok(());
+    }
+    let amount = util::get_amount_of_denom(Balance::from(info.funds.clone()), cfg.denom.clone())?;
+    if amount != expected {
+        return Err(ContractError::StakeMismatch { expected });
+    }
+    Ok(())
+}
+
+pub fn execute_blackjack_deal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    from_balance: Option<Uint128>
+) -> Result<Response, ContractError> {
+
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+
+    if BLACKJACK_GAMES.has(deps.storage, &info.sender) {
+        return Err(ContractError::GameInProgress {});
+    }
+
+    let amount = ledger::take_stake(deps.storage, &info, cfg.denom.clone(), from_balance)?;
+    limits::check_bet(deps.storage, &env, &info.sender, amount)?;
+    rate_limit::check(deps.storage, &env, &info.sender, 1)?;
+
+    // A natural pays 3:2
+    let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+    if contract_amount < amount * Uint128::from(5u128) / Uint128::from(2u128) {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    payout_cap::check(deps.storage, &env, &info.sender, amount * Uint128::from(5u128) / Uint128::from(2u128))?;
+
+    let obj = HashObj {
+        time: env.block.time.seconds(),
+        address: info.sender.clone(),
+        level: 0,
+        count: cfg.blackjack_count
+    };
+
+    let mut game = BlackjackGame {
+        address: info.sender.clone(),
+        seed: util::calculate_hash(&obj),
+        bet_amount: amount,
+        dealer: vec![],
+        hands: vec![BlackjackHand {
+            cards: vec![],
+            stake: amount,
+            doubled: false,
+            done: false
+        }],
+        active: 0,
+        drawn: vec![],
+        reserved: Uint128::zero(),
+        from_balance: from_balance.is_some(),
+        expires_at: env.block.time.seconds() + constants::BLACKJACK_TIMEOUT,
+        timestamp: env.block.time.seconds()
+    };
+
+    let entropy = util::block_entropy(&env);
+    let card = draw(&mut game, entropy);
+    game.hands[0].cards.push(card);
+    let card = draw(&mut game, entropy);
+    game.dealer.push(card);
+    let card = draw(&mut game, entropy);
+    game.hands[0].cards.push(card);
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    let jackpot_amount = jackpot::settle_jackpot(deps.storage, &obj, "blackjack", amount, &info.sender, env.block.time.seconds())?;
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, jackpot_amount, from_balance.is_some(), &mut messages)?;
+    limits::record_return(deps.storage, &env, &info.sender, jackpot_amount)?;
+
+    advance(&mut game);
+    if is_finished(&game) {
+        return settle(deps, env, game, messages);
+    }
+
+    let needed = max_payout(&game);
+    payout_cap::hold(deps.storage, &env, &info.sender, game.reserved, needed)?;
+    util::adjust_reservation(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone(), &mut game.reserved, needed)?;
+    BLACKJACK_GAMES.save(deps.storage, &info.sender, &game)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "blackjack_deal"),
+            attr("address", info.sender.clone()),
+            attr("amount", amount),
+            attr("player", cards_to_string(&game.hands[0].cards)),
+            attr("dealer", cards_to_string(&game.dealer)),
+            attr("jackpot", jackpot_amount),
+        ]))
+}
+
+pub fn execute_blackjack_hit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+
+    let mut game = load_game(&deps, &env, &info.sender)?;
+
+    let entropy = util::block_entropy(&env);
+    let card = draw(&mut game, entropy);
+    let active = game.active as usize;
+    game.hands[active].cards.push(card);
+    let value = hand_value(&game.hands[active].cards);
+
+    advance(&mut game);
+    if is_finished(&game) {
+        return settle(deps, env, game, vec![]);
+    }
+
+    game.expires_at = env.block.time.seconds() + constants::BLACKJACK_TIMEOUT;
+    BLACKJACK_GAMES.save(deps.storage, &info.sender, &game)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "blackjack_hit"),
+        attr("address", info.sender),
+        attr("card", card.to_string()),
+        attr("value", value.to_string()),
+    ]))
+}
+
+pub fn execute_blackjack_stand(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+
+    // Standing is always allowed, an expired game just stands every hand left
+    let mut game = BLACKJACK_GAMES.may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NoActiveGame {})?;
+
+    if env.block.time.seconds() >= game.expires_at {
+        game.active = game.hands.len() as u8;
+    } else {
+        let active = game.active as usize;
+        game.hands[active].done = true;
+        advance(&mut game);
+    }
+
+    if is_finished(&game) {
+        return settle(deps, env, game, vec![]);
+    }
+
+    game.expires_at = env.block.time.seconds() + constants::BLACKJACK_TIMEOUT;
+    BLACKJACK_GAMES.save(deps.storage, &info.sender, &game)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "blackjack_stand"),
+        attr("address", info.sender),
+        attr("active", game.active.to_string()),
+    ]))
+}
+
+pub fn execute_blackjack_double(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let mut game = load_game(&deps, &env, &info.sender)?;
+    let active = game.active as usize;
+
+    if game.hands[active].cards.len() != 2 {
+        return Err(ContractError::InvalidAction {});
+    }
+    take_extra_stake(deps.storage, &info, &cfg, &game, game.hands[active].stake)?;
+    limits::check_wager(deps.storage, &env, &info.sender, game.hands[active].stake)?;
+
+    let entropy = util::block_entropy(&env);
+    let card = draw(&mut game, entropy);
+    let hand = &mut game.hands[active];
+    hand.cards.push(card);
+    hand.stake += hand.stake;
+    hand.doubled = true;
+    hand.done = true;
+
+    let needed = max_payout(&game);
+    payout_cap::hold(deps.storage, &env, &info.sender, game.reserved, needed)?;
+    util::adjust_reservation(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone(), &mut game.reserved, needed)?;
+
+    advance(&mut game);
+    if is_finished(&game) {
+        return settle(deps, env, game, vec![]);
+    }
+
+    game.expires_at = env.block.time.seconds() + constants::BLACKJACK_TIMEOUT;
+    BLACKJACK_GAMES.save(deps.storage, &info.sender, &game)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "blackjack_double"),
+        attr("address", info.sender),
+        attr("card", card.to_string()),
+    ]))
+}
+
+pub fn execute_blackjack_split(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let mut game = load_game(&deps, &env, &info.sender)?;
+
+    // A single split of the first two cards when they have the same rank
+    let cards = game.hands[0].cards.clone();
+    if game.hands.len() != 1 || cards.len() != 2 || util::card_rank(cards[0]) != util::card_rank(cards[1]) {
+        return Err(ContractError::InvalidAction {});
+    }
+    let stake = game.hands[0].stake;
+    take_extra_stake(deps.storage, &info, &cfg, &game, stake)?;
+    limits::check_wager(deps.storage, &env, &info.sender, stake)?;
+
+    let entropy = util::block_entropy(&env);
+    let first = draw(&mut game, entropy);
+    let second = draw(&mut game, entropy);
+    game.hands = vec![
+        BlackjackHand { cards: vec![cards[0], first], stake, doubled: false, done: false },
+        BlackjackHand { cards: vec![cards[1], second], stake, doubled: false, done: false },
+    ];
+
+    let needed = max_payout(&game);
+    payout_cap::hold(deps.storage, &env, &info.sender, game.reserved, needed)?;
+    util::adjust_reservation(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone(), &mut game.reserved, needed)?;
+
+    advance(&mut game);
+    if is_finished(&game) {
+        return settle(deps, env, game, vec![]);
+    }
+
+    game.expires_at = env.block.time.seconds() + constants::BLACKJACK_TIMEOUT;
+    BLACKJACK_GAMES.save(deps.storage, &info.sender, &game)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "blackjack_split"),
+        attr("address", info.sender),
+        attr("first", cards_to_string(&game.hands[0].cards)),
+        attr("second", cards_to_string(&game.hands[1].cards)),
+    ]))
+}
+
+pub fn execute_blackjack_timeout(
+    deps: DepsMut,
+    env: Env,
+    address: Addr
+) -> Result<Response, ContractError> {
+
+    let mut game = BLACKJACK_GAMES.may_load(deps.storage, &address)?
+        .ok_or(ContractError::NoActiveGame {})?;
+
+    if env.block.time.seconds() < game.expires_at {
+        return Err(ContractError::GameNotExpired { expires_at: game.expires_at });
+    }
+
+    game.active = game.hands.len() as u8;
+    settle(deps, env, game, vec![])
+}
+
+/// Plays the dealer hand (no hole card, stands on all 17s) and pays every player hand
+fn settle(
+    deps: DepsMut,
+    env: Env,
+    mut game: BlackjackGame,
+    mut messages: Vec<CosmosMsg>
+) -> Result<Response, ContractError> {
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    let entropy = util::block_entropy(&env);
+
+    let natural = game.hands.len() == 1 && is_blackjack(&game.hands[0].cards);
+    let all_bust = game.hands.iter().all(|h| hand_value(&h.cards) > 21);
+    if natural {
+        let card = draw(&mut game, entropy);
+        game.dealer.push(card);
+    } else if !all_bust {
+        while hand_value(&game.dealer) < 17 {
+            let card = draw(&mut game, entropy);
+            game.dealer.push(card);
+        }
+    }
+
+    let dealer_value = hand_value(&game.dealer);
+    let dealer_blackjack = is_blackjack(&game.dealer);
+
+    let mut total_stake = Uint128::zero();
+    let mut gross = Uint128::zero();
+    for hand in game.hands.iter() {
+        let value = hand_value(&hand.cards);
+        total_stake += hand.stake;
+        gross += if value > 21 {
+            Uint128::zero()
+        } else if natural {
+            if dealer_blackjack { hand.stake } else { hand.stake * Uint128::from(5u128) / Uint128::from(2u128) }
+        } else if dealer_blackjack || (value < dealer_value && dealer_value <= 21) {
+            Uint128::zero()
+        } else if value == dealer_value {
+            hand.stake
+        } else {
+            hand.stake * Uint128::from(2u128)
+        };
+    }
+
+    let owner_amount = total_stake * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
+    let reward_amount = gross.saturating_sub(owner_amount);
+
+    let win = if gross > total_stake {
+        Some(0)
+    } else if gross == total_stake {
+        Some(1)
+    } else {
+        Some(2)
+    };
+
+    messages.push(util::fee_message(deps.storage, deps.querier, deps.api, cfg.denom.clone(), &game.address, total_stake, owner_amount)?);
+    let frozen = ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &game.address, reward_amount, game.from_balance, &mut messages)?;
+
+    util::release(deps.storage, game.reserved)?;
+    payout_cap::hold(deps.storage, &env, &game.address, game.reserved, Uint128::zero())?;
+    BLACKJACK_GAMES.remove(deps.storage, &game.address);
+
+    let record = JHistory {
+        id: cfg.blackjack_count + 1,
+        address: game.address.clone(),
+        seed: game.seed,
+        hands: game.hands.clone(),
+        dealer: game.dealer.clone(),
+        win,
+        bet_amount: total_stake,
+        payout: reward_amount,
+        timestamp: env.block.time.seconds()
+    };
+    JHISTORY.save(deps.storage, cfg.blackjack_count, &record)?;
+    let breaker = settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+        player: &game.address,
+        game: "blackjack",
+        count: cfg.blackjack_count,
+        win: record.win,
+        wagered: total_stake,
+        fee: owner_amount,
+        paid: reward_amount,
+        jackpot: Uint128::zero()
+    })?;
+
+    cfg.blackjack_count += 1;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_events(breaker)
+        .add_attributes(vec![
+            attr("action", "blackjack_settle"),
+            attr("address", game.address.clone()),
+            attr("amount", total_stake),
+            attr("dealer", cards_to_string(&game.dealer)),
+            attr("payout", reward_amount),
+            attr("frozen", frozen),
+            attr("win", win.expect("u8").to_string()),
+        ]))
+}
+
+pub fn query_blackjack_game(deps: Deps, address: Addr) -> StdResult<BlackjackGameResponse> {
+    Ok(BlackjackGameResponse {
+        game: BLACKJACK_GAMES.may_load(deps.storage, &address)?
+    })
+}
+
+pub fn query_jhistory(deps: Deps, count: u32) -> StdResult<JHistoryResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let real_count = (cfg.blackjack_count - history::first_retained(deps.storage, "blackjack")?).min(count as u64) as usize;
+
+    let mut list:Vec<JHistory> = vec![];
+    for i in 0..real_count {
+        list.push(JHISTORY.load(deps.storage, cfg.blackjack_count - 1 - i as u64)?);
+    }
+
+    Ok(JHistoryResponse {
+        list
+    })
+}
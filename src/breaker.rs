@@ -0,0 +1,153 @@
+use cosmwasm_std::{
+    attr, Deps, DepsMut, Env, Event, Int128, MessageInfo, Order, QuerierWrapper, Response, StdResult, Storage, Uint128
+};
+use cw_storage_plus::Bound;
+use crate::error::ContractError;
+use crate::msg::{BreakerResponse, BreakerTrip};
+use crate::state::{BREAKER_CONFIG, BREAKER_PNL, BREAKER_TRIP, BreakerConfig, CONFIG};
+use crate::util;
+use crate::constants;
+
+/// Length of one PnL bucket, the window is tracked in `BREAKER_BUCKETS` of them
+fn bucket_length(config: &BreakerConfig) -> u64 {
+    (config.window / constants::BREAKER_BUCKETS).max(1)
+}
+
+/// House PnL over the buckets of the window ending at `now`
+fn window_pnl(storage: &dyn Storage, config: &BreakerConfig, now: u64) -> StdResult<i128> {
+    let current = now / bucket_length(config);
+    let first = current.saturating_sub(constants::BREAKER_BUCKETS - 1);
+    BREAKER_PNL
+        .range(storage, Some(Bound::inclusive(first)), None, Order::Ascending)
+        .map(|item| item.map(|(_, pnl)| pnl.i128()))
+        .sum()
+}
+
+fn clear_pnl(storage: &mut dyn Storage) -> StdResult<()> {
+    let buckets = BREAKER_PNL
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<u64>>>()?;
+    for bucket in buckets {
+        BREAKER_PNL.remove(storage, bucket);
+    }
+    Ok(())
+}
+
+/// Rejects bets while the breaker is tripped
+pub fn check_halted(storage: &dyn Storage) -> Result<(), ContractError> {
+    if let Some(trip) = BREAKER_TRIP.may_load(storage)? {
+        return Err(ContractError::Halted { at: trip.at });
+    }
+    Ok(())
+}
+
+/// Counts a settled bet toward the house PnL of the window, `paid` being everything the bankroll
+/// gave out for it, fee included. Trips the breaker once the window's loss exceeds
+/// `max_drawdown_bps` of the bankroll it started from; the settling bet itself still goes through.
+pub fn record(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+    env: &Env,
+    staked: Uint128,
+    paid: Uint128
+) -> Result<Option<Event>, ContractError> {
+    let config = BREAKER_CONFIG.may_load(storage)?.unwrap_or_default();
+    if config.max_drawdown_bps == 0 || config.window == 0 {
+        return Ok(None);
+    }
+
+    let now = env.block.time.seconds();
+    let current = now / bucket_length(&config);
+
+    // Buckets that left the window are no longer summed
+    let expired = BREAKER_PNL
+        .keys(storage, None, Some(Bound::inclusive(current.saturating_sub(constants::BREAKER_BUCKETS))), Order::Ascending)
+        .collect::<StdResult<Vec<u64>>>()?;
+    for bucket in expired {
+        BREAKER_PNL.remove(storage, bucket);
+    }
+
+    let pnl = BREAKER_PNL.may_load(storage, current)?.unwrap_or_default().i128()
+        + staked.u128() as i128 - paid.u128() as i128;
+    BREAKER_PNL.save(storage, current, &Int128::from(pnl))?;
+
+    if BREAKER_TRIP.may_load(storage)?.is_some() {
+        return Ok(None);
+    }
+
+    let window = window_pnl(storage, &config, now)?;
+    if window >= 0 {
+        return Ok(None);
+    }
+    let loss = Uint128::from(window.unsigned_abs());
+    let cfg = CONFIG.load(storage)?;
+    let bankroll = util::get_bankroll(storage, querier, cfg.denom, env.contract.address.clone())? + loss;
+    if loss * Uint128::from(constants::BPS) <= bankroll * Uint128::from(config.max_drawdown_bps) {
+        return Ok(None);
+    }
+
+    BREAKER_TRIP.save(storage, &BreakerTrip {
+        at: now,
+        loss,
+        bankroll
+    })?;
+
+    Ok(Some(Event::new("circuit_breaker").add_attributes(vec![
+        attr("at", now.to_string()),
+        attr("loss", loss),
+        attr("bankroll", bankroll),
+        attr("max_drawdown_bps", config.max_drawdown_bps.to_string()),
+    ])))
+}
+
+pub fn execute_update_breaker(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_drawdown_bps: u64,
+    window: u64
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    if max_drawdown_bps > constants::BPS || (max_drawdown_bps > 0 && window == 0) {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    // Buckets are sized from the window, so the PnL tracked so far no longer applies
+    clear_pnl(deps.storage)?;
+    BREAKER_CONFIG.save(deps.storage, &BreakerConfig {
+        max_drawdown_bps,
+        window
+    })?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_breaker"),
+        attr("max_drawdown_bps", max_drawdown_bps.to_string()),
+        attr("window", window.to_string()),
+    ]))
+}
+
+/// Resumes betting after a trip with an empty PnL window
+pub fn execute_reset_breaker(
+    deps: DepsMut,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    BREAKER_TRIP.remove(deps.storage);
+    clear_pnl(deps.storage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "reset_breaker"),
+    ]))
+}
+
+pub fn query_breaker(deps: Deps, env: Env) -> StdResult<BreakerResponse> {
+    let config = BREAKER_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let pnl = if config.window == 0 { 0 } else { window_pnl(deps.storage, &config, env.block.time.seconds())? };
+    Ok(BreakerResponse {
+        max_drawdown_bps: config.max_drawdown_bps,
+        window: config.window,
+        pnl: Int128::from(pnl),
+        trip: BREAKER_TRIP.may_load(deps.storage)?
+    })
+}
@@ -4,8 +4,51 @@ pub const THRESHOLD: u64 = 50;
 
 pub const OWNER_RATE: u64 = 50000;
 pub const MULTIPLY: u64 = 1000000;
+pub const BPS: u64 = 10000;
 
 pub const REWARD_RATE: u64 = 2;
 
 pub const MAX_LIMIT: u32 = 30;
 pub const DEFAULT_LIMIT: u32 = 20;
+pub const MAX_PRUNE_LIMIT: u32 = 100;
+pub const HISTORY_MAX_SCAN: u32 = 300;
+
+pub const PLINKO_MIN_ROWS: u8 = 8;
+pub const PLINKO_MAX_ROWS: u8 = 16;
+
+pub const KENO_NUMBERS: u8 = 40;
+pub const KENO_DRAWS: u8 = 10;
+pub const KENO_MAX_PICKS: u8 = 10;
+
+pub const LOTTERY_MAX_TIERS: usize = 10;
+
+pub const DECK_SIZE: u8 = 52;
+pub const BLACKJACK_TIMEOUT: u64 = 3600;
+
+pub const SLOTS_MIN_REELS: usize = 3;
+pub const SLOTS_MAX_REELS: usize = 5;
+pub const SLOTS_MAX_STRIP: usize = 64;
+pub const SLOTS_MAX_ROWS: u8 = 4;
+pub const SLOTS_MAX_PAYLINES: usize = 20;
+
+pub const MINES_MIN_GRID: u8 = 9;
+pub const MINES_MAX_GRID: u8 = 25;
+pub const MINES_TIMEOUT: u64 = 3600;
+
+pub const HILO_HOUSE_EDGE: u64 = 10000;
+pub const HILO_TIMEOUT: u64 = 3600;
+
+pub const WHEEL_MAX_SEGMENTS: usize = 64;
+
+pub const BATCH_MAX_ROUNDS: u32 = 100;
+
+pub const LOYALTY_MAX_TIERS: usize = 10;
+pub const LOYALTY_MAX_POINTS_RATE: u64 = 1000 * MULTIPLY;
+
+pub const LEADERBOARD_SIZE: u32 = 100;
+
+pub const DAY: u64 = 86400;
+pub const WEEK: u64 = 7 * DAY;
+pub const MONTH: u64 = 30 * DAY;
+pub const LIMITS_DELAY: u64 = DAY;
+pub const BREAKER_BUCKETS: u64 = 24;
@@ -1,22 +1,41 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128, CosmosMsg
+    attr, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128, CosmosMsg, Order
 };
 
-use std::hash::{Hash, Hasher};
-use std::collections::hash_map::DefaultHasher;
 use cw2::{get_contract_version, set_contract_version};
 use crate::error::ContractError;
 use crate::msg::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, HashObj, RHistory, RHistoryResponse, FHistory, FHistoryResponse, DHistory, DHistoryResponse, BHistory, BHistoryResponse
+    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, HashObj, RHistory, RHistoryResponse, FHistory, FHistoryResponse, DHistory, DHistoryResponse, BHistory, BHistoryResponse,
+    PHistory, PHistoryResponse, PlinkoRisk, PlinkoTableResponse, KHistory, KHistoryResponse, KenoTableResponse,
+    SHistory, SHistoryResponse, SlotsPay, SlotsConfigResponse,
+    WHistory, WHistoryResponse, WheelSegment, WheelPresetResponse, WheelPresetsResponse
 };
-use cw20::{Balance};
 use crate::state::{
-    Config, CONFIG, RHISTORY, FHISTORY, DHISTORY, BHISTORY
+    Config, CONFIG, RHISTORY, FHISTORY, DHISTORY, BHISTORY, PHISTORY, PLINKO_TABLES, KHISTORY, KENO_TABLES, Jackpot, JACKPOT, SHISTORY, SlotsConfig, SLOTS_CONFIG, WHISTORY, WHEEL_PRESETS
 };
 
 use crate::util;
+use crate::jackpot;
+use crate::lottery;
+use crate::blackjack;
+use crate::mines;
+use crate::hilo;
+use crate::ledger;
+use crate::batch;
+use crate::referral;
+use crate::loyalty;
+use crate::voucher;
+use crate::limits;
+use crate::screening;
+use crate::rate_limit;
+use crate::breaker;
+use crate::payout_cap;
+use crate::stats;
+use crate::settlement::{self, Bet};
+use crate::leaderboard;
+use crate::history;
 use crate::constants;
 // Version info, for migration info
 const CONTRACT_NAME: &str = "bet";
@@ -38,10 +57,20 @@ pub fn instantiate(
         flip_count: 0u64,
         rps_count: 0u64,
         dice_count: 0u64,
-        roulette_count: 0u64
+        roulette_count: 0u64,
+        plinko_count: 0u64,
+        keno_count: 0u64,
+        lottery_count: 0u64,
+        blackjack_count: 0u64,
+        slots_count: 0u64,
+        mines_count: 0u64,
+        hilo_count: 0u64,
+        wheel_count: 0u64,
+        voucher_count: 0u64
     };
     
     CONFIG.save(deps.storage, &config)?;
+    JACKPOT.save(deps.storage, &Jackpot::default())?;
 
     Ok(Response::default())
 }
@@ -56,40 +85,81 @@ pub fn execute(
     match msg {
         ExecuteMsg::UpdateOwner { owner } => util::execute_update_owner(deps.storage, deps.api, info.sender.clone(), owner),
         ExecuteMsg::UpdateEnabled { enabled } => util::execute_update_enabled(deps.storage, deps.api, info.sender.clone(), enabled),
-        ExecuteMsg::Flip { level } => execute_flip(deps, env, info, level),
-        ExecuteMsg::Rps { level } => execute_rps(deps, env, info, level),
-        ExecuteMsg::Dice { level } => execute_dice(deps, env, info, level),
-        ExecuteMsg::Roulette { level } => execute_roulette(deps, env, info, level),
+        ExecuteMsg::Flip { level, from_balance } => execute_flip(deps, env, info, level, from_balance),
+        ExecuteMsg::Rps { level, from_balance } => execute_rps(deps, env, info, level, from_balance),
+        ExecuteMsg::Dice { level, from_balance } => execute_dice(deps, env, info, level, from_balance),
+        ExecuteMsg::Roulette { level, from_balance } => execute_roulette(deps, env, info, level, from_balance),
+        ExecuteMsg::Plinko { rows, risk, from_balance } => execute_plinko(deps, env, info, rows, risk, from_balance),
+        ExecuteMsg::UpdatePlinkoTable { rows, risk, multipliers } => execute_update_plinko_table(deps, info, rows, risk, multipliers),
+        ExecuteMsg::Keno { picks, from_balance } => execute_keno(deps, env, info, picks, from_balance),
+        ExecuteMsg::UpdateKenoTable { picks, multipliers } => execute_update_keno_table(deps, info, picks, multipliers),
+        ExecuteMsg::Slots { from_balance } => execute_slots(deps, env, info, from_balance),
+        ExecuteMsg::UpdateSlots { reels, rows, paylines, paytable } => execute_update_slots(deps, info, reels, rows, paylines, paytable),
+        ExecuteMsg::Wheel { preset, from_balance } => execute_wheel(deps, env, info, preset, from_balance),
+        ExecuteMsg::UpdateWheelPreset { preset, segments } => execute_update_wheel_preset(deps, info, preset, segments),
+        ExecuteMsg::UpdateJackpot { contribution_bps, odds } => jackpot::execute_update_jackpot(deps, info, contribution_bps, odds),
+        ExecuteMsg::SeedJackpot {} => jackpot::execute_seed_jackpot(deps, info),
+        ExecuteMsg::OpenLottery { ticket_price, end_time, prize_tiers } => lottery::execute_open_lottery(deps, env, info, ticket_price, end_time, prize_tiers),
+        ExecuteMsg::BuyTickets { count, from_balance } => lottery::execute_buy_tickets(deps, env, info, count, from_balance),
+        ExecuteMsg::DrawLottery { round_id } => lottery::execute_draw_lottery(deps, env, round_id),
+        ExecuteMsg::BlackjackDeal { from_balance } => blackjack::execute_blackjack_deal(deps, env, info, from_balance),
+        ExecuteMsg::BlackjackHit {} => blackjack::execute_blackjack_hit(deps, env, info),
+        ExecuteMsg::BlackjackStand {} => blackjack::execute_blackjack_stand(deps, env, info),
+        ExecuteMsg::BlackjackDouble {} => blackjack::execute_blackjack_double(deps, env, info),
+        ExecuteMsg::BlackjackSplit {} => blackjack::execute_blackjack_split(deps, env, info),
+        ExecuteMsg::BlackjackTimeout { address } => blackjack::execute_blackjack_timeout(deps, env, address),
+        ExecuteMsg::MinesStart { grid, mines, from_balance } => mines::execute_mines_start(deps, env, info, grid, mines, from_balance),
+        ExecuteMsg::MinesReveal { tile } => mines::execute_mines_reveal(deps, env, info, tile),
+        ExecuteMsg::MinesCashout {} => mines::execute_mines_cashout(deps, env, info),
+        ExecuteMsg::MinesTimeout { address } => mines::execute_mines_timeout(deps, env, address),
+        ExecuteMsg::HiloStart { from_balance } => hilo::execute_hilo_start(deps, env, info, from_balance),
+        ExecuteMsg::HiloGuess { choice } => hilo::execute_hilo_guess(deps, env, info, choice),
+        ExecuteMsg::HiloCashout {} => hilo::execute_hilo_cashout(deps, env, info),
+        ExecuteMsg::HiloTimeout { address } => hilo::execute_hilo_timeout(deps, env, address),
+        ExecuteMsg::BatchBet { game, selection, rounds, stake_per_round, from_balance } => batch::execute_batch_bet(deps, env, info, game, selection, rounds, stake_per_round, from_balance),
+        ExecuteMsg::AutoBet { game, selection, strategy, base_stake, rounds, stop_loss, take_profit, from_balance } => batch::execute_auto_bet(deps, env, info, game, selection, strategy, base_stake, rounds, stop_loss, take_profit, from_balance),
+        ExecuteMsg::SetReferrer { referrer } => referral::execute_set_referrer(deps, env, info, referrer),
+        ExecuteMsg::UpdateReferralShare { share_bps } => referral::execute_update_referral_share(deps, info, share_bps),
+        ExecuteMsg::ClaimReferral {} => referral::execute_claim_referral(deps, info),
+        ExecuteMsg::UpdateLoyalty { points_rate, min_redeem_points, tiers } => loyalty::execute_update_loyalty(deps, info, points_rate, min_redeem_points, tiers),
+        ExecuteMsg::RedeemPoints { points } => loyalty::execute_redeem_points(deps, info, points),
+        ExecuteMsg::IssueVoucher { address, amount, game, expires_at } => voucher::execute_issue_voucher(deps, env, info, address, amount, game, expires_at),
+        ExecuteMsg::RevokeVoucher { voucher_id } => voucher::execute_revoke_voucher(deps, info, voucher_id),
+        ExecuteMsg::VoucherBet { voucher_id, game, selection } => voucher::execute_voucher_bet(deps, env, info, voucher_id, game, selection),
+        ExecuteMsg::SetLimits { limits } => limits::execute_set_limits(deps, env, info, limits),
+        ExecuteMsg::SelfExclude { until } => limits::execute_self_exclude(deps, env, info, until),
+        ExecuteMsg::UpdateBlocklist { add, remove } => screening::execute_update_blocklist(deps, info, add, remove),
+        ExecuteMsg::UpdateScreening { contract } => screening::execute_update_screening(deps, info, contract),
+        ExecuteMsg::UpdateRateLimits { limits } => rate_limit::execute_update_rate_limits(deps, info, limits),
+        ExecuteMsg::PruneRateUsage { start_after, limit } => rate_limit::execute_prune_rate_usage(deps, env, start_after, limit),
+        ExecuteMsg::UpdateBreaker { max_drawdown_bps, window } => breaker::execute_update_breaker(deps, info, max_drawdown_bps, window),
+        ExecuteMsg::ResetBreaker {} => breaker::execute_reset_breaker(deps, info),
+        ExecuteMsg::UpdatePayoutCaps { caps } => payout_cap::execute_update_payout_caps(deps, info, caps),
+        ExecuteMsg::PayLeaderboard { period, kind, index, prizes } => leaderboard::execute_pay_leaderboard(deps, env, info, period, kind, index, prizes),
+        ExecuteMsg::UpdateHistoryRetention { retention } => history::execute_update_history_retention(deps, info, retention),
+        ExecuteMsg::PruneHistory { game, limit } => history::execute_prune_history(deps, env, game, limit),
+        ExecuteMsg::Deposit {} => ledger::execute_deposit(deps, info),
+        ExecuteMsg::WithdrawBalance { amount } => ledger::execute_withdraw_balance(deps, info, amount),
         ExecuteMsg::Withdraw { amount } => execute_withdraw(deps, env, info, amount)     
     }
 }
 
-fn get_winner(p1: u64, p2: u64) -> Option<u8> {
-    match (p1, p2) {
-        (0, 1) => Some(0),
-        (2, 0) => Some(0),
-        (1, 2) => Some(0),
-        (0, 2) => Some(2),
-        (2, 1) => Some(2),
-        (1, 0) => Some(2),
-        _ => Some(1),
-    }
-}
-
 pub fn execute_flip(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    level: u64
+    level: u64,
+    from_balance: Option<Uint128>
 ) -> Result<Response, ContractError> {
 
     util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
 
     let mut cfg = CONFIG.load(deps.storage)?;
 
-    let balance = Balance::from(info.funds);
-
-    let amount = util::get_amount_of_denom(balance, cfg.denom.clone())?;
+    let amount = ledger::take_stake(deps.storage, &info, cfg.denom.clone(), from_balance)?;
+    limits::check_bet(deps.storage, &env, &info.sender, amount)?;
+    rate_limit::check(deps.storage, &env, &info.sender, 1)?;
 
     if level != 0 && level != 1 {
         return Err(ContractError::InvalidBet {});
@@ -103,7 +173,7 @@ pub fn execute_flip(
         count: cfg.flip_count
     };
 
-    let hash = calculate_hash(&obj);
+    let hash = util::calculate_hash(&obj);
     
     let mut win = Some(1);
     
@@ -111,24 +181,27 @@ pub fn execute_flip(
         win = Some(0);
     }
     
-    let mut reward_amount = Uint128::zero();
-
     let owner_amount = amount * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
-    reward_amount = amount * Uint128::from(constants::REWARD_RATE) - owner_amount;
+    let reward_amount = amount * Uint128::from(constants::REWARD_RATE) - owner_amount;
+    payout_cap::check(deps.storage, &env, &info.sender, reward_amount)?;
 
-    let contract_amount = util::get_token_amount_of_address(deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+    let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
 
     if contract_amount < reward_amount {
         win = Some(1);
     }
 
-    let mut messages:Vec<CosmosMsg> = vec![];
-    messages.push(util::transfer_token_message(deps.querier, cfg.denom.clone(), owner_amount, deps.api.addr_validate(constants::TREASURY_ADDR)?)?);
+    let mut messages:Vec<CosmosMsg> = vec![
+        util::fee_message(deps.storage, deps.querier, deps.api, cfg.denom.clone(), &info.sender, amount, owner_amount)?
+    ];
+
+    let jackpot_amount = jackpot::settle_jackpot(deps.storage, &obj, "flip", amount, &info.sender, env.block.time.seconds())?;
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, jackpot_amount, from_balance.is_some(), &mut messages)?;
 
     match win {
         Some(0) => {
             //Player wins            
-            messages.push(util::transfer_token_message(deps.querier, cfg.denom.clone(), reward_amount, info.sender.clone())?);
+            ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, reward_amount, from_balance.is_some(), &mut messages)?;
         }
         Some(1) => {
             //Player Lose
@@ -146,34 +219,49 @@ pub fn execute_flip(
         timestamp: env.block.time.seconds()
     };
     FHISTORY.save(deps.storage, cfg.flip_count, &record)?;
+    let paid = if win == Some(0) { reward_amount } else { Uint128::zero() };
+    let breaker = settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+        player: &info.sender,
+        game: "flip",
+        count: cfg.flip_count,
+        win: record.win,
+        wagered: amount,
+        fee: owner_amount,
+        paid,
+        jackpot: jackpot_amount
+    })?;
 
     cfg.flip_count += 1;
     CONFIG.save(deps.storage, &cfg)?;
-    
-    return Ok(Response::new()
+
+    Ok(Response::new()
         .add_messages(messages)
+        .add_events(breaker)
         .add_attributes(vec![
             attr("action", "flip"),
             attr("address", info.sender.clone()),
             attr("amount", amount),
             attr("win", win.expect("u8").to_string()),
-        ]));
+            attr("jackpot", jackpot_amount),
+        ]))
 }
 
 pub fn execute_rps(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    level: u64
+    level: u64,
+    from_balance: Option<Uint128>
 ) -> Result<Response, ContractError> {
 
     util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
 
     let mut cfg = CONFIG.load(deps.storage)?;
 
-    let balance = Balance::from(info.funds);
-
-    let amount = util::get_amount_of_denom(balance, cfg.denom.clone())?;
+    let amount = ledger::take_stake(deps.storage, &info, cfg.denom.clone(), from_balance)?;
+    limits::check_bet(deps.storage, &env, &info.sender, amount)?;
+    rate_limit::check(deps.storage, &env, &info.sender, 1)?;
 
     if level != 0 && level != 1 && level != 2{
         return Err(ContractError::InvalidBet {});
@@ -187,38 +275,40 @@ pub fn execute_rps(
         count: cfg.rps_count
     };
 
-    let hash = calculate_hash(&obj);
+    let hash = util::calculate_hash(&obj);
     
     let cpu_move = hash % 3;
     
-    let mut reward_amount = Uint128::zero();
-
     let owner_amount = amount * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
-    reward_amount = amount * Uint128::from(constants::REWARD_RATE) - owner_amount;
+    let reward_amount = amount * Uint128::from(constants::REWARD_RATE) - owner_amount;
+    payout_cap::check(deps.storage, &env, &info.sender, reward_amount)?;
 
-    let contract_amount = util::get_token_amount_of_address(deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+    let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
 
-    let mut win = get_winner(level, cpu_move);
+    let mut win = util::get_winner(level, cpu_move);
 
     if contract_amount < reward_amount {
         win = Some(2);
     }
 
-    let mut messages:Vec<CosmosMsg> = vec![];
-    messages.push(util::transfer_token_message(deps.querier, cfg.denom.clone(), owner_amount, deps.api.addr_validate(constants::TREASURY_ADDR)?)?);
+    let mut messages:Vec<CosmosMsg> = vec![
+        util::fee_message(deps.storage, deps.querier, deps.api, cfg.denom.clone(), &info.sender, amount, owner_amount)?
+    ];
+
+    let jackpot_amount = jackpot::settle_jackpot(deps.storage, &obj, "rps", amount, &info.sender, env.block.time.seconds())?;
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, jackpot_amount, from_balance.is_some(), &mut messages)?;
 
     match win {
         Some(0) => {
             //Player wins            
-            messages.push(util::transfer_token_message(deps.querier, cfg.denom.clone(), reward_amount, info.sender.clone())?);
+            ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, reward_amount, from_balance.is_some(), &mut messages)?;
         }
         Some(2) => {
             //Player Lose
         }
         _ => {
             // It's a tie
-            reward_amount = amount - owner_amount;
-            messages.push(util::transfer_token_message(deps.querier, cfg.denom.clone(), reward_amount, info.sender.clone())?);
+            ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, amount - owner_amount, from_balance.is_some(), &mut messages)?;
         }
     }
 
@@ -231,34 +321,53 @@ pub fn execute_rps(
         timestamp: env.block.time.seconds()
     };
     RHISTORY.save(deps.storage, cfg.rps_count, &record)?;
+    let paid = match win {
+        Some(0) => reward_amount,
+        Some(2) => Uint128::zero(),
+        _ => amount - owner_amount
+    };
+    let breaker = settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+        player: &info.sender,
+        game: "rps",
+        count: cfg.rps_count,
+        win: record.win,
+        wagered: amount,
+        fee: owner_amount,
+        paid,
+        jackpot: jackpot_amount
+    })?;
 
     cfg.rps_count += 1;
     CONFIG.save(deps.storage, &cfg)?;
-    
-    return Ok(Response::new()
+
+    Ok(Response::new()
         .add_messages(messages)
+        .add_events(breaker)
         .add_attributes(vec![
             attr("action", "rps"),
             attr("address", info.sender.clone()),
             attr("amount", amount),
             attr("win", win.expect("u8").to_string()),
-        ]));
+            attr("jackpot", jackpot_amount),
+        ]))
 }
 
 pub fn execute_dice(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    level: u64
+    level: u64,
+    from_balance: Option<Uint128>
 ) -> Result<Response, ContractError> {
 
     util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
 
     let mut cfg = CONFIG.load(deps.storage)?;
 
-    let balance = Balance::from(info.funds);
-
-    let amount = util::get_amount_of_denom(balance, cfg.denom.clone())?;
+    let amount = ledger::take_stake(deps.storage, &info, cfg.denom.clone(), from_balance)?;
+    limits::check_bet(deps.storage, &env, &info.sender, amount)?;
+    rate_limit::check(deps.storage, &env, &info.sender, 1)?;
 
     if level != 0 && level != 1 {
         return Err(ContractError::InvalidBet {});
@@ -272,7 +381,7 @@ pub fn execute_dice(
         count: cfg.dice_count
     };
 
-    let mut hash = calculate_hash(&obj) % 6;
+    let mut hash = util::calculate_hash(&obj) % 6;
     
     let mut win = Some(1);
     
@@ -280,25 +389,28 @@ pub fn execute_dice(
         win = Some(0);
     }
     
-    let mut reward_amount = Uint128::zero();
-
     let owner_amount = amount * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
-    reward_amount = amount * Uint128::from(constants::REWARD_RATE) - owner_amount;
+    let reward_amount = amount * Uint128::from(constants::REWARD_RATE) - owner_amount;
+    payout_cap::check(deps.storage, &env, &info.sender, reward_amount)?;
 
-    let contract_amount = util::get_token_amount_of_address(deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+    let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
 
     if contract_amount < reward_amount {
         win = Some(1);
-        hash = hash + 1;
+        hash += 1;
     }
 
-    let mut messages:Vec<CosmosMsg> = vec![];
-    messages.push(util::transfer_token_message(deps.querier, cfg.denom.clone(), owner_amount, deps.api.addr_validate(constants::TREASURY_ADDR)?)?);
+    let mut messages:Vec<CosmosMsg> = vec![
+        util::fee_message(deps.storage, deps.querier, deps.api, cfg.denom.clone(), &info.sender, amount, owner_amount)?
+    ];
+
+    let jackpot_amount = jackpot::settle_jackpot(deps.storage, &obj, "dice", amount, &info.sender, env.block.time.seconds())?;
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, jackpot_amount, from_balance.is_some(), &mut messages)?;
 
     match win {
         Some(0) => {
             //Player wins            
-            messages.push(util::transfer_token_message(deps.querier, cfg.denom.clone(), reward_amount, info.sender.clone())?);
+            ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, reward_amount, from_balance.is_some(), &mut messages)?;
         }
         Some(1) => {
             //Player Lose            
@@ -316,80 +428,54 @@ pub fn execute_dice(
         timestamp: env.block.time.seconds()
     };
     DHISTORY.save(deps.storage, cfg.dice_count, &record)?;
+    let paid = if win == Some(0) { reward_amount } else { Uint128::zero() };
+    let breaker = settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+        player: &info.sender,
+        game: "dice",
+        count: cfg.dice_count,
+        win: record.win,
+        wagered: amount,
+        fee: owner_amount,
+        paid,
+        jackpot: jackpot_amount
+    })?;
 
     cfg.dice_count += 1;
     CONFIG.save(deps.storage, &cfg)?;
-    
-    return Ok(Response::new()
+
+    Ok(Response::new()
         .add_messages(messages)
+        .add_events(breaker)
         .add_attributes(vec![
             attr("action", "dice"),
             attr("address", info.sender.clone()),
             attr("amount", amount),
             attr("win", hash.to_string()),
-        ]));
+            attr("jackpot", jackpot_amount),
+        ]))
 }
 
 pub fn execute_roulette(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    level: u64
+    level: u64,
+    from_balance: Option<Uint128>
 ) -> Result<Response, ContractError> {
 
     util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
 
     let mut cfg = CONFIG.load(deps.storage)?;
 
-    let balance = Balance::from(info.funds);
-
-    let amount = util::get_amount_of_denom(balance, cfg.denom.clone())?;
+    let amount = ledger::take_stake(deps.storage, &info, cfg.denom.clone(), from_balance)?;
+    limits::check_bet(deps.storage, &env, &info.sender, amount)?;
+    rate_limit::check(deps.storage, &env, &info.sender, 1)?;
 
     if level > 48 {
         return Err(ContractError::InvalidBet {});
     }
 
-    let red = [
-    1,
-    3,
-    5,
-    7,
-    9,
-    12,
-    14,
-    16,
-    18,
-    19,
-    21,
-    23,
-    25,
-    27,
-    30,
-    32,
-    34,
-    36
-  ];
-  let black = [
-    2,
-    4,
-    6,
-    8,
-    10,
-    11,
-    13,
-    15,
-    17,
-    20,
-    22,
-    24,
-    26,
-    28,
-    29,
-    31,
-    33,
-    35
-  ];
-
     // Do flip   
     let obj = HashObj {
         time: env.block.time.seconds(),
@@ -398,81 +484,33 @@ pub fn execute_roulette(
         count: cfg.roulette_count
     };
 
-    let mut hash = calculate_hash(&obj) % 37;
+    let hash = util::calculate_hash(&obj) % 37;
     
-    let mut win = Some(1);
-    
-    if level == 37 && ((hash - 1) % 3 == 0)
-    {
-        win = Some(0);
-    }        
-    else if level == 38 && ((hash - 2) % 3 == 0)
-    {
-        win = Some(0);
-    }
-    else if level == 39 && (hash % 3 == 0)
-    {
-        win = Some(0);
-    }
-    else if level == 40 && (hash <= 12 && hash > 0)
-    {
-        win = Some(0);
-    }
-    else if level == 41 && (hash <= 24 && hash > 12)
-    {
-        win = Some(0);
-    }
-    else if level == 42 && (hash <= 36 && hash > 24)
-    {
-        win = Some(0);
-    }
-    else if level == 43 && (hash <= 18 && hash >= 1)
-    {
-        win = Some(0);
-    }
-    else if level == 44 && (hash <= 36 && hash >= 19)
-    {
-        win = Some(0);
-    }
-    else if level == 45 && (hash % 2 == 0)
-    {
-        win = Some(0);
-    }
-    else if level == 46 && (hash % 2 == 1)
-    {
-        win = Some(0);
-    }
-    else if level == 47 && (red.contains(&hash))
-    {
-        win = Some(0);
-    }
-    else if level == 48 && (black.contains(&hash))
-    {
-        win = Some(0);
-    }
-    else if level == hash
-    {
-        win = Some(0);
-    }
-    
-    let mut reward_amount = Uint128::zero();
+    let hit = util::roulette_hit(level, hash);
 
+    let win = if hit { Some(0) } else { Some(1) };
+    
     let owner_amount = amount * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
-    reward_amount = amount * Uint128::from(constants::REWARD_RATE) - owner_amount;
+    let reward_amount = amount * Uint128::from(constants::REWARD_RATE) - owner_amount;
+    payout_cap::check(deps.storage, &env, &info.sender, reward_amount)?;
 
-    let contract_amount = util::get_token_amount_of_address(deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+    let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
 
     if contract_amount < reward_amount {
         return Err(ContractError::InsufficientFunds {});
     }
 
-    let mut messages:Vec<CosmosMsg> = vec![];
-    messages.push(util::transfer_token_message(deps.querier, cfg.denom.clone(), owner_amount, deps.api.addr_validate(constants::TREASURY_ADDR)?)?);
+    let mut messages:Vec<CosmosMsg> = vec![
+        util::fee_message(deps.storage, deps.querier, deps.api, cfg.denom.clone(), &info.sender, amount, owner_amount)?
+    ];
+
+    let jackpot_amount = jackpot::settle_jackpot(deps.storage, &obj, "roulette", amount, &info.sender, env.block.time.seconds())?;
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, jackpot_amount, from_balance.is_some(), &mut messages)?;
 
     match win {
         Some(0) => {
             //Player wins            
-            messages.push(util::transfer_token_message(deps.querier, cfg.denom.clone(), reward_amount, info.sender.clone())?);
+            ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, reward_amount, from_balance.is_some(), &mut messages)?;
         }
         Some(1) => {
             //Player Lose            
@@ -490,18 +528,599 @@ pub fn execute_roulette(
         timestamp: env.block.time.seconds()
     };
     BHISTORY.save(deps.storage, cfg.roulette_count, &record)?;
+    let paid = if win == Some(0) { reward_amount } else { Uint128::zero() };
+    let breaker = settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+        player: &info.sender,
+        game: "roulette",
+        count: cfg.roulette_count,
+        win: record.win,
+        wagered: amount,
+        fee: owner_amount,
+        paid,
+        jackpot: jackpot_amount
+    })?;
 
     cfg.roulette_count += 1;
     CONFIG.save(deps.storage, &cfg)?;
-    
-    return Ok(Response::new()
+
+    Ok(Response::new()
         .add_messages(messages)
+        .add_events(breaker)
         .add_attributes(vec![
             attr("action", "dice"),
             attr("address", info.sender.clone()),
             attr("amount", amount),
             attr("win", hash.to_string()),
-        ]));
+            attr("jackpot", jackpot_amount),
+        ]))
+}
+
+pub fn execute_plinko(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rows: u8,
+    risk: PlinkoRisk,
+    from_balance: Option<Uint128>
+) -> Result<Response, ContractError> {
+
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    let amount = ledger::take_stake(deps.storage, &info, cfg.denom.clone(), from_balance)?;
+    limits::check_bet(deps.storage, &env, &info.sender, amount)?;
+    rate_limit::check(deps.storage, &env, &info.sender, 1)?;
+
+    if !(constants::PLINKO_MIN_ROWS..=constants::PLINKO_MAX_ROWS).contains(&rows) {
+        return Err(ContractError::InvalidBet {});
+    }
+
+    let multipliers = PLINKO_TABLES.may_load(deps.storage, (rows, risk as u8))?
+        .ok_or(ContractError::PlinkoTableNotSet { rows })?;
+
+    let obj = HashObj {
+        time: env.block.time.seconds(),
+        address: info.sender.clone(),
+        level: rows as u64,
+        count: cfg.plinko_count
+    };
+
+    // Each of the low `rows` bits decides one bounce, the slot is the number of right bounces
+    let path = util::calculate_hash(&obj) & ((1u64 << rows) - 1);
+    let slot = path.count_ones() as u8;
+    let multiplier = multipliers[slot as usize];
+
+    let owner_amount = amount * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
+    let max_multiplier = multipliers.iter().copied().max().unwrap_or_default();
+    let max_reward = (amount * Uint128::from(max_multiplier) / Uint128::from(constants::MULTIPLY)).saturating_sub(owner_amount);
+
+    let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+
+    if contract_amount < max_reward {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    payout_cap::check(deps.storage, &env, &info.sender, max_reward)?;
+
+    let reward_amount = (amount * Uint128::from(multiplier) / Uint128::from(constants::MULTIPLY)).saturating_sub(owner_amount);
+    // Slots paying back less than the stake once the fee is taken are losses
+    let win = if reward_amount > amount { Some(0) } else { Some(1) };
+
+    let mut messages:Vec<CosmosMsg> = vec![
+        util::fee_message(deps.storage, deps.querier, deps.api, cfg.denom.clone(), &info.sender, amount, owner_amount)?
+    ];
+
+    let jackpot_amount = jackpot::settle_jackpot(deps.storage, &obj, "plinko", amount, &info.sender, env.block.time.seconds())?;
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, jackpot_amount, from_balance.is_some(), &mut messages)?;
+
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, reward_amount, from_balance.is_some(), &mut messages)?;
+
+    let record = PHistory {
+        id: cfg.plinko_count + 1,
+        address: info.sender.clone(),
+        rows,
+        risk,
+        path,
+        slot,
+        multiplier,
+        win,
+        bet_amount: amount,
+        payout: reward_amount,
+        timestamp: env.block.time.seconds()
+    };
+    PHISTORY.save(deps.storage, cfg.plinko_count, &record)?;
+    let breaker = settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+        player: &info.sender,
+        game: "plinko",
+        count: cfg.plinko_count,
+        win: record.win,
+        wagered: amount,
+        fee: owner_amount,
+        paid: reward_amount,
+        jackpot: jackpot_amount
+    })?;
+
+    cfg.plinko_count += 1;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_events(breaker)
+        .add_attributes(vec![
+            attr("action", "plinko"),
+            attr("address", info.sender.clone()),
+            attr("amount", amount),
+            attr("rows", rows.to_string()),
+            attr("risk", risk.as_str()),
+            attr("slot", slot.to_string()),
+            attr("multiplier", multiplier.to_string()),
+            attr("win", win.expect("u8").to_string()),
+            attr("jackpot", jackpot_amount),
+        ]))
+}
+
+pub fn execute_update_plinko_table(
+    deps: DepsMut,
+    info: MessageInfo,
+    rows: u8,
+    risk: PlinkoRisk,
+    multipliers: Vec<u64>
+) -> Result<Response, ContractError> {
+
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    if !(constants::PLINKO_MIN_ROWS..=constants::PLINKO_MAX_ROWS).contains(&rows) || multipliers.len() != rows as usize + 1 {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    // The table must keep a house edge, i.e. pay back no more than it takes in on average
+    let rtp = util::plinko_rtp(&multipliers);
+    if rtp > constants::MULTIPLY {
+        return Err(ContractError::InvalidRtp { rtp });
+    }
+
+    PLINKO_TABLES.save(deps.storage, (rows, risk as u8), &multipliers)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "update_plinko_table"),
+            attr("rows", rows.to_string()),
+            attr("risk", risk.as_str()),
+            attr("rtp", rtp.to_string()),
+        ]))
+}
+
+pub fn execute_keno(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    picks: Vec<u8>,
+    from_balance: Option<Uint128>
+) -> Result<Response, ContractError> {
+
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    let amount = ledger::take_stake(deps.storage, &info, cfg.denom.clone(), from_balance)?;
+    limits::check_bet(deps.storage, &env, &info.sender, amount)?;
+    rate_limit::check(deps.storage, &env, &info.sender, 1)?;
+
+    if picks.is_empty() || picks.len() > constants::KENO_MAX_PICKS as usize {
+        return Err(ContractError::InvalidBet {});
+    }
+
+    // Picks as a bitmask, which also rejects duplicates and numbers off the board
+    let mut pick_mask = 0u64;
+    for pick in picks.iter() {
+        if *pick == 0 || *pick > constants::KENO_NUMBERS || pick_mask & (1u64 << pick) != 0 {
+            return Err(ContractError::InvalidBet {});
+        }
+        pick_mask |= 1u64 << pick;
+    }
+
+    let multipliers = KENO_TABLES.may_load(deps.storage, picks.len() as u8)?
+        .ok_or(ContractError::KenoTableNotSet { picks: picks.len() as u8 })?;
+
+    let obj = HashObj {
+        time: env.block.time.seconds(),
+        address: info.sender.clone(),
+        level: pick_mask,
+        count: cfg.keno_count
+    };
+
+    let seed = util::calculate_hash(&obj);
+
+    // Partial Fisher-Yates shuffle of the board, one derived hash per draw
+    let mut board: Vec<u8> = (1..=constants::KENO_NUMBERS).collect();
+    for i in 0..constants::KENO_DRAWS as usize {
+        let j = i + (util::calculate_hash(&(seed, i)) % (board.len() - i) as u64) as usize;
+        board.swap(i, j);
+    }
+    let draws = board[..constants::KENO_DRAWS as usize].to_vec();
+    let hits = draws.iter().filter(|d| pick_mask & (1u64 << **d) != 0).count() as u8;
+    let multiplier = multipliers[hits as usize];
+
+    let owner_amount = amount * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
+    let max_multiplier = multipliers.iter().copied().max().unwrap_or_default();
+    let max_reward = (amount * Uint128::from(max_multiplier) / Uint128::from(constants::MULTIPLY)).saturating_sub(owner_amount);
+
+    let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+
+    if contract_amount < max_reward {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    payout_cap::check(deps.storage, &env, &info.sender, max_reward)?;
+
+    let reward_amount = (amount * Uint128::from(multiplier) / Uint128::from(constants::MULTIPLY)).saturating_sub(owner_amount);
+    let win = if reward_amount > amount { Some(0) } else { Some(1) };
+
+    let mut messages:Vec<CosmosMsg> = vec![
+        util::fee_message(deps.storage, deps.querier, deps.api, cfg.denom.clone(), &info.sender, amount, owner_amount)?
+    ];
+
+    let jackpot_amount = jackpot::settle_jackpot(deps.storage, &obj, "keno", amount, &info.sender, env.block.time.seconds())?;
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, jackpot_amount, from_balance.is_some(), &mut messages)?;
+
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, reward_amount, from_balance.is_some(), &mut messages)?;
+
+    let record = KHistory {
+        id: cfg.keno_count + 1,
+        address: info.sender.clone(),
+        picks,
+        draws: draws.clone(),
+        hits,
+        multiplier,
+        win,
+        bet_amount: amount,
+        payout: reward_amount,
+        timestamp: env.block.time.seconds()
+    };
+    KHISTORY.save(deps.storage, cfg.keno_count, &record)?;
+    let breaker = settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+        player: &info.sender,
+        game: "keno",
+        count: cfg.keno_count,
+        win: record.win,
+        wagered: amount,
+        fee: owner_amount,
+        paid: reward_amount,
+        jackpot: jackpot_amount
+    })?;
+
+    cfg.keno_count += 1;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let draws_str = draws.iter().map(|d| d.to_string()).collect::<Vec<String>>().join(",");
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_events(breaker)
+        .add_attributes(vec![
+            attr("action", "keno"),
+            attr("address", info.sender.clone()),
+            attr("amount", amount),
+            attr("draws", draws_str),
+            attr("hits", hits.to_string()),
+            attr("multiplier", multiplier.to_string()),
+            attr("win", win.expect("u8").to_string()),
+            attr("jackpot", jackpot_amount),
+        ]))
+}
+
+pub fn execute_update_keno_table(
+    deps: DepsMut,
+    info: MessageInfo,
+    picks: u8,
+    multipliers: Vec<u64>
+) -> Result<Response, ContractError> {
+
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    if picks == 0 || picks > constants::KENO_MAX_PICKS || multipliers.len() != picks as usize + 1 {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let rtp = util::keno_rtp(&multipliers);
+    if rtp > constants::MULTIPLY {
+        return Err(ContractError::InvalidRtp { rtp });
+    }
+
+    KENO_TABLES.save(deps.storage, picks, &multipliers)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "update_keno_table"),
+            attr("picks", picks.to_string()),
+            attr("rtp", rtp.to_string()),
+        ]))
+}
+
+pub fn execute_slots(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    from_balance: Option<Uint128>
+) -> Result<Response, ContractError> {
+
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    let amount = ledger::take_stake(deps.storage, &info, cfg.denom.clone(), from_balance)?;
+    limits::check_bet(deps.storage, &env, &info.sender, amount)?;
+    rate_limit::check(deps.storage, &env, &info.sender, 1)?;
+
+    let slots = SLOTS_CONFIG.may_load(deps.storage)?
+        .ok_or(ContractError::SlotsNotSet {})?;
+
+    let obj = HashObj {
+        time: env.block.time.seconds(),
+        address: info.sender.clone(),
+        level: slots.paylines.len() as u64,
+        count: cfg.slots_count
+    };
+
+    let seed = util::calculate_hash(&obj);
+    let stops: Vec<u64> = slots.reels.iter().enumerate()
+        .map(|(i, reel)| util::calculate_hash(&(seed, i)) % reel.len() as u64)
+        .collect();
+
+    // The stake is split evenly over the paylines
+    let mut line_total = 0u64;
+    for line in slots.paylines.iter() {
+        let symbols: Vec<u8> = line.iter().enumerate()
+            .map(|(i, row)| {
+                let reel = &slots.reels[i];
+                reel[((stops[i] + *row as u64) % reel.len() as u64) as usize]
+            })
+            .collect();
+        let (symbol, count) = util::slots_line(&symbols);
+        line_total += util::slots_pay(&slots.paytable, symbol, count);
+    }
+    let multiplier = line_total / slots.paylines.len() as u64;
+
+    let owner_amount = amount * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
+    let max_multiplier = slots.paytable.iter().map(|p| p.multiplier).max().unwrap_or_default();
+    let max_reward = (amount * Uint128::from(max_multiplier) / Uint128::from(constants::MULTIPLY)).saturating_sub(owner_amount);
+
+    let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+
+    if contract_amount < max_reward {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    payout_cap::check(deps.storage, &env, &info.sender, max_reward)?;
+
+    let reward_amount = (amount * Uint128::from(multiplier) / Uint128::from(constants::MULTIPLY)).saturating_sub(owner_amount);
+    let win = if reward_amount > amount { Some(0) } else { Some(1) };
+
+    let mut messages:Vec<CosmosMsg> = vec![
+        util::fee_message(deps.storage, deps.querier, deps.api, cfg.denom.clone(), &info.sender, amount, owner_amount)?
+    ];
+
+    let jackpot_amount = jackpot::settle_jackpot(deps.storage, &obj, "slots", amount, &info.sender, env.block.time.seconds())?;
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, jackpot_amount, from_balance.is_some(), &mut messages)?;
+
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, reward_amount, from_balance.is_some(), &mut messages)?;
+
+    let stops_str = stops.iter().map(|s| s.to_string()).collect::<Vec<String>>().join(",");
+
+    let record = SHistory {
+        id: cfg.slots_count + 1,
+        address: info.sender.clone(),
+        stops,
+        multiplier,
+        win,
+        bet_amount: amount,
+        payout: reward_amount,
+        timestamp: env.block.time.seconds()
+    };
+    SHISTORY.save(deps.storage, cfg.slots_count, &record)?;
+    let breaker = settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+        player: &info.sender,
+        game: "slots",
+        count: cfg.slots_count,
+        win: record.win,
+        wagered: amount,
+        fee: owner_amount,
+        paid: reward_amount,
+        jackpot: jackpot_amount
+    })?;
+
+    cfg.slots_count += 1;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_events(breaker)
+        .add_attributes(vec![
+            attr("action", "slots"),
+            attr("address", info.sender.clone()),
+            attr("amount", amount),
+            attr("stops", stops_str),
+            attr("multiplier", multiplier.to_string()),
+            attr("win", win.expect("u8").to_string()),
+            attr("jackpot", jackpot_amount),
+        ]))
+}
+
+pub fn execute_update_slots(
+    deps: DepsMut,
+    info: MessageInfo,
+    reels: Vec<Vec<u8>>,
+    rows: u8,
+    paylines: Vec<Vec<u8>>,
+    paytable: Vec<SlotsPay>
+) -> Result<Response, ContractError> {
+
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    if reels.len() < constants::SLOTS_MIN_REELS
+        || reels.len() > constants::SLOTS_MAX_REELS
+        || reels.iter().any(|r| r.is_empty() || r.len() > constants::SLOTS_MAX_STRIP)
+        || rows == 0
+        || rows > constants::SLOTS_MAX_ROWS
+        || paylines.is_empty()
+        || paylines.len() > constants::SLOTS_MAX_PAYLINES
+        || paylines.iter().any(|l| l.len() != reels.len() || l.iter().any(|row| *row >= rows))
+        || paytable.iter().any(|p| p.count == 0 || p.count as usize > reels.len())
+    {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let rtp = util::slots_rtp(&reels, &paytable);
+    if rtp > constants::MULTIPLY {
+        return Err(ContractError::InvalidRtp { rtp });
+    }
+
+    SLOTS_CONFIG.save(deps.storage, &SlotsConfig {
+        reels,
+        rows,
+        paylines,
+        paytable
+    })?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "update_slots"),
+            attr("rtp", rtp.to_string()),
+        ]))
+}
+
+pub fn execute_wheel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    preset: u8,
+    from_balance: Option<Uint128>
+) -> Result<Response, ContractError> {
+
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    let amount = ledger::take_stake(deps.storage, &info, cfg.denom.clone(), from_balance)?;
+    limits::check_bet(deps.storage, &env, &info.sender, amount)?;
+    rate_limit::check(deps.storage, &env, &info.sender, 1)?;
+
+    let segments = WHEEL_PRESETS.may_load(deps.storage, preset)?
+        .ok_or(ContractError::WheelPresetNotSet { preset })?;
+
+    let obj = HashObj {
+        time: env.block.time.seconds(),
+        address: info.sender.clone(),
+        level: preset as u64,
+        count: cfg.wheel_count
+    };
+
+    // Walk the segments until the cumulative weight passes the drawn point
+    let total_weight: u64 = segments.iter().map(|s| s.weight).sum();
+    let mut point = util::calculate_hash(&obj) % total_weight;
+    let mut segment = 0usize;
+    while point >= segments[segment].weight {
+        point -= segments[segment].weight;
+        segment += 1;
+    }
+    let multiplier = segments[segment].multiplier;
+
+    let owner_amount = amount * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
+    let max_multiplier = segments.iter().map(|s| s.multiplier).max().unwrap_or_default();
+    let max_reward = (amount * Uint128::from(max_multiplier) / Uint128::from(constants::MULTIPLY)).saturating_sub(owner_amount);
+
+    let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+
+    if contract_amount < max_reward {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    payout_cap::check(deps.storage, &env, &info.sender, max_reward)?;
+
+    let reward_amount = (amount * Uint128::from(multiplier) / Uint128::from(constants::MULTIPLY)).saturating_sub(owner_amount);
+    let win = if reward_amount > amount { Some(0) } else { Some(1) };
+
+    let mut messages:Vec<CosmosMsg> = vec![
+        util::fee_message(deps.storage, deps.querier, deps.api, cfg.denom.clone(), &info.sender, amount, owner_amount)?
+    ];
+
+    let jackpot_amount = jackpot::settle_jackpot(deps.storage, &obj, "wheel", amount, &info.sender, env.block.time.seconds())?;
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, jackpot_amount, from_balance.is_some(), &mut messages)?;
+
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, reward_amount, from_balance.is_some(), &mut messages)?;
+
+    let record = WHistory {
+        id: cfg.wheel_count + 1,
+        address: info.sender.clone(),
+        preset,
+        segment: segment as u8,
+        multiplier,
+        win,
+        bet_amount: amount,
+        payout: reward_amount,
+        timestamp: env.block.time.seconds()
+    };
+    WHISTORY.save(deps.storage, cfg.wheel_count, &record)?;
+    let breaker = settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+        player: &info.sender,
+        game: "wheel",
+        count: cfg.wheel_count,
+        win: record.win,
+        wagered: amount,
+        fee: owner_amount,
+        paid: reward_amount,
+        jackpot: jackpot_amount
+    })?;
+
+    cfg.wheel_count += 1;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_events(breaker)
+        .add_attributes(vec![
+            attr("action", "wheel"),
+            attr("address", info.sender.clone()),
+            attr("amount", amount),
+            attr("preset", preset.to_string()),
+            attr("segment", segment.to_string()),
+            attr("multiplier", multiplier.to_string()),
+            attr("win", win.expect("u8").to_string()),
+            attr("jackpot", jackpot_amount),
+        ]))
+}
+
+pub fn execute_update_wheel_preset(
+    deps: DepsMut,
+    info: MessageInfo,
+    preset: u8,
+    segments: Vec<WheelSegment>
+) -> Result<Response, ContractError> {
+
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    if segments.is_empty()
+        || segments.len() > constants::WHEEL_MAX_SEGMENTS
+        || segments.iter().any(|s| s.weight == 0)
+        || segments.iter().map(|s| s.weight as u128).sum::<u128>() > u64::MAX as u128
+    {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let rtp = util::wheel_rtp(&segments);
+    if rtp > constants::MULTIPLY {
+        return Err(ContractError::InvalidRtp { rtp });
+    }
+
+    WHEEL_PRESETS.save(deps.storage, preset, &segments)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "update_wheel_preset"),
+            attr("preset", preset.to_string()),
+            attr("rtp", rtp.to_string()),
+        ]))
 }
 
 pub fn execute_withdraw(
@@ -515,29 +1134,23 @@ pub fn execute_withdraw(
 
     let cfg = CONFIG.load(deps.storage)?;
     
-    let contract_amount = util::get_token_amount_of_address(deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+    let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
 
     if contract_amount < amount {
         return Err(ContractError::NotEnoughCoins {contract_amount });
     }
 
-    let mut messages:Vec<CosmosMsg> = vec![];
-    messages.push(util::transfer_token_message(deps.querier, cfg.denom.clone(), amount, info.sender.clone())?);
+    let messages:Vec<CosmosMsg> = vec![
+        util::transfer_token_message(deps.querier, cfg.denom.clone(), amount, info.sender.clone())?
+    ];
 
-    
-    return Ok(Response::new()
+    Ok(Response::new()
         .add_messages(messages)
         .add_attributes(vec![
             attr("action", "withdraw"),
             attr("address", info.sender.clone()),
             attr("amount", amount),
-        ]));
-}
-
-fn calculate_hash<T: Hash>(t: &T) -> u64 {
-    let mut s = DefaultHasher::new();
-    t.hash(&mut s);
-    s.finish()
+        ]))
 }
 
 
@@ -545,11 +1158,56 @@ fn calculate_hash<T: Hash>(t: &T) -> u64 {
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} 
-            => to_binary(&query_config(deps, env)?),
-        QueryMsg::RistoryMsg {count} => to_binary(&query_rhistory(deps, count)?),
-        QueryMsg::FistoryMsg {count} => to_binary(&query_fhistory(deps, count)?),
-        QueryMsg::DistoryMsg {count} => to_binary(&query_dhistory(deps, count)?),
-        QueryMsg::BistoryMsg {count} => to_binary(&query_bhistory(deps, count)?),
+            => to_json_binary(&query_config(deps, env)?),
+        QueryMsg::RistoryMsg {count} => to_json_binary(&query_rhistory(deps, count)?),
+        QueryMsg::FistoryMsg {count} => to_json_binary(&query_fhistory(deps, count)?),
+        QueryMsg::DistoryMsg {count} => to_json_binary(&query_dhistory(deps, count)?),
+        QueryMsg::BistoryMsg {count} => to_json_binary(&query_bhistory(deps, count)?),
+        QueryMsg::PistoryMsg {count} => to_json_binary(&query_phistory(deps, count)?),
+        QueryMsg::PlinkoTable {rows, risk} => to_json_binary(&query_plinko_table(deps, rows, risk)?),
+        QueryMsg::KistoryMsg {count} => to_json_binary(&query_khistory(deps, count)?),
+        QueryMsg::KenoTable {picks} => to_json_binary(&query_keno_table(deps, picks)?),
+        QueryMsg::SistoryMsg {count} => to_json_binary(&query_shistory(deps, count)?),
+        QueryMsg::SlotsConfig {} => to_json_binary(&query_slots_config(deps)?),
+        QueryMsg::WistoryMsg {count} => to_json_binary(&query_whistory(deps, count)?),
+        QueryMsg::WheelPreset {preset} => to_json_binary(&query_wheel_preset(deps, preset)?),
+        QueryMsg::WheelPresets {} => to_json_binary(&query_wheel_presets(deps)?),
+        QueryMsg::Balance {address} => to_json_binary(&ledger::query_balance(deps, address)?),
+        QueryMsg::Balances {start_after, limit} => to_json_binary(&ledger::query_balances(deps, start_after, limit)?),
+        QueryMsg::ReferralConfig {} => to_json_binary(&referral::query_referral_config(deps)?),
+        QueryMsg::Referrer {address} => to_json_binary(&referral::query_referrer(deps, address)?),
+        QueryMsg::Referrals {referrer, start_after, limit} => to_json_binary(&referral::query_referrals(deps, referrer, start_after, limit)?),
+        QueryMsg::ReferrerEarnings {address} => to_json_binary(&referral::query_referrer_earnings(deps, address)?),
+        QueryMsg::TopReferrers {limit} => to_json_binary(&referral::query_top_referrers(deps, limit)?),
+        QueryMsg::LoyaltyConfig {} => to_json_binary(&loyalty::query_loyalty_config(deps)?),
+        QueryMsg::Loyalty {address} => to_json_binary(&loyalty::query_loyalty(deps, address)?),
+        QueryMsg::Vouchers {address, start_after, limit} => to_json_binary(&voucher::query_vouchers(deps, address, start_after, limit)?),
+        QueryMsg::Limits {address} => to_json_binary(&limits::query_limits(deps, env, address)?),
+        QueryMsg::IsBlocked {address} => to_json_binary(&screening::query_is_blocked(deps, address)?),
+        QueryMsg::Blocklist {start_after, limit} => to_json_binary(&screening::query_blocklist(deps, start_after, limit)?),
+        QueryMsg::ScreeningConfig {} => to_json_binary(&screening::query_screening_config(deps)?),
+        QueryMsg::RateLimits {} => to_json_binary(&rate_limit::query_rate_limits(deps)?),
+        QueryMsg::RateUsage {address} => to_json_binary(&rate_limit::query_rate_usage(deps, env, address)?),
+        QueryMsg::Breaker {} => to_json_binary(&breaker::query_breaker(deps, env)?),
+        QueryMsg::PayoutCaps {} => to_json_binary(&payout_cap::query_payout_caps(deps)?),
+        QueryMsg::PayoutAllowance { address } => to_json_binary(&payout_cap::query_payout_allowance(deps, env, address)?),
+        QueryMsg::Stats { game } => to_json_binary(&stats::query_stats(deps, game)?),
+        QueryMsg::PlayerStats { address } => to_json_binary(&stats::query_player_stats(deps, address)?),
+        QueryMsg::Leaderboard { period, kind, index, limit } => to_json_binary(&leaderboard::query_leaderboard(deps, env, period, kind, index, limit)?),
+        QueryMsg::FilteredHistory { game, filter, start_after, limit } => history::query_filtered_history(deps, game, filter, start_after, limit),
+        QueryMsg::HistoryRetention {} => to_json_binary(&history::query_history_retention(deps)?),
+        QueryMsg::OldestHistory { game } => to_json_binary(&history::query_oldest_history(deps, game)?),
+        QueryMsg::Jackpot {} => to_json_binary(&jackpot::query_jackpot(deps)?),
+        QueryMsg::JackpotWinners {count} => to_json_binary(&jackpot::query_jackpot_winners(deps, count)?),
+        QueryMsg::LotteryRound {round_id} => to_json_binary(&lottery::query_lottery_round(deps, round_id)?),
+        QueryMsg::LotteryRounds {count} => to_json_binary(&lottery::query_lottery_rounds(deps, count)?),
+        QueryMsg::LotteryTickets {round_id, address} => to_json_binary(&lottery::query_lottery_tickets(deps, round_id, address)?),
+        QueryMsg::JistoryMsg {count} => to_json_binary(&blackjack::query_jhistory(deps, count)?),
+        QueryMsg::BlackjackGame {address} => to_json_binary(&blackjack::query_blackjack_game(deps, address)?),
+        QueryMsg::MistoryMsg {count} => to_json_binary(&mines::query_mhistory(deps, count)?),
+        QueryMsg::MinesGame {address} => to_json_binary(&mines::query_mines_game(deps, address)?),
+        QueryMsg::HiloHistory {count} => to_json_binary(&hilo::query_hhistory(deps, count)?),
+        QueryMsg::HiloGame {address} => to_json_binary(&hilo::query_hilo_game(deps, address)?),
     }
 }
 
@@ -565,6 +1223,15 @@ pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
         rps_count: cfg.rps_count,
         dice_count: cfg.dice_count,
         roulette_count: cfg.roulette_count,
+        plinko_count: cfg.plinko_count,
+        keno_count: cfg.keno_count,
+        lottery_count: cfg.lottery_count,
+        blackjack_count: cfg.blackjack_count,
+        slots_count: cfg.slots_count,
+        mines_count: cfg.mines_count,
+        hilo_count: cfg.hilo_count,
+        wheel_count: cfg.wheel_count,
+        voucher_count: cfg.voucher_count,
     })
 }
 
@@ -574,7 +1241,7 @@ fn query_rhistory(
 ) -> StdResult<RHistoryResponse> {
     let cfg = CONFIG.load(deps.storage)?;
 
-    let real_count = cfg.rps_count.min(count as u64) as usize;
+    let real_count = (cfg.rps_count - history::first_retained(deps.storage, "rps")?).min(count as u64) as usize;
 
     let mut list:Vec<RHistory> = vec![];
     for i in 0..real_count {
@@ -593,7 +1260,7 @@ fn query_fhistory(
 ) -> StdResult<FHistoryResponse> {
     let cfg = CONFIG.load(deps.storage)?;
 
-    let real_count = cfg.flip_count.min(count as u64) as usize;
+    let real_count = (cfg.flip_count - history::first_retained(deps.storage, "flip")?).min(count as u64) as usize;
 
     let mut list:Vec<FHistory> = vec![];
     for i in 0..real_count {
@@ -612,7 +1279,7 @@ fn query_dhistory(
 ) -> StdResult<DHistoryResponse> {
     let cfg = CONFIG.load(deps.storage)?;
 
-    let real_count = cfg.dice_count.min(count as u64) as usize;
+    let real_count = (cfg.dice_count - history::first_retained(deps.storage, "dice")?).min(count as u64) as usize;
 
     let mut list:Vec<DHistory> = vec![];
     for i in 0..real_count {
@@ -631,7 +1298,7 @@ fn query_bhistory(
 ) -> StdResult<BHistoryResponse> {
     let cfg = CONFIG.load(deps.storage)?;
 
-    let real_count = cfg.roulette_count.min(count as u64) as usize;
+    let real_count = (cfg.roulette_count - history::first_retained(deps.storage, "roulette")?).min(count as u64) as usize;
 
     let mut list:Vec<BHistory> = vec![];
     for i in 0..real_count {
@@ -644,6 +1311,161 @@ fn query_bhistory(
     
 }
 
+fn query_phistory(
+    deps: Deps,
+    count: u32
+) -> StdResult<PHistoryResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let real_count = (cfg.plinko_count - history::first_retained(deps.storage, "plinko")?).min(count as u64) as usize;
+
+    let mut list:Vec<PHistory> = vec![];
+    for i in 0..real_count {
+        list.push(PHISTORY.load(deps.storage, cfg.plinko_count - 1 - i as u64)?);
+    }
+    
+    Ok(PHistoryResponse {
+        list
+    })
+    
+}
+
+fn query_plinko_table(
+    deps: Deps,
+    rows: u8,
+    risk: PlinkoRisk
+) -> StdResult<PlinkoTableResponse> {
+    let multipliers = PLINKO_TABLES.load(deps.storage, (rows, risk as u8))?;
+    let rtp = util::plinko_rtp(&multipliers);
+
+    Ok(PlinkoTableResponse {
+        rows,
+        risk,
+        multipliers,
+        rtp
+    })
+}
+
+fn query_khistory(
+    deps: Deps,
+    count: u32
+) -> StdResult<KHistoryResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let real_count = (cfg.keno_count - history::first_retained(deps.storage, "keno")?).min(count as u64) as usize;
+
+    let mut list:Vec<KHistory> = vec![];
+    for i in 0..real_count {
+        list.push(KHISTORY.load(deps.storage, cfg.keno_count - 1 - i as u64)?);
+    }
+    
+    Ok(KHistoryResponse {
+        list
+    })
+    
+}
+
+fn query_keno_table(
+    deps: Deps,
+    picks: u8
+) -> StdResult<KenoTableResponse> {
+    let multipliers = KENO_TABLES.load(deps.storage, picks)?;
+    let rtp = util::keno_rtp(&multipliers);
+
+    Ok(KenoTableResponse {
+        picks,
+        multipliers,
+        rtp
+    })
+}
+
+fn query_shistory(
+    deps: Deps,
+    count: u32
+) -> StdResult<SHistoryResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let real_count = (cfg.slots_count - history::first_retained(deps.storage, "slots")?).min(count as u64) as usize;
+
+    let mut list:Vec<SHistory> = vec![];
+    for i in 0..real_count {
+        list.push(SHISTORY.load(deps.storage, cfg.slots_count - 1 - i as u64)?);
+    }
+    
+    Ok(SHistoryResponse {
+        list
+    })
+    
+}
+
+fn query_slots_config(
+    deps: Deps
+) -> StdResult<SlotsConfigResponse> {
+    let slots = SLOTS_CONFIG.load(deps.storage)?;
+    let rtp = util::slots_rtp(&slots.reels, &slots.paytable);
+
+    Ok(SlotsConfigResponse {
+        reels: slots.reels,
+        rows: slots.rows,
+        paylines: slots.paylines,
+        paytable: slots.paytable,
+        rtp
+    })
+}
+
+fn query_whistory(
+    deps: Deps,
+    count: u32
+) -> StdResult<WHistoryResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let real_count = (cfg.wheel_count - history::first_retained(deps.storage, "wheel")?).min(count as u64) as usize;
+
+    let mut list:Vec<WHistory> = vec![];
+    for i in 0..real_count {
+        list.push(WHISTORY.load(deps.storage, cfg.wheel_count - 1 - i as u64)?);
+    }
+    
+    Ok(WHistoryResponse {
+        list
+    })
+    
+}
+
+fn query_wheel_preset(
+    deps: Deps,
+    preset: u8
+) -> StdResult<WheelPresetResponse> {
+    let segments = WHEEL_PRESETS.load(deps.storage, preset)?;
+    let rtp = util::wheel_rtp(&segments);
+
+    Ok(WheelPresetResponse {
+        preset,
+        segments,
+        rtp
+    })
+}
+
+fn query_wheel_presets(
+    deps: Deps
+) -> StdResult<WheelPresetsResponse> {
+    let list = WHEEL_PRESETS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(preset, segments)| {
+            let rtp = util::wheel_rtp(&segments);
+            WheelPresetResponse {
+                preset,
+                segments,
+                rtp
+            }
+        }))
+        .collect::<StdResult<Vec<WheelPresetResponse>>>()?;
+
+    Ok(WheelPresetsResponse {
+        list
+    })
+}
+
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
@@ -52,6 +52,120 @@ pub enum ContractError {
     #[error("Cannot migrate from different contract type: {previous_contract}")]
     CannotMigrate { previous_contract: String },
 
+    #[error("Plinko table not set for {rows} rows")]
+    PlinkoTableNotSet { rows: u8 },
+
+    #[error("Keno table not set for {picks} picks")]
+    KenoTableNotSet { picks: u8 },
+
+    #[error("Slots not configured")]
+    SlotsNotSet {},
+
+    #[error("Wheel preset {preset} not set")]
+    WheelPresetNotSet { preset: u8 },
+
+    #[error("Invalid RTP : {rtp}")]
+    InvalidRtp { rtp: u64 },
+
+    #[error("Lottery round {round_id} is still open")]
+    LotteryRoundOpen { round_id: u64 },
+
+    #[error("Lottery closed")]
+    LotteryClosed {},
+
+    #[error("Lottery not ended : ends at {end_time}")]
+    LotteryNotEnded { end_time: u64 },
+
+    #[error("Lottery already drawn")]
+    LotteryAlreadyDrawn {},
+
+    #[error("Ticket payment mismatch : expected {expected}")]
+    TicketPaymentMismatch { expected: Uint128 },
+
+    #[error("Stake mismatch : expected {expected}")]
+    StakeMismatch { expected: Uint128 },
+
+    #[error("Game in progress")]
+    GameInProgress {},
+
+    #[error("No active game")]
+    NoActiveGame {},
+
+    #[error("Game expired")]
+    GameExpired {},
+
+    #[error("Game not expired : expires at {expires_at}")]
+    GameNotExpired { expires_at: u64 },
+
+    #[error("Mine layout does not match the game commitment")]
+    CommitmentMismatch {},
+
+    #[error("InvalidAction")]
+    InvalidAction {},
+
+    #[error("Insufficient balance : {balance}")]
+    InsufficientBalance { balance: Uint128 },
+
+    #[error("Referrer already set")]
+    ReferrerAlreadySet {},
+
+    #[error("Invalid referrer")]
+    InvalidReferrer {},
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+
+    #[error("Not enough points : at least {min} needed")]
+    NotEnoughPoints { min: Uint128 },
+
+    #[error("Voucher {voucher_id} not found")]
+    VoucherNotFound { voucher_id: u64 },
+
+    #[error("Voucher expired")]
+    VoucherExpired {},
+
+    #[error("Voucher not valid for this game")]
+    VoucherGameMismatch {},
+
+    #[error("Self-excluded until {until}")]
+    SelfExcluded { until: u64 },
+
+    #[error("Cooldown active until {until}")]
+    CooldownActive { until: u64 },
+
+    #[error("{period} wager limit of {limit} exceeded")]
+    WagerLimitExceeded { period: String, limit: Uint128 },
+
+    #[error("{period} loss limit of {limit} exceeded")]
+    LossLimitExceeded { period: String, limit: Uint128 },
+
+    #[error("Address blocked : {address}")]
+    AddressBlocked { address: String },
+
+    #[error("Too many bets from this address in this block")]
+    BlockRateLimited {},
+
+    #[error("Too many bets from this address : wait until {until}")]
+    AddressRateLimited { until: u64 },
+
+    #[error("Too many bets in this block")]
+    GlobalRateLimited {},
+
+    #[error("Betting halted by the circuit breaker at {at}")]
+    Halted { at: u64 },
+
+    #[error("Daily payout cap of this address reached : at most {remaining} can still be paid")]
+    PlayerPayoutCapExceeded { remaining: Uint128 },
+
+    #[error("Daily payout cap reached : at most {remaining} can still be paid")]
+    GlobalPayoutCapExceeded { remaining: Uint128 },
+
+    #[error("Leaderboard prizes already paid")]
+    LeaderboardAlreadyPaid {},
+
+    #[error("Multiplier overflow : cash out instead")]
+    MultiplierOverflow {},
+
     #[error("Count {count}")]
     Count { count: u64 },
 }
@@ -0,0 +1,277 @@
+use cosmwasm_std::{
+    attr, Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128
+};
+use std::convert::TryFrom;
+use crate::error::ContractError;
+use crate::msg::{HashObj, HHistory, HHistoryResponse, HiloChoice, HiloGame, HiloGameResponse};
+use crate::state::{CONFIG, HHISTORY, HILO_GAMES};
+use crate::util;
+use crate::screening;
+use crate::jackpot;
+use crate::ledger;
+use crate::limits;
+use crate::rate_limit;
+use crate::payout_cap;
+use crate::settlement::{self, Bet};
+use crate::history;
+use crate::constants;
+
+/// Multiplier applied to the pending payout when `choice` is right, scaled by `MULTIPLY`.
+/// None when no card left in the deck can win.
+fn guess_factor(cards: &[u8], choice: HiloChoice) -> Option<u64> {
+    let rank = util::card_rank(*cards.last()?);
+    let remaining: Vec<u8> = (0..constants::DECK_SIZE).filter(|c| !cards.contains(c)).collect();
+    let winning = remaining.iter()
+        .filter(|c| match choice {
+            HiloChoice::Higher => util::card_rank(**c) >= rank,
+            HiloChoice::Lower => util::card_rank(**c) <= rank,
+        })
+        .count() as u128;
+    if winning == 0 {
+        return None;
+    }
+    let fair = constants::MULTIPLY as u128 * remaining.len() as u128 / winning;
+    Some((fair * (constants::MULTIPLY - constants::HILO_HOUSE_EDGE) as u128 / constants::MULTIPLY as u128) as u64)
+}
+
+fn load_game(deps: &DepsMut, env: &Env, address: &Addr) -> Result<HiloGame, ContractError> {
+    let game = HILO_GAMES.may_load(deps.storage, address)?
+        .ok_or(ContractError::NoActiveGame {})?;
+    if env.block.time.seconds() >= game.expires_at {
+        return Err(ContractError::GameExpired {});
+    }
+    Ok(game)
+}
+
+pub fn execute_hilo_start(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    from_balance: Option<Uint128>
+) -> Result<Response, ContractError> {
+
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+
+    if HILO_GAMES.has(deps.storage, &info.sender) {
+        return Err(ContractError::GameInProgress {});
+    }
+
+    let amount = ledger::take_stake(deps.storage, &info, cfg.denom.clone(), from_balance)?;
+    limits::check_bet(deps.storage, &env, &info.sender, amount)?;
+    rate_limit::check(deps.storage, &env, &info.sender, 1)?;
+    payout_cap::check(deps.storage, &env, &info.sender, amount)?;
+
+    let obj = HashObj {
+        time: env.block.time.seconds(),
+        address: info.sender.clone(),
+        level: 0,
+        count: cfg.hilo_count
+    };
+    let seed = util::calculate_hash(&obj);
+    let card = util::draw_card(seed, &[], util::block_entropy(&env));
+
+    let game = HiloGame {
+        address: info.sender.clone(),
+        seed,
+        cards: vec![card],
+        guesses: vec![],
+        multiplier: constants::MULTIPLY,
+        bet_amount: amount,
+        reserved: Uint128::zero(),
+        from_balance: from_balance.is_some(),
+        expires_at: env.block.time.seconds() + constants::HILO_TIMEOUT,
+        timestamp: env.block.time.seconds()
+    };
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    let jackpot_amount = jackpot::settle_jackpot(deps.storage, &obj, "hilo", amount, &info.sender, env.block.time.seconds())?;
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, jackpot_amount, from_balance.is_some(), &mut messages)?;
+    limits::record_return(deps.storage, &env, &info.sender, jackpot_amount)?;
+
+    HILO_GAMES.save(deps.storage, &info.sender, &game)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "hilo_start"),
+            attr("address", info.sender.clone()),
+            attr("amount", amount),
+            attr("card", card.to_string()),
+            attr("jackpot", jackpot_amount),
+        ]))
+}
+
+pub fn execute_hilo_guess(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    choice: HiloChoice
+) -> Result<Response, ContractError> {
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let mut game = load_game(&deps, &env, &info.sender)?;
+
+    let factor = guess_factor(&game.cards, choice).ok_or(ContractError::InvalidAction {})?;
+    let next_multiplier = u64::try_from(game.multiplier as u128 * factor as u128 / constants::MULTIPLY as u128)
+        .map_err(|_| ContractError::MultiplierOverflow {})?;
+
+    // Back the payout of a right guess before the card is drawn
+    let needed = game.bet_amount.checked_mul(Uint128::from(next_multiplier))
+        .map_err(|_| ContractError::MultiplierOverflow {})? / Uint128::from(constants::MULTIPLY);
+    payout_cap::hold(deps.storage, &env, &info.sender, game.reserved, needed)?;
+    util::adjust_reservation(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone(), &mut game.reserved, needed)?;
+
+    let current = util::card_rank(*game.cards.last().expect("card"));
+    let card = util::draw_card(game.seed, &game.cards, util::block_entropy(&env));
+    let rank = util::card_rank(card);
+    game.cards.push(card);
+    game.guesses.push(choice);
+
+    let right = match choice {
+        HiloChoice::Higher => rank >= current,
+        HiloChoice::Lower => rank <= current,
+    };
+    if !right {
+        game.multiplier = 0;
+        return settle(deps, env, game);
+    }
+
+    game.multiplier = next_multiplier;
+
+    if game.cards.len() == constants::DECK_SIZE as usize {
+        return settle(deps, env, game);
+    }
+
+    game.expires_at = env.block.time.seconds() + constants::HILO_TIMEOUT;
+    HILO_GAMES.save(deps.storage, &info.sender, &game)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "hilo_guess"),
+        attr("address", info.sender),
+        attr("card", card.to_string()),
+        attr("multiplier", game.multiplier.to_string()),
+    ]))
+}
+
+pub fn execute_hilo_cashout(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+
+    // Cashing out stays possible after the timeout
+    let game = HILO_GAMES.may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NoActiveGame {})?;
+
+    if game.guesses.is_empty() {
+        return Err(ContractError::InvalidAction {});
+    }
+
+    settle(deps, env, game)
+}
+
+/// Cashes out an abandoned game for the player at its pending multiplier
+pub fn execute_hilo_timeout(
+    deps: DepsMut,
+    env: Env,
+    address: Addr
+) -> Result<Response, ContractError> {
+
+    let game = HILO_GAMES.may_load(deps.storage, &address)?
+        .ok_or(ContractError::NoActiveGame {})?;
+
+    if env.block.time.seconds() < game.expires_at {
+        return Err(ContractError::GameNotExpired { expires_at: game.expires_at });
+    }
+
+    settle(deps, env, game)
+}
+
+fn settle(
+    deps: DepsMut,
+    env: Env,
+    game: HiloGame
+) -> Result<Response, ContractError> {
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    let amount = game.bet_amount;
+    let owner_amount = amount * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
+    let reward_amount = (amount * Uint128::from(game.multiplier) / Uint128::from(constants::MULTIPLY)).saturating_sub(owner_amount);
+    let win = if reward_amount > amount { Some(0) } else { Some(1) };
+
+    let mut messages: Vec<CosmosMsg> = vec![
+        util::fee_message(deps.storage, deps.querier, deps.api, cfg.denom.clone(), &game.address, amount, owner_amount)?
+    ];
+    let frozen = ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &game.address, reward_amount, game.from_balance, &mut messages)?;
+
+    util::release(deps.storage, game.reserved)?;
+    payout_cap::hold(deps.storage, &env, &game.address, game.reserved, Uint128::zero())?;
+    HILO_GAMES.remove(deps.storage, &game.address);
+
+    let record = HHistory {
+        id: cfg.hilo_count + 1,
+        address: game.address.clone(),
+        seed: game.seed,
+        cards: game.cards.clone(),
+        guesses: game.guesses.clone(),
+        multiplier: game.multiplier,
+        win,
+        bet_amount: amount,
+        payout: reward_amount,
+        timestamp: env.block.time.seconds()
+    };
+    HHISTORY.save(deps.storage, cfg.hilo_count, &record)?;
+    let breaker = settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+        player: &game.address,
+        game: "hilo",
+        count: cfg.hilo_count,
+        win: record.win,
+        wagered: amount,
+        fee: owner_amount,
+        paid: reward_amount,
+        jackpot: Uint128::zero()
+    })?;
+
+    cfg.hilo_count += 1;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_events(breaker)
+        .add_attributes(vec![
+            attr("action", "hilo_settle"),
+            attr("address", game.address.clone()),
+            attr("amount", amount),
+            attr("card", game.cards.last().expect("card").to_string()),
+            attr("multiplier", game.multiplier.to_string()),
+            attr("payout", reward_amount),
+            attr("frozen", frozen),
+            attr("win", win.expect("u8").to_string()),
+        ]))
+}
+
+pub fn query_hilo_game(deps: Deps, address: Addr) -> StdResult<HiloGameResponse> {
+    Ok(HiloGameResponse {
+        game: HILO_GAMES.may_load(deps.storage, &address)?
+    })
+}
+
+pub fn query_hhistory(deps: Deps, count: u32) -> StdResult<HHistoryResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let real_count = (cfg.hilo_count - history::first_retained(deps.storage, "hilo")?).min(count as u64) as usize;
+
+    let mut list:Vec<HHistory> = vec![];
+    for i in 0..real_count {
+        list.push(HHISTORY.load(deps.storage, cfg.hilo_count - 1 - i as u64)?);
+    }
+
+    Ok(HHistoryResponse {
+        list
+    })
+}
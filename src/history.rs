@@ -0,0 +1,296 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128
+};
+use cw_storage_plus::{Bound, Map};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::error::ContractError;
+use crate::msg::{
+    BHistory, DHistory, FHistory, HHistory, HistoryFilter, HistoryOutcome, HistoryPage, HistoryRetention, JHistory,
+    KHistory, MHistory, OldestHistoryResponse, PHistory, RHistory, SHistory, WHistory
+};
+use crate::state::{
+    Config, BHISTORY, CONFIG, DHISTORY, FHISTORY, HHISTORY, HISTORY_BY_TIME, HISTORY_BY_WIN, HISTORY_RETENTION,
+    HISTORY_START, JHISTORY, KHISTORY, MHISTORY, PHISTORY, RHISTORY, SHISTORY, WHISTORY
+};
+use crate::util;
+use crate::stats;
+use crate::constants;
+
+/// Fields every game history record has
+pub trait HistoryRecord {
+    fn id(&self) -> u64;
+    fn bet_amount(&self) -> Uint128;
+    fn timestamp(&self) -> u64;
+    fn win(&self) -> Option<u8>;
+}
+
+macro_rules! history_record {
+    ($($t:ty),*) => {
+        $(impl HistoryRecord for $t {
+            fn id(&self) -> u64 { self.id }
+            fn bet_amount(&self) -> Uint128 { self.bet_amount }
+            fn timestamp(&self) -> u64 { self.timestamp }
+            fn win(&self) -> Option<u8> { self.win }
+        })*
+    };
+}
+
+history_record!(FHistory, RHistory, DHistory, BHistory, PHistory, KHistory, JHistory, SHistory, MHistory, HHistory, WHistory);
+
+/// Indexes the record saved under `count` in the history of `game` by time and outcome.
+/// Called next to every history save.
+pub fn index(storage: &mut dyn Storage, game: &str, count: u64, timestamp: u64, win: Option<u8>) -> StdResult<()> {
+    HISTORY_BY_TIME.save(storage, (game, timestamp, count), &Empty {})?;
+    if let Some(win) = win {
+        HISTORY_BY_WIN.save(storage, (game, win, count), &Empty {})?;
+    }
+    Ok(())
+}
+
+/// Value `win` is recorded with for `outcome`
+fn outcome_code(game: &str, outcome: HistoryOutcome) -> Option<u8> {
+    match (outcome, stats::has_ties(game)) {
+        (HistoryOutcome::Win, _) => Some(0),
+        (HistoryOutcome::Tie, true) => Some(1),
+        (HistoryOutcome::Tie, false) => None,
+        (HistoryOutcome::Lose, true) => Some(2),
+        (HistoryOutcome::Lose, false) => Some(1),
+    }
+}
+
+/// Inclusive range of counts matching the time range of `filter` and coming before `start_after`
+fn count_range(storage: &dyn Storage, game: &str, filter: &HistoryFilter, start_after: Option<u64>) -> StdResult<Option<(u64, u64)>> {
+    let low = match filter.from {
+        Some(from) => HISTORY_BY_TIME.sub_prefix(game)
+            .keys(storage, Some(Bound::inclusive((from, 0u64))), None, Order::Ascending)
+            .next()
+            .transpose()?
+            .map(|(_, count)| count),
+        None => Some(0)
+    };
+    let high = match filter.to {
+        Some(to) => HISTORY_BY_TIME.sub_prefix(game)
+            .keys(storage, None, Some(Bound::inclusive((to, u64::MAX))), Order::Descending)
+            .next()
+            .transpose()?
+            .map(|(_, count)| count),
+        None => Some(u64::MAX)
+    };
+    // Ids count from 1, so `start_after` is exclusive of count `start_after - 1`
+    let high = match start_after {
+        Some(id) => high.and_then(|high| id.checked_sub(2).map(|before| high.min(before))),
+        None => high
+    };
+    Ok(match (low, high) {
+        (Some(low), Some(high)) if low <= high => Some((low, high)),
+        _ => None
+    })
+}
+
+/// Newest first records of `history` matching `filter`. At most `HISTORY_MAX_SCAN` records are
+/// looked at per call, `next` is the id to carry on after when there may be more.
+/// Records saved before the indexes existed are only found without a time range or outcome.
+fn page<T: HistoryRecord + Serialize + DeserializeOwned>(
+    storage: &dyn Storage,
+    game: &str,
+    history: Map<u64, T>,
+    filter: HistoryFilter,
+    start_after: Option<u64>,
+    limit: Option<u32>
+) -> StdResult<HistoryPage<T>> {
+    let limit = limit.unwrap_or(constants::DEFAULT_LIMIT).min(constants::MAX_LIMIT) as usize;
+
+    let (low, high) = match count_range(storage, game, &filter, start_after)? {
+        Some(range) => range,
+        None => return Ok(HistoryPage { list: vec![], next: None })
+    };
+    let (min, max) = (Some(Bound::inclusive(low)), Some(Bound::inclusive(high)));
+
+    let counts: Box<dyn Iterator<Item = StdResult<u64>>> = match filter.outcome {
+        Some(outcome) => match outcome_code(game, outcome) {
+            Some(win) => HISTORY_BY_WIN.prefix((game, win)).keys(storage, min, max, Order::Descending),
+            None => return Ok(HistoryPage { list: vec![], next: None })
+        },
+        None => history.keys(storage, min, max, Order::Descending)
+    };
+
+    let mut list: Vec<T> = vec![];
+    let mut next = None;
+    for (scanned, count) in counts.enumerate() {
+        let count = count?;
+        if scanned as u32 >= constants::HISTORY_MAX_SCAN {
+            next = Some(count + 2);
+            break;
+        }
+        let record = history.load(storage, count)?;
+        if filter.min_bet.is_some_and(|min_bet| record.bet_amount() < min_bet) {
+            continue;
+        }
+        list.push(record);
+        if list.len() == limit {
+            next = list.last().map(|r| r.id());
+            break;
+        }
+    }
+
+    Ok(HistoryPage {
+        list,
+        next
+    })
+}
+
+pub fn query_filtered_history(
+    deps: Deps,
+    game: String,
+    filter: HistoryFilter,
+    start_after: Option<u64>,
+    limit: Option<u32>
+) -> StdResult<Binary> {
+    let storage = deps.storage;
+    match game.as_str() {
+        "flip" => to_json_binary(&page(storage, &game, FHISTORY, filter, start_after, limit)?),
+        "rps" => to_json_binary(&page(storage, &game, RHISTORY, filter, start_after, limit)?),
+        "dice" => to_json_binary(&page(storage, &game, DHISTORY, filter, start_after, limit)?),
+        "roulette" => to_json_binary(&page(storage, &game, BHISTORY, filter, start_after, limit)?),
+        "plinko" => to_json_binary(&page(storage, &game, PHISTORY, filter, start_after, limit)?),
+        "keno" => to_json_binary(&page(storage, &game, KHISTORY, filter, start_after, limit)?),
+        "blackjack" => to_json_binary(&page(storage, &game, JHISTORY, filter, start_after, limit)?),
+        "slots" => to_json_binary(&page(storage, &game, SHISTORY, filter, start_after, limit)?),
+        "mines" => to_json_binary(&page(storage, &game, MHISTORY, filter, start_after, limit)?),
+        "hilo" => to_json_binary(&page(storage, &game, HHISTORY, filter, start_after, limit)?),
+        "wheel" => to_json_binary(&page(storage, &game, WHISTORY, filter, start_after, limit)?),
+        _ => Err(StdError::generic_err(format!("Unknown game {}", game)))
+    }
+}
+
+/// Records saved so far in the history of `game`, None for an unknown game
+fn history_count(cfg: &Config, game: &str) -> Option<u64> {
+    match game {
+        "flip" => Some(cfg.flip_count),
+        "rps" => Some(cfg.rps_count),
+        "dice" => Some(cfg.dice_count),
+        "roulette" => Some(cfg.roulette_count),
+        "plinko" => Some(cfg.plinko_count),
+        "keno" => Some(cfg.keno_count),
+        "blackjack" => Some(cfg.blackjack_count),
+        "slots" => Some(cfg.slots_count),
+        "mines" => Some(cfg.mines_count),
+        "hilo" => Some(cfg.hilo_count),
+        "wheel" => Some(cfg.wheel_count),
+        _ => None
+    }
+}
+
+/// Count of the oldest record of `game` not pruned yet
+pub fn first_retained(storage: &dyn Storage, game: &str) -> StdResult<u64> {
+    Ok(HISTORY_START.may_load(storage, game)?.unwrap_or_default())
+}
+
+/// Removes up to `limit` of the oldest records of `history` the retention no longer keeps, with
+/// their index entries, and returns how many went. A record stays while it is among the last
+/// `keep_last` or newer than `max_age`; with neither set every record stays.
+#[allow(clippy::too_many_arguments)]
+fn prune<T: HistoryRecord + Serialize + DeserializeOwned>(
+    storage: &mut dyn Storage,
+    game: &str,
+    history: Map<u64, T>,
+    total: u64,
+    retention: &HistoryRetention,
+    now: u64,
+    limit: u64
+) -> StdResult<u64> {
+    if retention.keep_last.is_none() && retention.max_age.is_none() {
+        return Ok(0);
+    }
+
+    let start = first_retained(storage, game)?;
+    let mut count = start;
+    while count < total && count - start < limit {
+        if retention.keep_last.is_some_and(|keep| count + keep >= total) {
+            break;
+        }
+        let record = history.load(storage, count)?;
+        if retention.max_age.is_some_and(|age| record.timestamp() + age > now) {
+            break;
+        }
+        history.remove(storage, count);
+        HISTORY_BY_TIME.remove(storage, (game, record.timestamp(), count));
+        if let Some(win) = record.win() {
+            HISTORY_BY_WIN.remove(storage, (game, win, count));
+        }
+        count += 1;
+    }
+
+    HISTORY_START.save(storage, game, &count)?;
+    Ok(count - start)
+}
+
+pub fn execute_update_history_retention(
+    deps: DepsMut,
+    info: MessageInfo,
+    retention: HistoryRetention
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    HISTORY_RETENTION.save(deps.storage, &retention)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_history_retention"),
+        attr("keep_last", retention.keep_last.map(|k| k.to_string()).unwrap_or_default()),
+        attr("max_age", retention.max_age.map(|a| a.to_string()).unwrap_or_default()),
+    ]))
+}
+
+/// Anyone can prune, the retention decides what goes. Game statistics are kept separately
+/// and are not affected.
+pub fn execute_prune_history(
+    deps: DepsMut,
+    env: Env,
+    game: String,
+    limit: Option<u32>
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let retention = HISTORY_RETENTION.may_load(deps.storage)?.unwrap_or_default();
+    let total = history_count(&cfg, &game).ok_or(ContractError::InvalidInput {})?;
+    let limit = limit.unwrap_or(constants::DEFAULT_LIMIT).min(constants::MAX_PRUNE_LIMIT) as u64;
+    let now = env.block.time.seconds();
+
+    let storage = deps.storage;
+    let pruned = match game.as_str() {
+        "flip" => prune(storage, &game, FHISTORY, total, &retention, now, limit)?,
+        "rps" => prune(storage, &game, RHISTORY, total, &retention, now, limit)?,
+        "dice" => prune(storage, &game, DHISTORY, total, &retention, now, limit)?,
+        "roulette" => prune(storage, &game, BHISTORY, total, &retention, now, limit)?,
+        "plinko" => prune(storage, &game, PHISTORY, total, &retention, now, limit)?,
+        "keno" => prune(storage, &game, KHISTORY, total, &retention, now, limit)?,
+        "blackjack" => prune(storage, &game, JHISTORY, total, &retention, now, limit)?,
+        "slots" => prune(storage, &game, SHISTORY, total, &retention, now, limit)?,
+        "mines" => prune(storage, &game, MHISTORY, total, &retention, now, limit)?,
+        "hilo" => prune(storage, &game, HHISTORY, total, &retention, now, limit)?,
+        "wheel" => prune(storage, &game, WHISTORY, total, &retention, now, limit)?,
+        _ => return Err(ContractError::InvalidInput {}),
+    };
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "prune_history"),
+        attr("game", game.clone()),
+        attr("pruned", pruned.to_string()),
+        attr("oldest_id", (first_retained(storage, &game)? + 1).to_string()),
+    ]))
+}
+
+pub fn query_history_retention(deps: Deps) -> StdResult<HistoryRetention> {
+    Ok(HISTORY_RETENTION.may_load(deps.storage)?.unwrap_or_default())
+}
+
+pub fn query_oldest_history(deps: Deps, game: String) -> StdResult<OldestHistoryResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let total = history_count(&cfg, &game).ok_or_else(|| StdError::generic_err(format!("Unknown game {}", game)))?;
+    let start = first_retained(deps.storage, &game)?;
+    Ok(OldestHistoryResponse {
+        game,
+        oldest_id: (start < total).then_some(start + 1),
+        retained: total - start
+    })
+}
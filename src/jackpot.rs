@@ -0,0 +1,116 @@
+use cosmwasm_std::{
+    attr, Addr, Deps, DepsMut, MessageInfo, Response, StdResult, Storage, Uint128
+};
+use cw20::Balance;
+use crate::error::ContractError;
+use crate::msg::{HashObj, JackpotResponse, JackpotWinner, JackpotWinnersResponse};
+use crate::state::{CONFIG, JACKPOT, JACKPOT_WINNERS};
+use crate::util;
+use crate::constants;
+
+const JACKPOT_SALT: &str = "jackpot";
+
+/// Adds the bet's contribution to the pool and runs the 1-in-`odds` draw.
+/// Returns the amount won, which the caller has to transfer to the player.
+pub fn settle_jackpot(
+    storage: &mut dyn Storage,
+    obj: &HashObj,
+    game: &str,
+    amount: Uint128,
+    address: &Addr,
+    timestamp: u64
+) -> Result<Uint128, ContractError> {
+    let mut jackpot = JACKPOT.may_load(storage)?.unwrap_or_default();
+
+    jackpot.amount += amount * Uint128::from(jackpot.contribution_bps) / Uint128::from(constants::BPS);
+
+    // Salted so the draw is independent of the game outcome derived from the same object
+    let draw = util::calculate_hash(&(util::calculate_hash(obj), JACKPOT_SALT));
+    if jackpot.odds == 0 || jackpot.amount.is_zero() || draw % jackpot.odds != 0 {
+        JACKPOT.save(storage, &jackpot)?;
+        return Ok(Uint128::zero());
+    }
+
+    let won = jackpot.amount;
+    jackpot.win_count += 1;
+    JACKPOT_WINNERS.save(storage, jackpot.win_count - 1, &JackpotWinner {
+        id: jackpot.win_count,
+        address: address.clone(),
+        game: game.to_string(),
+        amount: won,
+        timestamp
+    })?;
+    jackpot.amount = Uint128::zero();
+    JACKPOT.save(storage, &jackpot)?;
+
+    Ok(won)
+}
+
+pub fn execute_update_jackpot(
+    deps: DepsMut,
+    info: MessageInfo,
+    contribution_bps: u64,
+    odds: u64
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    if contribution_bps > constants::BPS {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let mut jackpot = JACKPOT.may_load(deps.storage)?.unwrap_or_default();
+    jackpot.contribution_bps = contribution_bps;
+    jackpot.odds = odds;
+    JACKPOT.save(deps.storage, &jackpot)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_jackpot"),
+        attr("contribution_bps", contribution_bps.to_string()),
+        attr("odds", odds.to_string()),
+    ]))
+}
+
+pub fn execute_seed_jackpot(
+    deps: DepsMut,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let amount = util::get_amount_of_denom(Balance::from(info.funds), cfg.denom)?;
+
+    let mut jackpot = JACKPOT.may_load(deps.storage)?.unwrap_or_default();
+    jackpot.amount += amount;
+    JACKPOT.save(deps.storage, &jackpot)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "seed_jackpot"),
+        attr("amount", amount),
+        attr("jackpot", jackpot.amount),
+    ]))
+}
+
+pub fn query_jackpot(deps: Deps) -> StdResult<JackpotResponse> {
+    let jackpot = JACKPOT.may_load(deps.storage)?.unwrap_or_default();
+    Ok(JackpotResponse {
+        amount: jackpot.amount,
+        contribution_bps: jackpot.contribution_bps,
+        odds: jackpot.odds,
+        win_count: jackpot.win_count
+    })
+}
+
+pub fn query_jackpot_winners(deps: Deps, count: u32) -> StdResult<JackpotWinnersResponse> {
+    let jackpot = JACKPOT.may_load(deps.storage)?.unwrap_or_default();
+
+    let real_count = jackpot.win_count.min(count as u64) as usize;
+
+    let mut list:Vec<JackpotWinner> = vec![];
+    for i in 0..real_count {
+        list.push(JACKPOT_WINNERS.load(deps.storage, jackpot.win_count - 1 - i as u64)?);
+    }
+
+    Ok(JackpotWinnersResponse {
+        list
+    })
+}
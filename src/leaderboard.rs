@@ -0,0 +1,180 @@
+use cosmwasm_std::{
+    attr, Addr, CosmosMsg, Deps, DepsMut, Empty, Env, Int128, MessageInfo, Order, Response, StdResult, Storage, Uint128
+};
+use cw_storage_plus::Map;
+use crate::error::ContractError;
+use crate::msg::{LeaderboardEntry, LeaderboardKind, LeaderboardPeriod, LeaderboardResponse};
+use crate::state::{CONFIG, LEADERBOARD_PAID, LEADERBOARD_SIZES, LEADERBOARD_TOTALS, LeaderboardTotals, PROFIT_RANKING, WAGERED_RANKING};
+use crate::util;
+use crate::ledger;
+use crate::constants;
+
+const PERIODS: [LeaderboardPeriod; 2] = [LeaderboardPeriod::Daily, LeaderboardPeriod::Weekly];
+const KINDS: [LeaderboardKind; 2] = [LeaderboardKind::Wagered, LeaderboardKind::Profit];
+
+/// Profit as a ranking key, offset so that losses sort below gains
+fn profit_score(profit: Int128) -> u128 {
+    (profit.i128() as u128) ^ (1u128 << 127)
+}
+
+fn ranking(kind: LeaderboardKind) -> Map<'static, ((u8, u64), u128, &'static Addr), Empty> {
+    match kind {
+        LeaderboardKind::Wagered => WAGERED_RANKING,
+        LeaderboardKind::Profit => PROFIT_RANKING
+    }
+}
+
+fn score(kind: LeaderboardKind, totals: &LeaderboardTotals) -> u128 {
+    match kind {
+        LeaderboardKind::Wagered => totals.wagered.u128(),
+        LeaderboardKind::Profit => profit_score(totals.profit)
+    }
+}
+
+/// Adds a settled bet of `player` to the boards of the current day and week. A ranking that
+/// grows past `LEADERBOARD_SIZE` drops its lowest player, whose totals are forgotten once
+/// they are on neither ranking, so they start over should they bet again in the period.
+pub fn record(storage: &mut dyn Storage, env: &Env, player: &Addr, wagered: Uint128, paid: Uint128) -> StdResult<()> {
+    let now = env.block.time.seconds();
+    for period in PERIODS {
+        let board = (period as u8, now / period.length());
+
+        let mut totals = LEADERBOARD_TOTALS.may_load(storage, (board.0, board.1, player))?.unwrap_or_default();
+        let listed = KINDS.map(|kind| ranking(kind).has(storage, (board, score(kind, &totals), player)));
+        for kind in KINDS {
+            ranking(kind).remove(storage, (board, score(kind, &totals), player));
+        }
+
+        totals.wagered += wagered;
+        totals.profit = Int128::new(totals.profit.i128() + paid.u128() as i128 - wagered.u128() as i128);
+
+        LEADERBOARD_TOTALS.save(storage, (board.0, board.1, player), &totals)?;
+        for (kind, listed) in KINDS.iter().copied().zip(listed.iter().copied()) {
+            ranking(kind).save(storage, (board, score(kind, &totals), player), &Empty {})?;
+            if listed {
+                continue;
+            }
+            let size = LEADERBOARD_SIZES.may_load(storage, (board.0, kind as u8, board.1))?.unwrap_or_default() + 1;
+            if size > constants::LEADERBOARD_SIZE {
+                drop_lowest(storage, board, kind)?;
+            } else {
+                LEADERBOARD_SIZES.save(storage, (board.0, kind as u8, board.1), &size)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Removes the lowest player of a ranking, and their totals unless the other ranking lists them
+fn drop_lowest(storage: &mut dyn Storage, board: (u8, u64), kind: LeaderboardKind) -> StdResult<()> {
+    let lowest = ranking(kind).sub_prefix(board)
+        .keys(storage, None, None, Order::Ascending)
+        .next()
+        .transpose()?;
+    if let Some((score_key, address)) = lowest {
+        ranking(kind).remove(storage, (board, score_key, &address));
+        let totals = LEADERBOARD_TOTALS.load(storage, (board.0, board.1, &address))?;
+        let other = match kind {
+            LeaderboardKind::Wagered => LeaderboardKind::Profit,
+            LeaderboardKind::Profit => LeaderboardKind::Wagered
+        };
+        if !ranking(other).has(storage, (board, score(other, &totals), &address)) {
+            LEADERBOARD_TOTALS.remove(storage, (board.0, board.1, &address));
+        }
+    }
+    Ok(())
+}
+
+/// Top `limit` players of a board, best first
+fn top(storage: &dyn Storage, period: LeaderboardPeriod, kind: LeaderboardKind, index: u64, limit: usize) -> StdResult<Vec<LeaderboardEntry>> {
+    ranking(kind)
+        .sub_prefix((period as u8, index))
+        .keys(storage, None, None, Order::Descending)
+        .take(limit)
+        .enumerate()
+        .map(|(i, key)| {
+            let (_, address) = key?;
+            let totals = LEADERBOARD_TOTALS.load(storage, (period as u8, index, &address))?;
+            Ok(LeaderboardEntry {
+                rank: i as u32 + 1,
+                address,
+                wagered: totals.wagered,
+                profit: totals.profit
+            })
+        })
+        .collect()
+}
+
+/// Pays `prizes` from the bankroll to the top ranks of a finished period, first prize to the first rank.
+/// Prizes without a player to rank stay with the house.
+pub fn execute_pay_leaderboard(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    period: LeaderboardPeriod,
+    kind: LeaderboardKind,
+    index: u64,
+    prizes: Vec<Uint128>
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+
+    if prizes.is_empty() || prizes.len() > constants::MAX_LIMIT as usize || index >= env.block.time.seconds() / period.length() {
+        return Err(ContractError::InvalidInput {});
+    }
+    if LEADERBOARD_PAID.has(deps.storage, (period as u8, kind as u8, index)) {
+        return Err(ContractError::LeaderboardAlreadyPaid {});
+    }
+
+    let winners = top(deps.storage, period, kind, index, prizes.len())?;
+    let total: Uint128 = prizes.iter().take(winners.len()).sum();
+
+    let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+    if contract_amount < total {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    // Prizes of blocked players are frozen in their balances, the board is still marked paid
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut frozen = Uint128::zero();
+    for (winner, prize) in winners.iter().zip(prizes.iter()) {
+        frozen += ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &winner.address, *prize, false, &mut messages)?;
+    }
+    LEADERBOARD_PAID.save(deps.storage, (period as u8, kind as u8, index), &total)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "pay_leaderboard"),
+            attr("period", period.as_str()),
+            attr("kind", kind.as_str()),
+            attr("index", index.to_string()),
+            attr("winners", winners.len().to_string()),
+            attr("amount", total),
+            attr("frozen", frozen),
+        ]))
+}
+
+/// Board of period `index`, the current one when None
+pub fn query_leaderboard(
+    deps: Deps,
+    env: Env,
+    period: LeaderboardPeriod,
+    kind: LeaderboardKind,
+    index: Option<u64>,
+    limit: Option<u32>
+) -> StdResult<LeaderboardResponse> {
+    let limit = limit.unwrap_or(constants::DEFAULT_LIMIT).min(constants::MAX_LIMIT) as usize;
+    let index = index.unwrap_or(env.block.time.seconds() / period.length());
+
+    Ok(LeaderboardResponse {
+        period,
+        kind,
+        index,
+        starts_at: index * period.length(),
+        ends_at: (index + 1) * period.length(),
+        list: top(deps.storage, period, kind, index, limit)?,
+        paid: LEADERBOARD_PAID.may_load(deps.storage, (period as u8, kind as u8, index))?
+    })
+}
@@ -0,0 +1,150 @@
+use cosmwasm_std::{
+    attr, Addr, CosmosMsg, Deps, DepsMut, MessageInfo, Order, QuerierWrapper, Response, StdResult, Storage, Uint128
+};
+use cw_storage_plus::Bound;
+use cw20::{Balance, Denom};
+use crate::error::ContractError;
+use crate::msg::{BalanceResponse, BalancesResponse};
+use crate::state::{BALANCES, CONFIG, TOTAL_BALANCE};
+use crate::util;
+use crate::screening;
+use crate::constants;
+
+pub fn credit(storage: &mut dyn Storage, address: &Addr, amount: Uint128) -> StdResult<Uint128> {
+    let balance = BALANCES.may_load(storage, address)?.unwrap_or_default() + amount;
+    BALANCES.save(storage, address, &balance)?;
+    let total = TOTAL_BALANCE.may_load(storage)?.unwrap_or_default() + amount;
+    TOTAL_BALANCE.save(storage, &total)?;
+    Ok(balance)
+}
+
+pub fn debit(storage: &mut dyn Storage, address: &Addr, amount: Uint128) -> Result<Uint128, ContractError> {
+    let balance = BALANCES.may_load(storage, address)?.unwrap_or_default();
+    if balance < amount {
+        return Err(ContractError::InsufficientBalance { balance });
+    }
+    if balance == amount {
+        BALANCES.remove(storage, address);
+    } else {
+        BALANCES.save(storage, address, &(balance - amount))?;
+    }
+    let total = TOTAL_BALANCE.may_load(storage)?.unwrap_or_default().saturating_sub(amount);
+    TOTAL_BALANCE.save(storage, &total)?;
+    Ok(balance - amount)
+}
+
+/// Stake of a bet, either debited from the player's balance or taken from the attached funds
+pub fn take_stake(
+    storage: &mut dyn Storage,
+    info: &MessageInfo,
+    denom: Denom,
+    from_balance: Option<Uint128>
+) -> Result<Uint128, ContractError> {
+    match from_balance {
+        Some(amount) => {
+            if amount.is_zero() || !info.funds.is_empty() {
+                return Err(ContractError::InvalidInput {});
+            }
+            debit(storage, &info.sender, amount)?;
+            Ok(amount)
+        },
+        None => util::get_amount_of_denom(Balance::from(info.funds.clone()), denom)
+    }
+}
+
+/// Pays a player, crediting the balance for bets placed from it and transferring otherwise.
+/// A blocked address is always credited instead, where the amount stays frozen outside the
+/// bankroll until it is unblocked and withdraws, so settling a game, round or board still goes
+/// through. Returns the amount frozen this way.
+pub fn pay(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+    denom: Denom,
+    address: &Addr,
+    amount: Uint128,
+    to_balance: bool,
+    messages: &mut Vec<CosmosMsg>
+) -> Result<Uint128, ContractError> {
+    if amount.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    if screening::is_blocked(storage, querier, address)? {
+        credit(storage, address, amount)?;
+        return Ok(amount);
+    }
+    if to_balance {
+        credit(storage, address, amount)?;
+    } else {
+        messages.push(util::transfer_token_message(querier, denom, amount, address.clone())?);
+    }
+    Ok(Uint128::zero())
+}
+
+pub fn execute_deposit(
+    deps: DepsMut,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let amount = util::get_amount_of_denom(Balance::from(info.funds), cfg.denom)?;
+    let balance = credit(deps.storage, &info.sender, amount)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deposit"),
+        attr("address", info.sender),
+        attr("amount", amount),
+        attr("balance", balance),
+    ]))
+}
+
+pub fn execute_withdraw_balance(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Uint128
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let balance = debit(deps.storage, &info.sender, amount)?;
+
+    let message = util::transfer_token_message(deps.querier, cfg.denom, amount, info.sender.clone())?;
+
+    Ok(Response::new()
+        .add_message(message)
+        .add_attributes(vec![
+            attr("action", "withdraw_balance"),
+            attr("address", info.sender),
+            attr("amount", amount),
+            attr("balance", balance),
+        ]))
+}
+
+pub fn query_balance(deps: Deps, address: Addr) -> StdResult<BalanceResponse> {
+    let balance = BALANCES.may_load(deps.storage, &address)?.unwrap_or_default();
+    Ok(BalanceResponse {
+        address,
+        balance
+    })
+}
+
+pub fn query_balances(deps: Deps, start_after: Option<Addr>, limit: Option<u32>) -> StdResult<BalancesResponse> {
+    let limit = limit.unwrap_or(constants::DEFAULT_LIMIT).min(constants::MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let list = BALANCES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(address, balance)| BalanceResponse { address, balance }))
+        .collect::<StdResult<Vec<BalanceResponse>>>()?;
+
+    Ok(BalancesResponse {
+        list,
+        total: TOTAL_BALANCE.may_load(deps.storage)?.unwrap_or_default()
+    })
+}
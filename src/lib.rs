@@ -4,5 +4,27 @@ pub mod msg;
 pub mod state;
 pub mod util;
 pub mod constants;
+pub mod jackpot;
+pub mod lottery;
+pub mod blackjack;
+pub mod mines;
+pub mod hilo;
+pub mod ledger;
+pub mod batch;
+pub mod referral;
+pub mod loyalty;
+pub mod voucher;
+pub mod limits;
+pub mod screening;
+pub mod rate_limit;
+pub mod breaker;
+pub mod payout_cap;
+pub mod stats;
+pub mod leaderboard;
+pub mod history;
+pub mod settlement;
+
+#[cfg(test)]
+mod tests;
 
 pub use crate::error::ContractError;
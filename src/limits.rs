@@ -0,0 +1,189 @@
+use cosmwasm_std::{
+    attr, Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Uint128
+};
+use crate::error::ContractError;
+use crate::msg::{BetLimits, LimitsResponse, LimitWindow};
+use crate::state::{PLAYER_ACTIVITY, PLAYER_LIMITS, PlayerActivity, PlayerLimits};
+use crate::constants;
+
+/// Tighter of two optional caps, None being no cap
+fn tighter(a: Option<Uint128>, b: Option<Uint128>) -> Option<Uint128> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// Every field of `a` at least as tight as the one of `b`
+fn tightest(a: &BetLimits, b: &BetLimits) -> BetLimits {
+    BetLimits {
+        daily_wager: tighter(a.daily_wager, b.daily_wager),
+        weekly_wager: tighter(a.weekly_wager, b.weekly_wager),
+        monthly_wager: tighter(a.monthly_wager, b.monthly_wager),
+        daily_loss: tighter(a.daily_loss, b.daily_loss),
+        weekly_loss: tighter(a.weekly_loss, b.weekly_loss),
+        monthly_loss: tighter(a.monthly_loss, b.monthly_loss),
+        cooldown: a.cooldown.max(b.cooldown)
+    }
+}
+
+/// Limits in force at `now`, a pending loosening applies once its delay has passed
+fn load_limits(storage: &dyn Storage, player: &Addr, now: u64) -> StdResult<PlayerLimits> {
+    let mut limits = PLAYER_LIMITS.may_load(storage, player)?.unwrap_or_default();
+    if limits.pending.is_some() && now >= limits.pending_at {
+        limits.limits = limits.pending.take().expect("pending");
+        limits.pending_at = 0;
+    }
+    Ok(limits)
+}
+
+/// Activity with every window moved to the period containing `now`
+fn load_activity(storage: &dyn Storage, player: &Addr, now: u64) -> StdResult<PlayerActivity> {
+    let mut activity = PLAYER_ACTIVITY.may_load(storage, player)?.unwrap_or_default();
+    for (window, length) in [
+        (&mut activity.daily, constants::DAY),
+        (&mut activity.weekly, constants::WEEK),
+        (&mut activity.monthly, constants::MONTH),
+    ] {
+        if window.period != now / length {
+            *window = LimitWindow { period: now / length, ..LimitWindow::default() };
+        }
+    }
+    Ok(activity)
+}
+
+/// Checks the self-exclusion and cooldown of `player`, then counts `amount` toward the limits.
+/// Called once by every game entry point with the stake it takes.
+pub fn check_bet(storage: &mut dyn Storage, env: &Env, player: &Addr, amount: Uint128) -> Result<(), ContractError> {
+    let now = env.block.time.seconds();
+    let limits = load_limits(storage, player, now)?;
+
+    if now < limits.excluded_until {
+        return Err(ContractError::SelfExcluded { until: limits.excluded_until });
+    }
+
+    let mut activity = load_activity(storage, player, now)?;
+    if activity.last_bet > 0 && now < activity.last_bet + limits.limits.cooldown {
+        return Err(ContractError::CooldownActive { until: activity.last_bet + limits.limits.cooldown });
+    }
+    activity.last_bet = now;
+    PLAYER_ACTIVITY.save(storage, player, &activity)?;
+
+    check_wager(storage, env, player, amount)
+}
+
+/// Counts a further stake of a bet already admitted by `check_bet` toward the limits.
+/// The whole stake counts as a possible loss until its return is recorded.
+pub fn check_wager(storage: &mut dyn Storage, env: &Env, player: &Addr, amount: Uint128) -> Result<(), ContractError> {
+    let now = env.block.time.seconds();
+    let limits = load_limits(storage, player, now)?.limits;
+    let mut activity = load_activity(storage, player, now)?;
+
+    for (period, window, wager_limit, loss_limit) in [
+        ("daily", &mut activity.daily, limits.daily_wager, limits.daily_loss),
+        ("weekly", &mut activity.weekly, limits.weekly_wager, limits.weekly_loss),
+        ("monthly", &mut activity.monthly, limits.monthly_wager, limits.monthly_loss),
+    ] {
+        if let Some(limit) = wager_limit {
+            if window.wagered + amount > limit {
+                return Err(ContractError::WagerLimitExceeded { period: period.to_string(), limit });
+            }
+        }
+        if let Some(limit) = loss_limit {
+            if window.wagered.saturating_sub(window.returned) + amount > limit {
+                return Err(ContractError::LossLimitExceeded { period: period.to_string(), limit });
+            }
+        }
+        window.wagered += amount;
+    }
+
+    PLAYER_ACTIVITY.save(storage, player, &activity)?;
+    Ok(())
+}
+
+/// Counts what a settled bet paid back to `player`, which offsets their losses
+pub fn record_return(storage: &mut dyn Storage, env: &Env, player: &Addr, amount: Uint128) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+    let mut activity = load_activity(storage, player, env.block.time.seconds())?;
+    activity.daily.returned += amount;
+    activity.weekly.returned += amount;
+    activity.monthly.returned += amount;
+    PLAYER_ACTIVITY.save(storage, player, &activity)
+}
+
+/// Tightening applies at once, loosening any limit only after `LIMITS_DELAY`
+pub fn execute_set_limits(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limits: BetLimits
+) -> Result<Response, ContractError> {
+    let now = env.block.time.seconds();
+
+    if [limits.daily_wager, limits.weekly_wager, limits.monthly_wager, limits.daily_loss, limits.weekly_loss, limits.monthly_loss]
+        .iter().any(|l| l.is_some_and(|l| l.is_zero()))
+    {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let mut player = load_limits(deps.storage, &info.sender, now)?;
+    let active = tightest(&player.limits, &limits);
+    if active == limits {
+        player.pending = None;
+        player.pending_at = 0;
+    } else {
+        player.pending = Some(limits);
+        player.pending_at = now + constants::LIMITS_DELAY;
+    }
+    player.limits = active;
+    PLAYER_LIMITS.save(deps.storage, &info.sender, &player)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_limits"),
+        attr("address", info.sender),
+        attr("pending_at", player.pending_at.to_string()),
+    ]))
+}
+
+/// Excludes the player from every game until `until`, which can only be extended
+pub fn execute_self_exclude(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    until: u64
+) -> Result<Response, ContractError> {
+    let mut player = load_limits(deps.storage, &info.sender, env.block.time.seconds())?;
+
+    if until <= env.block.time.seconds() || until <= player.excluded_until {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    player.excluded_until = until;
+    PLAYER_LIMITS.save(deps.storage, &info.sender, &player)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "self_exclude"),
+        attr("address", info.sender),
+        attr("until", until.to_string()),
+    ]))
+}
+
+pub fn query_limits(deps: Deps, env: Env, address: Addr) -> StdResult<LimitsResponse> {
+    let now = env.block.time.seconds();
+    let player = load_limits(deps.storage, &address, now)?;
+    let activity = load_activity(deps.storage, &address, now)?;
+    Ok(LimitsResponse {
+        address,
+        limits: player.limits,
+        pending: player.pending,
+        pending_at: player.pending_at,
+        excluded_until: player.excluded_until,
+        last_bet: activity.last_bet,
+        daily: activity.daily,
+        weekly: activity.weekly,
+        monthly: activity.monthly
+    })
+}
@@ -0,0 +1,243 @@
+use cosmwasm_std::{
+    attr, Addr, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128
+};
+use cw_storage_plus::Bound;
+use crate::error::ContractError;
+use crate::msg::{LotteryRound, LotteryRoundsResponse, LotteryTicketsResponse, LotteryWinner};
+use crate::state::{CONFIG, LOTTERY_BALANCE_TICKETS, LOTTERY_PLAYER_TICKETS, LOTTERY_ROUNDS, LOTTERY_TICKETS};
+use crate::util;
+use crate::screening;
+use crate::ledger;
+use crate::limits;
+use crate::rate_limit;
+use crate::constants;
+
+pub fn execute_open_lottery(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    ticket_price: Uint128,
+    end_time: u64,
+    prize_tiers: Vec<u64>
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    // Only one round runs at a time
+    if let Some(last) = LOTTERY_ROUNDS.may_load(deps.storage, cfg.lottery_count)? {
+        if !last.drawn {
+            return Err(ContractError::LotteryRoundOpen { round_id: last.id });
+        }
+    }
+
+    if ticket_price.is_zero()
+        || end_time <= env.block.time.seconds()
+        || prize_tiers.is_empty()
+        || prize_tiers.len() > constants::LOTTERY_MAX_TIERS
+        || prize_tiers.contains(&0)
+        || prize_tiers.iter().sum::<u64>() > constants::BPS
+    {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    cfg.lottery_count += 1;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let round = LotteryRound {
+        id: cfg.lottery_count,
+        ticket_price,
+        end_time,
+        prize_tiers,
+        total_tickets: 0,
+        pot: Uint128::zero(),
+        drawn: false,
+        fee: Uint128::zero(),
+        winners: vec![],
+        seed: util::calculate_hash(&(cfg.lottery_count, util::block_entropy(&env)))
+    };
+    LOTTERY_ROUNDS.save(deps.storage, round.id, &round)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "open_lottery"),
+        attr("round_id", round.id.to_string()),
+        attr("ticket_price", ticket_price),
+        attr("end_time", end_time.to_string()),
+    ]))
+}
+
+pub fn execute_buy_tickets(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    count: u64,
+    from_balance: Option<Uint128>
+) -> Result<Response, ContractError> {
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let mut round = LOTTERY_ROUNDS.may_load(deps.storage, cfg.lottery_count)?
+        .ok_or(ContractError::LotteryClosed {})?;
+
+    if round.drawn || env.block.time.seconds() >= round.end_time {
+        return Err(ContractError::LotteryClosed {});
+    }
+
+    if count == 0 {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let expected = round.ticket_price * Uint128::from(count);
+    if from_balance.is_some_and(|amount| amount != expected) {
+        return Err(ContractError::TicketPaymentMismatch { expected });
+    }
+    let amount = ledger::take_stake(deps.storage, &info, cfg.denom, from_balance)?;
+    if amount != expected {
+        return Err(ContractError::TicketPaymentMismatch { expected });
+    }
+    limits::check_bet(deps.storage, &env, &info.sender, amount)?;
+    rate_limit::check(deps.storage, &env, &info.sender, 1)?;
+
+    round.total_tickets += count;
+    round.pot += amount;
+    round.seed = util::calculate_hash(&(round.seed, &info.sender, count, util::block_entropy(&env)));
+    LOTTERY_ROUNDS.save(deps.storage, round.id, &round)?;
+
+    LOTTERY_TICKETS.save(deps.storage, (round.id, round.total_tickets), &info.sender)?;
+    if from_balance.is_some() {
+        LOTTERY_BALANCE_TICKETS.save(deps.storage, (round.id, round.total_tickets), &Empty {})?;
+    }
+    let tickets = LOTTERY_PLAYER_TICKETS.update(deps.storage, (round.id, &info.sender), |tickets| -> StdResult<_> {
+        Ok(tickets.unwrap_or_default() + count)
+    })?;
+
+    // Ticket sales belong to the round's winners until the draw
+    util::reserve(deps.storage, amount)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "buy_tickets"),
+        attr("address", info.sender),
+        attr("round_id", round.id.to_string()),
+        attr("count", count.to_string()),
+        attr("tickets", tickets.to_string()),
+    ]))
+}
+
+/// Owner of a zero-based ticket number, i.e. the first block ending after it, and whether
+/// that block was paid from the ledger balance
+fn ticket_owner(storage: &dyn Storage, round_id: u64, ticket: u64) -> StdResult<(Addr, bool)> {
+    let (end, address) = LOTTERY_TICKETS.prefix(round_id)
+        .range(storage, Some(Bound::exclusive(ticket)), None, Order::Ascending)
+        .next()
+        .ok_or_else(|| StdError::not_found("ticket"))??;
+    Ok((address, LOTTERY_BALANCE_TICKETS.has(storage, (round_id, end))))
+}
+
+/// Anyone can draw once the round has ended. The winners only depend on the round's seed,
+/// which no sale can change after the end, so the sender and block of the draw make no difference.
+pub fn execute_draw_lottery(
+    deps: DepsMut,
+    env: Env,
+    round_id: u64
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let mut round = LOTTERY_ROUNDS.load(deps.storage, round_id)?;
+
+    if round.drawn {
+        return Err(ContractError::LotteryAlreadyDrawn {});
+    }
+    if env.block.time.seconds() < round.end_time {
+        return Err(ContractError::LotteryNotEnded { end_time: round.end_time });
+    }
+
+    util::release(deps.storage, round.pot)?;
+
+    let fee = round.pot * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
+    let prize_pool = round.pot - fee;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !fee.is_zero() {
+        messages.push(util::transfer_token_message(deps.querier, cfg.denom.clone(), fee, deps.api.addr_validate(constants::TREASURY_ADDR)?)?);
+    }
+
+    let seed = round.seed;
+
+    // Each tier draws a distinct ticket, so winning chances are proportional to tickets held.
+    // Tiers left without a ticket stay with the house.
+    let mut winners: Vec<LotteryWinner> = vec![];
+    let mut frozen = Uint128::zero();
+    for (tier, share) in round.prize_tiers.iter().enumerate() {
+        if winners.len() as u64 >= round.total_tickets {
+            break;
+        }
+
+        let mut nonce = 0u64;
+        let ticket = loop {
+            let ticket = util::calculate_hash(&(seed, tier, nonce)) % round.total_tickets;
+            if !winners.iter().any(|w| w.ticket == ticket) {
+                break ticket;
+            }
+            nonce += 1;
+        };
+
+        // The prize of a blocked winner is frozen in their balance
+        let (address, to_balance) = ticket_owner(deps.storage, round.id, ticket)?;
+        let amount = prize_pool * Uint128::from(*share) / Uint128::from(constants::BPS);
+        frozen += ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &address, amount, to_balance, &mut messages)?;
+        limits::record_return(deps.storage, &env, &address, amount)?;
+
+        winners.push(LotteryWinner {
+            tier: tier as u8 + 1,
+            address,
+            ticket,
+            amount
+        });
+    }
+
+    round.drawn = true;
+    round.fee = fee;
+    round.winners = winners;
+    LOTTERY_ROUNDS.save(deps.storage, round.id, &round)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "draw_lottery"),
+            attr("round_id", round.id.to_string()),
+            attr("pot", round.pot),
+            attr("fee", fee),
+            attr("winners", round.winners.len().to_string()),
+            attr("frozen", frozen),
+        ]))
+}
+
+pub fn query_lottery_round(deps: Deps, round_id: u64) -> StdResult<LotteryRound> {
+    LOTTERY_ROUNDS.load(deps.storage, round_id)
+}
+
+pub fn query_lottery_rounds(deps: Deps, count: u32) -> StdResult<LotteryRoundsResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let real_count = cfg.lottery_count.min(count as u64) as usize;
+
+    let mut list:Vec<LotteryRound> = vec![];
+    for i in 0..real_count {
+        list.push(LOTTERY_ROUNDS.load(deps.storage, cfg.lottery_count - i as u64)?);
+    }
+
+    Ok(LotteryRoundsResponse {
+        list
+    })
+}
+
+pub fn query_lottery_tickets(deps: Deps, round_id: u64, address: Addr) -> StdResult<LotteryTicketsResponse> {
+    let tickets = LOTTERY_PLAYER_TICKETS.may_load(deps.storage, (round_id, &address))?.unwrap_or_default();
+    Ok(LotteryTicketsResponse {
+        round_id,
+        address,
+        tickets
+    })
+}
@@ -0,0 +1,131 @@
+use cosmwasm_std::{
+    attr, Addr, Deps, DepsMut, MessageInfo, Response, StdResult, Storage, Uint128
+};
+use crate::error::ContractError;
+use crate::msg::{LoyaltyResponse, VipTier};
+use crate::state::{CONFIG, LOYALTY, LOYALTY_CONFIG, LoyaltyConfig};
+use crate::util;
+use crate::screening;
+use crate::constants;
+
+/// Tier reached with `wagered`, counted from 1, and its rakeback. Tier 0 earns no rakeback.
+fn tier(tiers: &[VipTier], wagered: Uint128) -> (u8, u64) {
+    let reached = tiers.iter().take_while(|t| t.threshold <= wagered).count();
+    match reached {
+        0 => (0, 0),
+        n => (n as u8, tiers[n - 1].rakeback_bps)
+    }
+}
+
+/// Counts a wager toward the player's volume and points, and accrues the rakeback of the
+/// player's tier on `fee`, which stays reserved until redeemed
+pub fn accrue(storage: &mut dyn Storage, player: &Addr, amount: Uint128, fee: Uint128) -> StdResult<Uint128> {
+    let config = LOYALTY_CONFIG.may_load(storage)?.unwrap_or_default();
+    let mut loyalty = LOYALTY.may_load(storage, player)?.unwrap_or_default();
+
+    let (_, rakeback_bps) = tier(&config.tiers, loyalty.wagered);
+    let rakeback = fee * Uint128::from(rakeback_bps) / Uint128::from(constants::BPS);
+
+    loyalty.wagered += amount;
+    loyalty.points += amount.multiply_ratio(config.points_rate, constants::MULTIPLY);
+    loyalty.rakeback += rakeback;
+    LOYALTY.save(storage, player, &loyalty)?;
+
+    if !rakeback.is_zero() {
+        util::reserve(storage, rakeback)?;
+    }
+    Ok(rakeback)
+}
+
+pub fn execute_update_loyalty(
+    deps: DepsMut,
+    info: MessageInfo,
+    points_rate: u64,
+    min_redeem_points: Uint128,
+    tiers: Vec<VipTier>
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    // Higher tiers need more volume and never pay less
+    if points_rate > constants::LOYALTY_MAX_POINTS_RATE
+        || tiers.len() > constants::LOYALTY_MAX_TIERS
+        || tiers.iter().any(|t| t.rakeback_bps > constants::BPS)
+        || tiers.windows(2).any(|w| w[1].threshold <= w[0].threshold || w[1].rakeback_bps < w[0].rakeback_bps)
+    {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    LOYALTY_CONFIG.save(deps.storage, &LoyaltyConfig {
+        points_rate,
+        min_redeem_points,
+        tiers
+    })?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_loyalty"),
+        attr("points_rate", points_rate.to_string()),
+        attr("min_redeem_points", min_redeem_points),
+    ]))
+}
+
+/// Converts `points` into rakeback. Points are shares of the rakeback accrued at the player's
+/// tiers, so each pays the accrued rakeback divided by the points held, and spending all of
+/// them pays all of it.
+pub fn execute_redeem_points(
+    deps: DepsMut,
+    info: MessageInfo,
+    points: Uint128
+) -> Result<Response, ContractError> {
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let config = LOYALTY_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+
+    let mut loyalty = LOYALTY.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+    if points.is_zero() || points < config.min_redeem_points {
+        return Err(ContractError::NotEnoughPoints { min: config.min_redeem_points });
+    }
+    if points > loyalty.points {
+        return Err(ContractError::InvalidInput {});
+    }
+    let amount = loyalty.rakeback.multiply_ratio(points, loyalty.points);
+    if amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    loyalty.points -= points;
+    loyalty.rakeback -= amount;
+    loyalty.redeemed += amount;
+    LOYALTY.save(deps.storage, &info.sender, &loyalty)?;
+    util::release(deps.storage, amount)?;
+
+    let message = util::transfer_token_message(deps.querier, cfg.denom, amount, info.sender.clone())?;
+
+    Ok(Response::new()
+        .add_message(message)
+        .add_attributes(vec![
+            attr("action", "redeem_points"),
+            attr("address", info.sender),
+            attr("points", points),
+            attr("amount", amount),
+        ]))
+}
+
+pub fn query_loyalty_config(deps: Deps) -> StdResult<LoyaltyConfig> {
+    Ok(LOYALTY_CONFIG.may_load(deps.storage)?.unwrap_or_default())
+}
+
+pub fn query_loyalty(deps: Deps, address: Addr) -> StdResult<LoyaltyResponse> {
+    let config = LOYALTY_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let loyalty = LOYALTY.may_load(deps.storage, &address)?.unwrap_or_default();
+    let (tier, rakeback_bps) = tier(&config.tiers, loyalty.wagered);
+    Ok(LoyaltyResponse {
+        address,
+        wagered: loyalty.wagered,
+        points: loyalty.points,
+        tier,
+        rakeback_bps,
+        claimable: loyalty.rakeback,
+        redeemed: loyalty.redeemed
+    })
+}
@@ -0,0 +1,302 @@
+use cosmwasm_std::{
+    attr, Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128
+};
+use crate::error::ContractError;
+use crate::msg::{HashObj, MHistory, MHistoryResponse, MinesGame, MinesGameResponse};
+use crate::state::{CONFIG, MHISTORY, MINES_GAMES, MINES_SEEDS};
+use crate::util;
+use crate::screening;
+use crate::jackpot;
+use crate::ledger;
+use crate::limits;
+use crate::rate_limit;
+use crate::payout_cap;
+use crate::settlement::{self, Bet};
+use crate::history;
+use crate::constants;
+
+/// Fair multiplier after `safe` revealed tiles scaled by `MULTIPLY`, the inverse of the odds of surviving them
+fn multiplier(grid: u8, mines: u8, safe: u32) -> u64 {
+    let mut numerator = constants::MULTIPLY as u128;
+    let mut denominator = 1u128;
+    for i in 0..safe as u128 {
+        numerator *= grid as u128 - i;
+        denominator *= (grid - mines) as u128 - i;
+    }
+    (numerator / denominator) as u64
+}
+
+/// Mine positions as a bitmask, the first `mines` tiles of a partial Fisher-Yates shuffle
+fn place_mines(grid: u8, mines: u8, seed: u64) -> u64 {
+    let mut tiles: Vec<u8> = (0..grid).collect();
+    let mut layout = 0u64;
+    for i in 0..mines as usize {
+        let j = i + (util::calculate_hash(&(seed, i)) % (tiles.len() - i) as u64) as usize;
+        tiles.swap(i, j);
+        layout |= 1u64 << tiles[i];
+    }
+    layout
+}
+
+fn commitment(seed: u64, layout: u64) -> u64 {
+    util::calculate_hash(&(seed, layout))
+}
+
+fn load_game(deps: &DepsMut, env: &Env, address: &Addr) -> Result<MinesGame, ContractError> {
+    let game = MINES_GAMES.may_load(deps.storage, address)?
+        .ok_or(ContractError::NoActiveGame {})?;
+    if env.block.time.seconds() >= game.expires_at {
+        return Err(ContractError::GameExpired {});
+    }
+    Ok(game)
+}
+
+pub fn execute_mines_start(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    grid: u8,
+    mines: u8,
+    from_balance: Option<Uint128>
+) -> Result<Response, ContractError> {
+
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+
+    if MINES_GAMES.has(deps.storage, &info.sender) {
+        return Err(ContractError::GameInProgress {});
+    }
+
+    let amount = ledger::take_stake(deps.storage, &info, cfg.denom.clone(), from_balance)?;
+    limits::check_bet(deps.storage, &env, &info.sender, amount)?;
+    rate_limit::check(deps.storage, &env, &info.sender, 1)?;
+
+    if !(constants::MINES_MIN_GRID..=constants::MINES_MAX_GRID).contains(&grid) || mines == 0 || mines >= grid {
+        return Err(ContractError::InvalidBet {});
+    }
+
+    let obj = HashObj {
+        time: env.block.time.seconds(),
+        address: info.sender.clone(),
+        level: ((grid as u64) << 8) | mines as u64,
+        count: cfg.mines_count
+    };
+    // The layout is fixed now, only its commitment is part of the game
+    let seed = util::calculate_hash(&(util::calculate_hash(&obj), util::block_entropy(&env)));
+    let commitment = commitment(seed, place_mines(grid, mines, seed));
+
+    let mut game = MinesGame {
+        address: info.sender.clone(),
+        grid,
+        mines,
+        commitment,
+        revealed: 0,
+        bet_amount: amount,
+        reserved: Uint128::zero(),
+        from_balance: from_balance.is_some(),
+        expires_at: env.block.time.seconds() + constants::MINES_TIMEOUT,
+        timestamp: env.block.time.seconds()
+    };
+
+    // Back the payout of clearing every safe tile
+    let max_multiplier = multiplier(grid, mines, (grid - mines) as u32);
+    let max_reward = amount * Uint128::from(max_multiplier) / Uint128::from(constants::MULTIPLY);
+    payout_cap::hold(deps.storage, &env, &info.sender, game.reserved, max_reward)?;
+    util::adjust_reservation(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone(), &mut game.reserved, max_reward)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    let jackpot_amount = jackpot::settle_jackpot(deps.storage, &obj, "mines", amount, &info.sender, env.block.time.seconds())?;
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, jackpot_amount, from_balance.is_some(), &mut messages)?;
+    limits::record_return(deps.storage, &env, &info.sender, jackpot_amount)?;
+
+    MINES_GAMES.save(deps.storage, &info.sender, &game)?;
+    MINES_SEEDS.save(deps.storage, &info.sender, &seed)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            attr("action", "mines_start"),
+            attr("address", info.sender.clone()),
+            attr("amount", amount),
+            attr("grid", grid.to_string()),
+            attr("mines", mines.to_string()),
+            attr("commitment", commitment.to_string()),
+            attr("jackpot", jackpot_amount),
+        ]))
+}
+
+pub fn execute_mines_reveal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tile: u8
+) -> Result<Response, ContractError> {
+
+    let mut game = load_game(&deps, &env, &info.sender)?;
+
+    if tile >= game.grid || game.revealed & (1u64 << tile) != 0 {
+        return Err(ContractError::InvalidAction {});
+    }
+
+    let seed = MINES_SEEDS.load(deps.storage, &info.sender)?;
+    if place_mines(game.grid, game.mines, seed) & (1u64 << tile) != 0 {
+        return settle(deps, env, game, true);
+    }
+
+    game.revealed |= 1u64 << tile;
+
+    if game.revealed.count_ones() == (game.grid - game.mines) as u32 {
+        return settle(deps, env, game, false);
+    }
+
+    game.expires_at = env.block.time.seconds() + constants::MINES_TIMEOUT;
+    MINES_GAMES.save(deps.storage, &info.sender, &game)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "mines_reveal"),
+        attr("address", info.sender),
+        attr("tile", tile.to_string()),
+        attr("multiplier", multiplier(game.grid, game.mines, game.revealed.count_ones()).to_string()),
+    ]))
+}
+
+pub fn execute_mines_cashout(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+
+    // Cashing out stays possible after the timeout
+    let game = MINES_GAMES.may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NoActiveGame {})?;
+
+    if game.revealed == 0 {
+        return Err(ContractError::InvalidAction {});
+    }
+
+    settle(deps, env, game, false)
+}
+
+/// Cashes out an abandoned game for the player at its current multiplier
+pub fn execute_mines_timeout(
+    deps: DepsMut,
+    env: Env,
+    address: Addr
+) -> Result<Response, ContractError> {
+
+    let game = MINES_GAMES.may_load(deps.storage, &address)?
+        .ok_or(ContractError::NoActiveGame {})?;
+
+    if env.block.time.seconds() < game.expires_at {
+        return Err(ContractError::GameNotExpired { expires_at: game.expires_at });
+    }
+
+    settle(deps, env, game, false)
+}
+
+/// Publishes the seed and layout of a finished game, which must match its commitment
+fn settle(
+    deps: DepsMut,
+    env: Env,
+    game: MinesGame,
+    busted: bool
+) -> Result<Response, ContractError> {
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    let seed = MINES_SEEDS.load(deps.storage, &game.address)?;
+    let layout = place_mines(game.grid, game.mines, seed);
+    if commitment(seed, layout) != game.commitment {
+        return Err(ContractError::CommitmentMismatch {});
+    }
+
+    let multiplier = if busted { 0 } else { multiplier(game.grid, game.mines, game.revealed.count_ones()) };
+
+    let amount = game.bet_amount;
+    let owner_amount = amount * Uint128::from(constants::OWNER_RATE) / Uint128::from(constants::MULTIPLY);
+    let reward_amount = (amount * Uint128::from(multiplier) / Uint128::from(constants::MULTIPLY)).saturating_sub(owner_amount);
+    let win = if reward_amount > amount { Some(0) } else { Some(1) };
+
+    let mut messages: Vec<CosmosMsg> = vec![
+        util::fee_message(deps.storage, deps.querier, deps.api, cfg.denom.clone(), &game.address, amount, owner_amount)?
+    ];
+    let frozen = ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &game.address, reward_amount, game.from_balance, &mut messages)?;
+
+    util::release(deps.storage, game.reserved)?;
+    payout_cap::hold(deps.storage, &env, &game.address, game.reserved, Uint128::zero())?;
+    MINES_GAMES.remove(deps.storage, &game.address);
+    MINES_SEEDS.remove(deps.storage, &game.address);
+
+    let record = MHistory {
+        id: cfg.mines_count + 1,
+        address: game.address.clone(),
+        grid: game.grid,
+        mines: game.mines,
+        seed,
+        layout,
+        revealed: game.revealed,
+        multiplier,
+        win,
+        bet_amount: amount,
+        payout: reward_amount,
+        timestamp: env.block.time.seconds()
+    };
+    MHISTORY.save(deps.storage, cfg.mines_count, &record)?;
+    let breaker = settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+        player: &game.address,
+        game: "mines",
+        count: cfg.mines_count,
+        win: record.win,
+        wagered: amount,
+        fee: owner_amount,
+        paid: reward_amount,
+        jackpot: Uint128::zero()
+    })?;
+
+    cfg.mines_count += 1;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_events(breaker)
+        .add_attributes(vec![
+            attr("action", "mines_settle"),
+            attr("address", game.address.clone()),
+            attr("amount", amount),
+            attr("seed", seed.to_string()),
+            attr("layout", layout.to_string()),
+            attr("multiplier", multiplier.to_string()),
+            attr("payout", reward_amount),
+            attr("frozen", frozen),
+            attr("win", win.expect("u8").to_string()),
+        ]))
+}
+
+pub fn query_mines_game(deps: Deps, address: Addr) -> StdResult<MinesGameResponse> {
+    let game = MINES_GAMES.may_load(deps.storage, &address)?;
+    let multiplier = game.as_ref()
+        .map(|g| multiplier(g.grid, g.mines, g.revealed.count_ones()))
+        .unwrap_or_default();
+    Ok(MinesGameResponse {
+        game,
+        multiplier
+    })
+}
+
+pub fn query_mhistory(deps: Deps, count: u32) -> StdResult<MHistoryResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let real_count = (cfg.mines_count - history::first_retained(deps.storage, "mines")?).min(count as u64) as usize;
+
+    let mut list:Vec<MHistory> = vec![];
+    for i in 0..real_count {
+        list.push(MHISTORY.load(deps.storage, cfg.mines_count - 1 - i as u64)?);
+    }
+
+    Ok(MHistoryResponse {
+        list
+    })
+}
@@ -1,7 +1,8 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use cosmwasm_std::{Uint128, Addr};
+use cosmwasm_std::{Int128, Uint128, Addr};
 use cw20::Denom;
+use crate::constants;
 
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct InstantiateMsg {}
@@ -16,16 +17,195 @@ pub enum ExecuteMsg {
         enabled: bool,
     },
     Flip {
-        level: u64
+        level: u64,
+        from_balance: Option<Uint128>
     },
     Rps {
-        level: u64
+        level: u64,
+        from_balance: Option<Uint128>
     },
     Dice {
-        level: u64
+        level: u64,
+        from_balance: Option<Uint128>
     },
     Roulette {
-        level: u64
+        level: u64,
+        from_balance: Option<Uint128>
+    },
+    Plinko {
+        rows: u8,
+        risk: PlinkoRisk,
+        from_balance: Option<Uint128>
+    },
+    UpdatePlinkoTable {
+        rows: u8,
+        risk: PlinkoRisk,
+        multipliers: Vec<u64>
+    },
+    Keno {
+        picks: Vec<u8>,
+        from_balance: Option<Uint128>
+    },
+    UpdateKenoTable {
+        picks: u8,
+        multipliers: Vec<u64>
+    },
+    UpdateJackpot {
+        contribution_bps: u64,
+        odds: u64
+    },
+    SeedJackpot {},
+    OpenLottery {
+        ticket_price: Uint128,
+        end_time: u64,
+        prize_tiers: Vec<u64>
+    },
+    BuyTickets {
+        count: u64,
+        from_balance: Option<Uint128>
+    },
+    DrawLottery {
+        round_id: u64
+    },
+    BlackjackDeal {
+        from_balance: Option<Uint128>
+    },
+    BlackjackHit {},
+    BlackjackStand {},
+    BlackjackDouble {},
+    BlackjackSplit {},
+    BlackjackTimeout {
+        address: Addr
+    },
+    Slots {
+        from_balance: Option<Uint128>
+    },
+    UpdateSlots {
+        reels: Vec<Vec<u8>>,
+        rows: u8,
+        paylines: Vec<Vec<u8>>,
+        paytable: Vec<SlotsPay>
+    },
+    MinesStart {
+        grid: u8,
+        mines: u8,
+        from_balance: Option<Uint128>
+    },
+    MinesReveal {
+        tile: u8
+    },
+    MinesCashout {},
+    MinesTimeout {
+        address: Addr
+    },
+    HiloStart {
+        from_balance: Option<Uint128>
+    },
+    HiloGuess {
+        choice: HiloChoice
+    },
+    HiloCashout {},
+    HiloTimeout {
+        address: Addr
+    },
+    Wheel {
+        preset: u8,
+        from_balance: Option<Uint128>
+    },
+    UpdateWheelPreset {
+        preset: u8,
+        segments: Vec<WheelSegment>
+    },
+    BatchBet {
+        game: BatchGame,
+        selection: u64,
+        rounds: u32,
+        stake_per_round: Uint128,
+        from_balance: Option<Uint128>
+    },
+    AutoBet {
+        game: BatchGame,
+        selection: u64,
+        strategy: AutoStrategy,
+        base_stake: Uint128,
+        rounds: u32,
+        stop_loss: Option<Uint128>,
+        take_profit: Option<Uint128>,
+        from_balance: Option<Uint128>
+    },
+    SetReferrer {
+        referrer: Addr
+    },
+    UpdateReferralShare {
+        share_bps: u64
+    },
+    ClaimReferral {},
+    UpdateLoyalty {
+        points_rate: u64,
+        min_redeem_points: Uint128,
+        tiers: Vec<VipTier>
+    },
+    RedeemPoints {
+        points: Uint128
+    },
+    IssueVoucher {
+        address: Addr,
+        amount: Uint128,
+        game: Option<BatchGame>,
+        expires_at: u64
+    },
+    RevokeVoucher {
+        voucher_id: u64
+    },
+    VoucherBet {
+        voucher_id: u64,
+        game: BatchGame,
+        selection: u64
+    },
+    SetLimits {
+        limits: BetLimits
+    },
+    SelfExclude {
+        until: u64
+    },
+    UpdateBlocklist {
+        add: Vec<Addr>,
+        remove: Vec<Addr>
+    },
+    UpdateScreening {
+        contract: Option<Addr>
+    },
+    UpdateRateLimits {
+        limits: RateLimits
+    },
+    PruneRateUsage {
+        start_after: Option<Addr>,
+        limit: Option<u32>
+    },
+    UpdateBreaker {
+        max_drawdown_bps: u64,
+        window: u64
+    },
+    ResetBreaker {},
+    UpdatePayoutCaps {
+        caps: PayoutCaps
+    },
+    PayLeaderboard {
+        period: LeaderboardPeriod,
+        kind: LeaderboardKind,
+        index: u64,
+        prizes: Vec<Uint128>
+    },
+    UpdateHistoryRetention {
+        retention: HistoryRetention
+    },
+    PruneHistory {
+        game: String,
+        limit: Option<u32>
+    },
+    Deposit {},
+    WithdrawBalance {
+        amount: Uint128
     },
     Withdraw {
         amount: Uint128
@@ -47,6 +227,154 @@ pub enum QueryMsg {
     },
     BistoryMsg {
         count: u32
+    },
+    PistoryMsg {
+        count: u32
+    },
+    PlinkoTable {
+        rows: u8,
+        risk: PlinkoRisk
+    },
+    KistoryMsg {
+        count: u32
+    },
+    KenoTable {
+        picks: u8
+    },
+    Jackpot {},
+    JackpotWinners {
+        count: u32
+    },
+    LotteryRound {
+        round_id: u64
+    },
+    LotteryRounds {
+        count: u32
+    },
+    LotteryTickets {
+        round_id: u64,
+        address: Addr
+    },
+    JistoryMsg {
+        count: u32
+    },
+    BlackjackGame {
+        address: Addr
+    },
+    SistoryMsg {
+        count: u32
+    },
+    SlotsConfig {},
+    MistoryMsg {
+        count: u32
+    },
+    MinesGame {
+        address: Addr
+    },
+    HiloHistory {
+        count: u32
+    },
+    HiloGame {
+        address: Addr
+    },
+    WistoryMsg {
+        count: u32
+    },
+    WheelPreset {
+        preset: u8
+    },
+    WheelPresets {},
+    Balance {
+        address: Addr
+    },
+    Balances {
+        start_after: Option<Addr>,
+        limit: Option<u32>
+    },
+    ReferralConfig {},
+    Referrer {
+        address: Addr
+    },
+    Referrals {
+        referrer: Addr,
+        start_after: Option<Addr>,
+        limit: Option<u32>
+    },
+    ReferrerEarnings {
+        address: Addr
+    },
+    TopReferrers {
+        limit: Option<u32>
+    },
+    LoyaltyConfig {},
+    Loyalty {
+        address: Addr
+    },
+    Vouchers {
+        address: Addr,
+        start_after: Option<u64>,
+        limit: Option<u32>
+    },
+    Limits {
+        address: Addr
+    },
+    IsBlocked {
+        address: Addr
+    },
+    Blocklist {
+        start_after: Option<Addr>,
+        limit: Option<u32>
+    },
+    ScreeningConfig {},
+    RateLimits {},
+    RateUsage {
+        address: Addr
+    },
+    Breaker {},
+    PayoutCaps {},
+    PayoutAllowance {
+        address: Addr
+    },
+    Stats {
+        game: String
+    },
+    PlayerStats {
+        address: Addr
+    },
+    Leaderboard {
+        period: LeaderboardPeriod,
+        kind: LeaderboardKind,
+        index: Option<u64>,
+        limit: Option<u32>
+    },
+    /// Returns a `HistoryPage` of the game's own history records
+    FilteredHistory {
+        game: String,
+        filter: HistoryFilter,
+        start_after: Option<u64>,
+        limit: Option<u32>
+    },
+    HistoryRetention {},
+    OldestHistory {
+        game: String
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PlinkoRisk {
+    Low,
+    Medium,
+    High
+}
+
+impl PlinkoRisk {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlinkoRisk::Low => "low",
+            PlinkoRisk::Medium => "medium",
+            PlinkoRisk::High => "high",
+        }
     }
 }
 
@@ -61,6 +389,15 @@ pub struct ConfigResponse {
     pub rps_count: u64,
     pub dice_count: u64,
     pub roulette_count: u64,
+    pub plinko_count: u64,
+    pub keno_count: u64,
+    pub lottery_count: u64,
+    pub blackjack_count: u64,
+    pub slots_count: u64,
+    pub mines_count: u64,
+    pub hilo_count: u64,
+    pub wheel_count: u64,
+    pub voucher_count: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -123,6 +460,823 @@ pub struct BHistoryResponse {
     pub list: Vec<BHistory>
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PHistory {
+    pub id: u64,
+    pub address: Addr,
+    pub rows: u8,
+    pub risk: PlinkoRisk,
+    /// One bit per row, lowest bit first; a set bit bounces right
+    pub path: u64,
+    pub slot: u8,
+    pub multiplier: u64,
+    pub win: Option<u8>,
+    pub bet_amount: Uint128,
+    pub payout: Uint128,
+    pub timestamp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PHistoryResponse {
+    pub list: Vec<PHistory>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlinkoTableResponse {
+    pub rows: u8,
+    pub risk: PlinkoRisk,
+    /// Slot multipliers scaled by `MULTIPLY`, left to right
+    pub multipliers: Vec<u64>,
+    /// Expected return to player scaled by `MULTIPLY`
+    pub rtp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct KHistory {
+    pub id: u64,
+    pub address: Addr,
+    pub picks: Vec<u8>,
+    /// Drawn numbers in draw order
+    pub draws: Vec<u8>,
+    pub hits: u8,
+    pub multiplier: u64,
+    pub win: Option<u8>,
+    pub bet_amount: Uint128,
+    pub payout: Uint128,
+    pub timestamp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct KHistoryResponse {
+    pub list: Vec<KHistory>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct KenoTableResponse {
+    pub picks: u8,
+    /// Multipliers by hit count scaled by `MULTIPLY`
+    pub multipliers: Vec<u64>,
+    /// Expected return to player scaled by `MULTIPLY`
+    pub rtp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotResponse {
+    pub amount: Uint128,
+    pub contribution_bps: u64,
+    pub odds: u64,
+    pub win_count: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotWinner {
+    pub id: u64,
+    pub address: Addr,
+    pub game: String,
+    pub amount: Uint128,
+    pub timestamp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotWinnersResponse {
+    pub list: Vec<JackpotWinner>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LotteryRound {
+    pub id: u64,
+    pub ticket_price: Uint128,
+    pub end_time: u64,
+    /// Share of the prize pool per tier in basis points, first prize first
+    pub prize_tiers: Vec<u64>,
+    pub total_tickets: u64,
+    pub pot: Uint128,
+    pub drawn: bool,
+    pub fee: Uint128,
+    pub winners: Vec<LotteryWinner>,
+    /// Mixes in the block of every ticket sale, final once the round ends
+    pub seed: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LotteryWinner {
+    pub tier: u8,
+    pub address: Addr,
+    pub ticket: u64,
+    pub amount: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LotteryRoundsResponse {
+    pub list: Vec<LotteryRound>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LotteryTicketsResponse {
+    pub round_id: u64,
+    pub address: Addr,
+    pub tickets: u64
+}
+
+/// Cards are numbered 0..52, see `util::card_rank`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlackjackHand {
+    pub cards: Vec<u8>,
+    pub stake: Uint128,
+    pub doubled: bool,
+    pub done: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlackjackGame {
+    pub address: Addr,
+    /// Committed at the deal, every card also mixes in the block of the action drawing it
+    pub seed: u64,
+    pub bet_amount: Uint128,
+    pub dealer: Vec<u8>,
+    pub hands: Vec<BlackjackHand>,
+    /// Index of the hand being played
+    pub active: u8,
+    /// Every card dealt from the deck so far
+    pub drawn: Vec<u8>,
+    pub reserved: Uint128,
+    /// Stake was taken from the player balance, payouts go back to it
+    #[serde(default)]
+    pub from_balance: bool,
+    pub expires_at: u64,
+    pub timestamp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlackjackGameResponse {
+    pub game: Option<BlackjackGame>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JHistory {
+    pub id: u64,
+    pub address: Addr,
+    pub seed: u64,
+    pub hands: Vec<BlackjackHand>,
+    pub dealer: Vec<u8>,
+    /// 0 win, 1 push, 2 lose
+    pub win: Option<u8>,
+    pub bet_amount: Uint128,
+    pub payout: Uint128,
+    pub timestamp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JHistoryResponse {
+    pub list: Vec<JHistory>
+}
+
+/// Pays `multiplier` (scaled by `MULTIPLY`) of the line bet for `count` matching symbols from the leftmost reel
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SlotsPay {
+    pub symbol: u8,
+    pub count: u8,
+    pub multiplier: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SlotsConfigResponse {
+    pub reels: Vec<Vec<u8>>,
+    pub rows: u8,
+    pub paylines: Vec<Vec<u8>>,
+    pub paytable: Vec<SlotsPay>,
+    /// Expected return to player scaled by `MULTIPLY`
+    pub rtp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SHistory {
+    pub id: u64,
+    pub address: Addr,
+    /// Stop position of every reel, the top visible row
+    pub stops: Vec<u64>,
+    /// Total payout over the stake scaled by `MULTIPLY`
+    pub multiplier: u64,
+    pub win: Option<u8>,
+    pub bet_amount: Uint128,
+    pub payout: Uint128,
+    pub timestamp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SHistoryResponse {
+    pub list: Vec<SHistory>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinesGame {
+    pub address: Addr,
+    pub grid: u8,
+    pub mines: u8,
+    /// Hash of the seed and mine layout drawn at the start, both published when the game settles
+    pub commitment: u64,
+    pub revealed: u64,
+    pub bet_amount: Uint128,
+    pub reserved: Uint128,
+    /// Stake was taken from the player balance, payouts go back to it
+    #[serde(default)]
+    pub from_balance: bool,
+    pub expires_at: u64,
+    pub timestamp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinesGameResponse {
+    pub game: Option<MinesGame>,
+    /// Current cash-out multiplier scaled by `MULTIPLY`
+    pub multiplier: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MHistory {
+    pub id: u64,
+    pub address: Addr,
+    pub grid: u8,
+    pub mines: u8,
+    /// Seed the layout was drawn from, hashed with it into the game's commitment
+    pub seed: u64,
+    /// Mine positions as a bitmask
+    pub layout: u64,
+    pub revealed: u64,
+    pub multiplier: u64,
+    pub win: Option<u8>,
+    pub bet_amount: Uint128,
+    pub payout: Uint128,
+    pub timestamp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MHistoryResponse {
+    pub list: Vec<MHistory>
+}
+
+/// Guess on the next card's rank, a card of the same rank wins either way
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HiloChoice {
+    Higher,
+    Lower
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HiloGame {
+    pub address: Addr,
+    /// Committed at the start, every card also mixes in the block of the guess drawing it
+    pub seed: u64,
+    /// Cards in draw order, the last one is showing
+    pub cards: Vec<u8>,
+    pub guesses: Vec<HiloChoice>,
+    /// Pending payout over the stake scaled by `MULTIPLY`
+    pub multiplier: u64,
+    pub bet_amount: Uint128,
+    pub reserved: Uint128,
+    /// Stake was taken from the player balance, payouts go back to it
+    #[serde(default)]
+    pub from_balance: bool,
+    pub expires_at: u64,
+    pub timestamp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HiloGameResponse {
+    pub game: Option<HiloGame>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HHistory {
+    pub id: u64,
+    pub address: Addr,
+    pub seed: u64,
+    pub cards: Vec<u8>,
+    pub guesses: Vec<HiloChoice>,
+    pub multiplier: u64,
+    pub win: Option<u8>,
+    pub bet_amount: Uint128,
+    pub payout: Uint128,
+    pub timestamp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HHistoryResponse {
+    pub list: Vec<HHistory>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WheelSegment {
+    /// Scaled by `MULTIPLY`
+    pub multiplier: u64,
+    /// Relative chance of the segment, also its share of the wheel
+    pub weight: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WheelPresetResponse {
+    pub preset: u8,
+    pub segments: Vec<WheelSegment>,
+    /// Expected return to player scaled by `MULTIPLY`
+    pub rtp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WheelPresetsResponse {
+    pub list: Vec<WheelPresetResponse>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WHistory {
+    pub id: u64,
+    pub address: Addr,
+    pub preset: u8,
+    pub segment: u8,
+    pub multiplier: u64,
+    pub win: Option<u8>,
+    pub bet_amount: Uint128,
+    pub payout: Uint128,
+    pub timestamp: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WHistoryResponse {
+    pub list: Vec<WHistory>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BalanceResponse {
+    pub address: Addr,
+    pub balance: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BalancesResponse {
+    pub list: Vec<BalanceResponse>,
+    /// Sum of every player balance
+    pub total: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReferralConfigResponse {
+    pub share_bps: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReferrerResponse {
+    pub address: Addr,
+    pub referrer: Option<Addr>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReferralsResponse {
+    pub referrer: Addr,
+    pub list: Vec<Addr>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ReferrerEarnings {
+    pub referrals: u64,
+    /// Commissions accrued over all time, what is not claimed yet can be claimed
+    pub earned: Uint128,
+    pub claimed: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TopReferrer {
+    pub address: Addr,
+    pub earned: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TopReferrersResponse {
+    pub list: Vec<TopReferrer>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VipTier {
+    /// Wagered volume that reaches the tier
+    pub threshold: Uint128,
+    /// Share of the house fee paid back to players of the tier, in basis points
+    pub rakeback_bps: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LoyaltyResponse {
+    pub address: Addr,
+    pub wagered: Uint128,
+    pub points: Uint128,
+    /// Tiers reached, 0 before the first one
+    pub tier: u8,
+    pub rakeback_bps: u64,
+    pub claimable: Uint128,
+    pub redeemed: Uint128
+}
+
+/// Bet rate caps, 0 turns a cap off
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct RateLimits {
+    /// Bets per address in one block
+    pub per_block: u32,
+    /// Bets per address in a window of `window` seconds
+    pub per_window: u32,
+    pub window: u64,
+    /// Bets of all addresses in one block
+    pub global_per_block: u32
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct RateUsage {
+    pub height: u64,
+    pub block_count: u32,
+    pub window_start: u64,
+    pub window_count: u32
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateUsageResponse {
+    pub address: Addr,
+    pub usage: RateUsage
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BreakerTrip {
+    pub at: u64,
+    pub loss: Uint128,
+    /// Bankroll the window started from
+    pub bankroll: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BreakerResponse {
+    pub max_drawdown_bps: u64,
+    pub window: u64,
+    /// House PnL of the current window
+    pub pnl: Int128,
+    pub trip: Option<BreakerTrip>
+}
+
+/// Daily payout caps, None being no cap. Jackpot wins are paid from the pool bets fund and are not capped.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PayoutCaps {
+    pub player_daily: Option<Uint128>,
+    pub global_daily: Option<Uint128>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PayoutAllowanceResponse {
+    pub address: Addr,
+    pub player_paid: Uint128,
+    /// Still payable by the address's open games, counted toward the cap
+    pub player_pending: Uint128,
+    pub player_remaining: Option<Uint128>,
+    pub global_paid: Uint128,
+    pub global_pending: Uint128,
+    pub global_remaining: Option<Uint128>,
+    /// Both totals start over at this time
+    pub resets_at: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct GameStats {
+    pub bets: u64,
+    pub wagered: Uint128,
+    pub paid_out: Uint128,
+    pub fees: Uint128,
+    /// Stakes less fees and payouts, jackpots aside
+    pub house_pnl: Int128,
+    pub wins: u64,
+    pub losses: u64,
+    pub ties: u64,
+    pub largest_win: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    pub game: String,
+    pub stats: GameStats
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PlayerStats {
+    pub bets: u64,
+    pub wagered: Uint128,
+    pub won: Uint128,
+    /// Payouts less stakes, jackpots aside
+    pub net_pnl: Int128,
+    pub biggest_win: Uint128,
+    pub current_streak: u32,
+    pub longest_streak: u32
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlayerGameStats {
+    pub game: String,
+    pub stats: PlayerStats
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlayerStatsResponse {
+    pub address: Addr,
+    pub games: Vec<PlayerGameStats>
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardPeriod {
+    Daily,
+    Weekly
+}
+
+impl LeaderboardPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeaderboardPeriod::Daily => "daily",
+            LeaderboardPeriod::Weekly => "weekly",
+        }
+    }
+
+    /// Length in seconds, period indexes count these from the epoch
+    pub fn length(&self) -> u64 {
+        match self {
+            LeaderboardPeriod::Daily => constants::DAY,
+            LeaderboardPeriod::Weekly => constants::WEEK,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardKind {
+    Wagered,
+    Profit
+}
+
+impl LeaderboardKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeaderboardKind::Wagered => "wagered",
+            LeaderboardKind::Profit => "profit",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeaderboardEntry {
+    pub rank: u32,
+    pub address: Addr,
+    pub wagered: Uint128,
+    pub profit: Int128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeaderboardResponse {
+    pub period: LeaderboardPeriod,
+    pub kind: LeaderboardKind,
+    pub index: u64,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    pub list: Vec<LeaderboardEntry>,
+    /// Prizes paid for the board, if any
+    pub paid: Option<Uint128>
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryOutcome {
+    Win,
+    Lose,
+    Tie
+}
+
+/// Every set field must match, bounds are inclusive
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct HistoryFilter {
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub outcome: Option<HistoryOutcome>,
+    pub min_bet: Option<Uint128>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HistoryPage<T> {
+    /// Newest first
+    pub list: Vec<T>,
+    /// Id to pass as `start_after` for the next page, None once every record was looked at
+    pub next: Option<u64>
+}
+
+/// Records kept by `PruneHistory`, None keeping nothing by that rule and both None keeping everything
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct HistoryRetention {
+    /// Number of latest records kept
+    pub keep_last: Option<u64>,
+    /// Records newer than this many seconds are kept
+    pub max_age: Option<u64>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OldestHistoryResponse {
+    pub game: String,
+    /// None when no record is retained
+    pub oldest_id: Option<u64>,
+    pub retained: u64
+}
+
+/// Query the screening contract has to answer with a `ScreeningResponse`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreeningQueryMsg {
+    IsBlocked {
+        address: String
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ScreeningResponse {
+    pub blocked: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlockedResponse {
+    pub address: Addr,
+    pub blocked: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlocklistResponse {
+    pub list: Vec<Addr>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ScreeningConfigResponse {
+    pub contract: Option<Addr>
+}
+
+/// Caps a player sets on their own betting, None is no cap
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct BetLimits {
+    pub daily_wager: Option<Uint128>,
+    pub weekly_wager: Option<Uint128>,
+    pub monthly_wager: Option<Uint128>,
+    /// Caps on stakes minus returns
+    pub daily_loss: Option<Uint128>,
+    pub weekly_loss: Option<Uint128>,
+    pub monthly_loss: Option<Uint128>,
+    /// Seconds to wait between bets
+    pub cooldown: u64
+}
+
+/// Stakes and returns of a player within one day, week or month
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct LimitWindow {
+    /// Block time divided by the window length
+    pub period: u64,
+    pub wagered: Uint128,
+    pub returned: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LimitsResponse {
+    pub address: Addr,
+    pub limits: BetLimits,
+    pub pending: Option<BetLimits>,
+    pub pending_at: u64,
+    pub excluded_until: u64,
+    pub last_bet: u64,
+    pub daily: LimitWindow,
+    pub weekly: LimitWindow,
+    pub monthly: LimitWindow
+}
+
+/// Free bet handed out by the owner, good for one round
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Voucher {
+    pub id: u64,
+    pub address: Addr,
+    /// Stake of the free bet
+    pub amount: Uint128,
+    /// Game the voucher is restricted to, any batch game when None
+    pub game: Option<BatchGame>,
+    pub expires_at: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VouchersResponse {
+    pub list: Vec<Voucher>
+}
+
+/// Games decided by a single `level` selection, which can be played many rounds at once
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchGame {
+    Flip,
+    Rps,
+    Dice,
+    Roulette
+}
+
+impl BatchGame {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BatchGame::Flip => "flip",
+            BatchGame::Rps => "rps",
+            BatchGame::Dice => "dice",
+            BatchGame::Roulette => "roulette",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchRound {
+    /// Id of the round's record in the game history
+    pub id: u64,
+    /// Side, move or number drawn by the house
+    pub result: u64,
+    /// 0 for a win, 1 for a loss or a rock paper scissors tie, 2 for a rock paper scissors loss
+    pub win: u8,
+    pub bet_amount: Uint128,
+    pub payout: Uint128,
+    pub jackpot: Uint128
+}
+
+/// Returned as the response data of a batch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchBetResponse {
+    pub game: BatchGame,
+    pub rounds: Vec<BatchRound>,
+    pub total_stake: Uint128,
+    /// Payouts and jackpots of every round, paid out together
+    pub total_payout: Uint128
+}
+
+/// Stake progression of an auto-bet, every strategy starts at the base stake
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoStrategy {
+    /// Always the base stake
+    Flat,
+    /// Doubles after a loss, back to the base after a win
+    Martingale,
+    /// Doubles after a win, back to the base after a loss
+    AntiMartingale,
+    /// One base stake more after a loss, one less after a win
+    DAlembert
+}
+
+impl AutoStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AutoStrategy::Flat => "flat",
+            AutoStrategy::Martingale => "martingale",
+            AutoStrategy::AntiMartingale => "anti_martingale",
+            AutoStrategy::DAlembert => "d_alembert",
+        }
+    }
+}
+
+/// Why an auto-bet stopped
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoBetStop {
+    /// Every round was played
+    Rounds,
+    StopLoss,
+    TakeProfit,
+    /// The next stake was more than the player had left
+    Budget,
+    /// The house could not cover the next stake
+    Bankroll,
+    /// The next stake would break one of the player's limits, a payout cap or a rate limit
+    Limit
+}
+
+impl AutoBetStop {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AutoBetStop::Rounds => "rounds",
+            AutoBetStop::StopLoss => "stop_loss",
+            AutoBetStop::TakeProfit => "take_profit",
+            AutoBetStop::Budget => "budget",
+            AutoBetStop::Bankroll => "bankroll",
+            AutoBetStop::Limit => "limit",
+        }
+    }
+}
+
+/// Returned as the response data of an auto-bet
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AutoBetResponse {
+    pub game: BatchGame,
+    pub strategy: AutoStrategy,
+    pub rounds: Vec<BatchRound>,
+    pub rounds_played: u32,
+    pub total_stake: Uint128,
+    pub total_payout: Uint128,
+    pub net_profit: Int128,
+    pub stop: AutoBetStop
+}
 
 #[derive(Hash)]
 pub struct HashObj {
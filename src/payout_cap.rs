@@ -0,0 +1,118 @@
+use cosmwasm_std::{
+    attr, Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Uint128
+};
+use crate::error::ContractError;
+use crate::msg::{PayoutAllowanceResponse, PayoutCaps};
+use crate::state::{DailyPayout, GLOBAL_PAYOUT, GLOBAL_PENDING_PAYOUT, PAYOUT_CAPS, PENDING_PAYOUTS, PLAYER_PAYOUTS};
+use crate::util;
+use crate::constants;
+
+/// Payouts of the day containing `now`, those of earlier days start over
+fn current(payout: Option<DailyPayout>, now: u64) -> DailyPayout {
+    let day = now / constants::DAY;
+    match payout {
+        Some(payout) if payout.day == day => payout,
+        _ => DailyPayout { day, paid: Uint128::zero() }
+    }
+}
+
+/// What `player` and everyone together can still be paid today, None being no cap.
+/// Payouts open games may still make count as paid.
+fn remaining(storage: &dyn Storage, now: u64, player: &Addr) -> StdResult<(Option<Uint128>, Option<Uint128>)> {
+    let caps = PAYOUT_CAPS.may_load(storage)?.unwrap_or_default();
+    let player_paid = current(PLAYER_PAYOUTS.may_load(storage, player)?, now).paid
+        + PENDING_PAYOUTS.may_load(storage, player)?.unwrap_or_default();
+    let global_paid = current(GLOBAL_PAYOUT.may_load(storage)?, now).paid
+        + GLOBAL_PENDING_PAYOUT.may_load(storage)?.unwrap_or_default();
+    Ok((
+        caps.player_daily.map(|cap| cap.saturating_sub(player_paid)),
+        caps.global_daily.map(|cap| cap.saturating_sub(global_paid))
+    ))
+}
+
+/// Rejects a bet whose largest possible payout would go over today's caps. Jackpot wins are
+/// paid from the pool funded by bets, so they are neither checked nor recorded.
+pub fn check(storage: &dyn Storage, env: &Env, player: &Addr, max_payout: Uint128) -> Result<(), ContractError> {
+    let (player_remaining, global_remaining) = remaining(storage, env.block.time.seconds(), player)?;
+    if let Some(remaining) = player_remaining {
+        if max_payout > remaining {
+            return Err(ContractError::PlayerPayoutCapExceeded { remaining });
+        }
+    }
+    if let Some(remaining) = global_remaining {
+        if max_payout > remaining {
+            return Err(ContractError::GlobalPayoutCapExceeded { remaining });
+        }
+    }
+    Ok(())
+}
+
+/// Moves the largest payout an open game of `player` can still make from `held` to `needed`,
+/// checking any increase against the caps. Settling games hold nothing.
+pub fn hold(storage: &mut dyn Storage, env: &Env, player: &Addr, held: Uint128, needed: Uint128) -> Result<(), ContractError> {
+    if needed > held {
+        check(storage, env, player, needed - held)?;
+    }
+
+    let pending = PENDING_PAYOUTS.may_load(storage, player)?.unwrap_or_default().saturating_sub(held) + needed;
+    if pending.is_zero() {
+        PENDING_PAYOUTS.remove(storage, player);
+    } else {
+        PENDING_PAYOUTS.save(storage, player, &pending)?;
+    }
+
+    let global = GLOBAL_PENDING_PAYOUT.may_load(storage)?.unwrap_or_default().saturating_sub(held) + needed;
+    GLOBAL_PENDING_PAYOUT.save(storage, &global)?;
+    Ok(())
+}
+
+/// Counts what a settled bet paid to `player` toward today's totals
+pub fn record(storage: &mut dyn Storage, env: &Env, player: &Addr, paid: Uint128) -> StdResult<()> {
+    if paid.is_zero() {
+        return Ok(());
+    }
+    let now = env.block.time.seconds();
+
+    let mut payout = current(PLAYER_PAYOUTS.may_load(storage, player)?, now);
+    payout.paid += paid;
+    PLAYER_PAYOUTS.save(storage, player, &payout)?;
+
+    let mut payout = current(GLOBAL_PAYOUT.may_load(storage)?, now);
+    payout.paid += paid;
+    GLOBAL_PAYOUT.save(storage, &payout)
+}
+
+pub fn execute_update_payout_caps(
+    deps: DepsMut,
+    info: MessageInfo,
+    caps: PayoutCaps
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    PAYOUT_CAPS.save(deps.storage, &caps)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_payout_caps"),
+        attr("player_daily", caps.player_daily.map(|c| c.to_string()).unwrap_or_default()),
+        attr("global_daily", caps.global_daily.map(|c| c.to_string()).unwrap_or_default()),
+    ]))
+}
+
+pub fn query_payout_caps(deps: Deps) -> StdResult<PayoutCaps> {
+    Ok(PAYOUT_CAPS.may_load(deps.storage)?.unwrap_or_default())
+}
+
+pub fn query_payout_allowance(deps: Deps, env: Env, address: Addr) -> StdResult<PayoutAllowanceResponse> {
+    let now = env.block.time.seconds();
+    let (player_remaining, global_remaining) = remaining(deps.storage, now, &address)?;
+    Ok(PayoutAllowanceResponse {
+        player_paid: current(PLAYER_PAYOUTS.may_load(deps.storage, &address)?, now).paid,
+        player_pending: PENDING_PAYOUTS.may_load(deps.storage, &address)?.unwrap_or_default(),
+        player_remaining,
+        global_paid: current(GLOBAL_PAYOUT.may_load(deps.storage)?, now).paid,
+        global_pending: GLOBAL_PENDING_PAYOUT.may_load(deps.storage)?.unwrap_or_default(),
+        global_remaining,
+        resets_at: (now / constants::DAY + 1) * constants::DAY,
+        address
+    })
+}
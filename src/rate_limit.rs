@@ -0,0 +1,127 @@
+use cosmwasm_std::{
+    attr, Addr, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage
+};
+use cw_storage_plus::Bound;
+use crate::error::ContractError;
+use crate::msg::{RateLimits, RateUsage, RateUsageResponse};
+use crate::state::{GLOBAL_RATE, RATE_LIMITS, RATE_USAGE};
+use crate::util;
+use crate::constants;
+
+/// Usage of the current block and window, counters of past ones start over
+fn current_usage(usage: RateUsage, env: &Env, limits: &RateLimits) -> RateUsage {
+    let height = env.block.height;
+    let now = env.block.time.seconds();
+    let mut usage = usage;
+    if usage.height != height {
+        usage.height = height;
+        usage.block_count = 0;
+    }
+    if limits.window == 0 || now >= usage.window_start + limits.window {
+        usage.window_start = now;
+        usage.window_count = 0;
+    }
+    usage
+}
+
+/// Counts `bets` bets of `player` against the per-address and global limits, a limit of 0 is off.
+/// Called by every game entry point, batches count each of their rounds. Nothing is counted
+/// when a limit would be passed.
+pub fn check(storage: &mut dyn Storage, env: &Env, player: &Addr, bets: u32) -> Result<(), ContractError> {
+    let limits = RATE_LIMITS.may_load(storage)?.unwrap_or_default();
+
+    let mut global = GLOBAL_RATE.may_load(storage)?.unwrap_or_default();
+    if global.height != env.block.height {
+        global.height = env.block.height;
+        global.block_count = 0;
+    }
+    if limits.global_per_block > 0 && global.block_count + bets > limits.global_per_block {
+        return Err(ContractError::GlobalRateLimited {});
+    }
+
+    // Each address keeps a single record that is overwritten as blocks and windows pass
+    let mut usage = current_usage(RATE_USAGE.may_load(storage, player)?.unwrap_or_default(), env, &limits);
+    if limits.per_block > 0 && usage.block_count + bets > limits.per_block {
+        return Err(ContractError::BlockRateLimited {});
+    }
+    if limits.per_window > 0 && usage.window_count + bets > limits.per_window {
+        return Err(ContractError::AddressRateLimited { until: usage.window_start + limits.window });
+    }
+
+    global.block_count += bets;
+    GLOBAL_RATE.save(storage, &global)?;
+    usage.block_count += bets;
+    usage.window_count += bets;
+    RATE_USAGE.save(storage, player, &usage)?;
+
+    Ok(())
+}
+
+pub fn execute_update_rate_limits(
+    deps: DepsMut,
+    info: MessageInfo,
+    limits: RateLimits
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    if limits.per_window > 0 && limits.window == 0 {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    RATE_LIMITS.save(deps.storage, &limits)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_rate_limits"),
+        attr("per_block", limits.per_block.to_string()),
+        attr("per_window", limits.per_window.to_string()),
+        attr("window", limits.window.to_string()),
+        attr("global_per_block", limits.global_per_block.to_string()),
+    ]))
+}
+
+/// Removes the records of addresses idle for a whole window, scanning at most `limit` of them.
+/// Anyone can call it, `last` in the response is where the next call carries on.
+pub fn execute_prune_rate_usage(
+    deps: DepsMut,
+    env: Env,
+    start_after: Option<Addr>,
+    limit: Option<u32>
+) -> Result<Response, ContractError> {
+    let limits = RATE_LIMITS.may_load(deps.storage)?.unwrap_or_default();
+    let limit = limit.unwrap_or(constants::DEFAULT_LIMIT).min(constants::MAX_PRUNE_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let now = env.block.time.seconds();
+
+    let scanned = RATE_USAGE
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<(Addr, RateUsage)>>>()?;
+
+    let mut pruned = 0u32;
+    for (address, usage) in scanned.iter() {
+        if usage.height < env.block.height && now >= usage.window_start + limits.window {
+            RATE_USAGE.remove(deps.storage, address);
+            pruned += 1;
+        }
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "prune_rate_usage"),
+        attr("scanned", scanned.len().to_string()),
+        attr("pruned", pruned.to_string()),
+        attr("last", scanned.last().map(|(address, _)| address.to_string()).unwrap_or_default()),
+    ]))
+}
+
+pub fn query_rate_limits(deps: Deps) -> StdResult<RateLimits> {
+    Ok(RATE_LIMITS.may_load(deps.storage)?.unwrap_or_default())
+}
+
+pub fn query_rate_usage(deps: Deps, env: Env, address: Addr) -> StdResult<RateUsageResponse> {
+    let limits = RATE_LIMITS.may_load(deps.storage)?.unwrap_or_default();
+    let usage = current_usage(RATE_USAGE.may_load(deps.storage, &address)?.unwrap_or_default(), &env, &limits);
+    Ok(RateUsageResponse {
+        address,
+        usage
+    })
+}
@@ -0,0 +1,161 @@
+use cosmwasm_std::{
+    attr, Addr, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdResult, Storage, Uint128
+};
+use cw_storage_plus::Bound;
+use crate::error::ContractError;
+use crate::msg::{
+    ReferralConfigResponse, ReferralsResponse, ReferrerEarnings, ReferrerResponse, TopReferrer, TopReferrersResponse
+};
+use crate::state::{CONFIG, REFERRAL_EARNINGS, REFERRAL_RANKING, REFERRAL_SHARE, REFERRALS, REFERRERS};
+use crate::util;
+use crate::screening;
+use crate::constants;
+
+/// Accrues the referrer's commission on a fee paid by `player`, which stays reserved until claimed
+pub fn accrue(storage: &mut dyn Storage, player: &Addr, owner_amount: Uint128) -> StdResult<Uint128> {
+    let referrer = match REFERRERS.may_load(storage, player)? {
+        Some(referrer) => referrer,
+        None => return Ok(Uint128::zero())
+    };
+
+    let share = REFERRAL_SHARE.may_load(storage)?.unwrap_or_default();
+    let commission = owner_amount * Uint128::from(share) / Uint128::from(constants::BPS);
+    if commission.is_zero() {
+        return Ok(commission);
+    }
+
+    let mut earnings = REFERRAL_EARNINGS.may_load(storage, &referrer)?.unwrap_or_default();
+    REFERRAL_RANKING.remove(storage, (earnings.earned.u128(), &referrer));
+    earnings.earned += commission;
+    REFERRAL_RANKING.save(storage, (earnings.earned.u128(), &referrer), &Empty {})?;
+    REFERRAL_EARNINGS.save(storage, &referrer, &earnings)?;
+
+    util::reserve(storage, commission)?;
+    Ok(commission)
+}
+
+pub fn execute_set_referrer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    referrer: Addr
+) -> Result<Response, ContractError> {
+    let referrer = deps.api.addr_validate(referrer.as_str())?;
+
+    if REFERRERS.has(deps.storage, &info.sender) {
+        return Err(ContractError::ReferrerAlreadySet {});
+    }
+    // Rejects self-referrals and two players referring each other
+    if referrer == info.sender || REFERRERS.may_load(deps.storage, &referrer)?.as_ref() == Some(&info.sender) {
+        return Err(ContractError::InvalidReferrer {});
+    }
+
+    REFERRERS.save(deps.storage, &info.sender, &referrer)?;
+    REFERRALS.save(deps.storage, (&referrer, &info.sender), &env.block.time.seconds())?;
+
+    let mut earnings = REFERRAL_EARNINGS.may_load(deps.storage, &referrer)?.unwrap_or_default();
+    earnings.referrals += 1;
+    REFERRAL_EARNINGS.save(deps.storage, &referrer, &earnings)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_referrer"),
+        attr("address", info.sender),
+        attr("referrer", referrer),
+    ]))
+}
+
+pub fn execute_update_referral_share(
+    deps: DepsMut,
+    info: MessageInfo,
+    share_bps: u64
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    if share_bps > constants::BPS {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    REFERRAL_SHARE.save(deps.storage, &share_bps)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_referral_share"),
+        attr("share_bps", share_bps.to_string()),
+    ]))
+}
+
+pub fn execute_claim_referral(
+    deps: DepsMut,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let mut earnings = REFERRAL_EARNINGS.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+    let amount = earnings.earned - earnings.claimed;
+    if amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    earnings.claimed += amount;
+    REFERRAL_EARNINGS.save(deps.storage, &info.sender, &earnings)?;
+    util::release(deps.storage, amount)?;
+
+    let message = util::transfer_token_message(deps.querier, cfg.denom, amount, info.sender.clone())?;
+
+    Ok(Response::new()
+        .add_message(message)
+        .add_attributes(vec![
+            attr("action", "claim_referral"),
+            attr("address", info.sender),
+            attr("amount", amount),
+        ]))
+}
+
+pub fn query_referral_config(deps: Deps) -> StdResult<ReferralConfigResponse> {
+    Ok(ReferralConfigResponse {
+        share_bps: REFERRAL_SHARE.may_load(deps.storage)?.unwrap_or_default()
+    })
+}
+
+pub fn query_referrer(deps: Deps, address: Addr) -> StdResult<ReferrerResponse> {
+    let referrer = REFERRERS.may_load(deps.storage, &address)?;
+    Ok(ReferrerResponse {
+        address,
+        referrer
+    })
+}
+
+/// Players referred directly by `referrer`, deeper levels of the tree are fetched per player
+pub fn query_referrals(deps: Deps, referrer: Addr, start_after: Option<Addr>, limit: Option<u32>) -> StdResult<ReferralsResponse> {
+    let limit = limit.unwrap_or(constants::DEFAULT_LIMIT).min(constants::MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let list = REFERRALS.prefix(&referrer)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    Ok(ReferralsResponse {
+        referrer,
+        list
+    })
+}
+
+pub fn query_referrer_earnings(deps: Deps, address: Addr) -> StdResult<ReferrerEarnings> {
+    Ok(REFERRAL_EARNINGS.may_load(deps.storage, &address)?.unwrap_or_default())
+}
+
+pub fn query_top_referrers(deps: Deps, limit: Option<u32>) -> StdResult<TopReferrersResponse> {
+    let limit = limit.unwrap_or(constants::DEFAULT_LIMIT).min(constants::MAX_LIMIT) as usize;
+
+    let list = REFERRAL_RANKING
+        .keys(deps.storage, None, None, Order::Descending)
+        .take(limit)
+        .map(|key| key.map(|(earned, address)| TopReferrer { address, earned: Uint128::from(earned) }))
+        .collect::<StdResult<Vec<TopReferrer>>>()?;
+
+    Ok(TopReferrersResponse {
+        list
+    })
+}
@@ -0,0 +1,102 @@
+use cosmwasm_std::{
+    attr, Addr, Deps, DepsMut, Empty, MessageInfo, Order, QuerierWrapper, Response, StdResult, Storage
+};
+use cw_storage_plus::Bound;
+use crate::error::ContractError;
+use crate::msg::{BlockedResponse, BlocklistResponse, ScreeningConfigResponse, ScreeningQueryMsg, ScreeningResponse};
+use crate::state::{BLOCKLIST, SCREENING_CONTRACT};
+use crate::util;
+use crate::constants;
+
+/// Whether `address` is on the blocklist or, when one is set, flagged by the screening contract
+pub fn is_blocked(storage: &dyn Storage, querier: QuerierWrapper, address: &Addr) -> StdResult<bool> {
+    if BLOCKLIST.has(storage, address) {
+        return Ok(true);
+    }
+    match SCREENING_CONTRACT.may_load(storage)? {
+        Some(contract) => {
+            let response: ScreeningResponse = querier.query_wasm_smart(contract, &ScreeningQueryMsg::IsBlocked {
+                address: address.to_string()
+            })?;
+            Ok(response.blocked)
+        },
+        None => Ok(false)
+    }
+}
+
+/// Rejects bets from and payouts to blocked addresses
+pub fn check_address(storage: &dyn Storage, querier: QuerierWrapper, address: &Addr) -> Result<(), ContractError> {
+    if is_blocked(storage, querier, address)? {
+        return Err(ContractError::AddressBlocked { address: address.to_string() });
+    }
+    Ok(())
+}
+
+pub fn execute_update_blocklist(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<Addr>,
+    remove: Vec<Addr>
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    for address in add.iter() {
+        BLOCKLIST.save(deps.storage, &deps.api.addr_validate(address.as_str())?, &Empty {})?;
+    }
+    for address in remove.iter() {
+        BLOCKLIST.remove(deps.storage, address);
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_blocklist"),
+        attr("added", add.len().to_string()),
+        attr("removed", remove.len().to_string()),
+    ]))
+}
+
+/// Sets or, with None, clears the contract every address is also screened against
+pub fn execute_update_screening(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: Option<Addr>
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    match contract.as_ref() {
+        Some(contract) => SCREENING_CONTRACT.save(deps.storage, &deps.api.addr_validate(contract.as_str())?)?,
+        None => SCREENING_CONTRACT.remove(deps.storage)
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_screening"),
+        attr("contract", contract.map(|c| c.to_string()).unwrap_or_default()),
+    ]))
+}
+
+pub fn query_is_blocked(deps: Deps, address: Addr) -> StdResult<BlockedResponse> {
+    let blocked = is_blocked(deps.storage, deps.querier, &address)?;
+    Ok(BlockedResponse {
+        address,
+        blocked
+    })
+}
+
+pub fn query_blocklist(deps: Deps, start_after: Option<Addr>, limit: Option<u32>) -> StdResult<BlocklistResponse> {
+    let limit = limit.unwrap_or(constants::DEFAULT_LIMIT).min(constants::MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let list = BLOCKLIST
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    Ok(BlocklistResponse {
+        list
+    })
+}
+
+pub fn query_screening_config(deps: Deps) -> StdResult<ScreeningConfigResponse> {
+    Ok(ScreeningConfigResponse {
+        contract: SCREENING_CONTRACT.may_load(deps.storage)?
+    })
+}
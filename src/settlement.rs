@@ -0,0 +1,39 @@
+use cosmwasm_std::{Addr, Env, Event, QuerierWrapper, Storage, Uint128};
+use crate::error::ContractError;
+use crate::stats::{self, Outcome};
+use crate::breaker;
+use crate::payout_cap;
+use crate::leaderboard;
+use crate::limits;
+use crate::history;
+
+/// A bet as every game settles it
+pub struct Bet<'a> {
+    pub player: &'a Addr,
+    pub game: &'a str,
+    /// Count the history record was saved under
+    pub count: u64,
+    /// Outcome code of the history record
+    pub win: Option<u8>,
+    pub wagered: Uint128,
+    pub fee: Uint128,
+    /// What the bankroll paid the player, jackpots aside
+    pub paid: Uint128,
+    pub jackpot: Uint128
+}
+
+/// Books a settled bet with the history index, loss limits, payout cap, stats, leaderboards
+/// and circuit breaker. Returns the event of a breaker trip, which the response has to carry.
+pub fn settle_bet(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+    env: &Env,
+    bet: Bet
+) -> Result<Option<Event>, ContractError> {
+    history::index(storage, bet.game, bet.count, env.block.time.seconds(), bet.win)?;
+    limits::record_return(storage, env, bet.player, bet.paid + bet.jackpot)?;
+    payout_cap::record(storage, env, bet.player, bet.paid)?;
+    stats::record(storage, bet.player, bet.game, bet.wagered, bet.fee, bet.paid, Outcome::of(bet.game, bet.win))?;
+    leaderboard::record(storage, env, bet.player, bet.wagered, bet.paid)?;
+    breaker::record(storage, querier, env, bet.wagered, bet.fee + bet.paid)
+}
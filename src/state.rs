@@ -1,10 +1,14 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr};
+use cosmwasm_std::{Addr, Empty, Int128};
 use crate::msg::{
-    RHistory, FHistory, DHistory, BHistory
+    RHistory, FHistory, DHistory, BHistory, PHistory, KHistory, JackpotWinner, LotteryRound,
+    BlackjackGame, JHistory, SHistory, SlotsPay,
+    MinesGame, MHistory, HiloGame, HHistory,
+    WHistory, WheelSegment, ReferrerEarnings, VipTier, Voucher, BetLimits, LimitWindow, RateLimits, RateUsage, BreakerTrip, PayoutCaps, GameStats, PlayerStats, HistoryRetention
 };
+use cosmwasm_std::Uint128;
 use cw_storage_plus::{Item, Map};
 use cw20::Denom;
 
@@ -18,7 +22,25 @@ pub struct Config {
     pub flip_count: u64,
     pub rps_count: u64,
     pub dice_count: u64,
-    pub roulette_count: u64
+    pub roulette_count: u64,
+    #[serde(default)]
+    pub plinko_count: u64,
+    #[serde(default)]
+    pub keno_count: u64,
+    #[serde(default)]
+    pub lottery_count: u64,
+    #[serde(default)]
+    pub blackjack_count: u64,
+    #[serde(default)]
+    pub slots_count: u64,
+    #[serde(default)]
+    pub mines_count: u64,
+    #[serde(default)]
+    pub hilo_count: u64,
+    #[serde(default)]
+    pub wheel_count: u64,
+    #[serde(default)]
+    pub voucher_count: u64
 }
 
 pub const CONFIG_KEY: &str = "config";
@@ -34,4 +56,298 @@ pub const DHISTORY_KEY: &str = "dhistory";
 pub const DHISTORY: Map<u64, DHistory> = Map::new(DHISTORY_KEY);
 
 pub const BHISTORY_KEY: &str = "bhistory";
-pub const BHISTORY: Map<u64, BHistory> = Map::new(BHISTORY_KEY);
\ No newline at end of file
+pub const BHISTORY: Map<u64, BHistory> = Map::new(BHISTORY_KEY);
+
+pub const PHISTORY_KEY: &str = "phistory";
+pub const PHISTORY: Map<u64, PHistory> = Map::new(PHISTORY_KEY);
+
+/// Plinko slot multipliers keyed by (rows, risk)
+pub const PLINKO_TABLES_KEY: &str = "plinko_tables";
+pub const PLINKO_TABLES: Map<(u8, u8), Vec<u64>> = Map::new(PLINKO_TABLES_KEY);
+
+pub const KHISTORY_KEY: &str = "khistory";
+pub const KHISTORY: Map<u64, KHistory> = Map::new(KHISTORY_KEY);
+
+/// Keno multipliers by hit count keyed by the number of picks
+pub const KENO_TABLES_KEY: &str = "keno_tables";
+pub const KENO_TABLES: Map<u8, Vec<u64>> = Map::new(KENO_TABLES_KEY);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Jackpot {
+    /// Pool held in the contract balance on top of the bankroll
+    pub amount: Uint128,
+    /// Share of every bet added to the pool, in basis points
+    pub contribution_bps: u64,
+    /// Every bet wins the pool with probability 1 / odds, 0 disables the draw
+    pub odds: u64,
+    pub win_count: u64
+}
+
+pub const JACKPOT_KEY: &str = "jackpot";
+pub const JACKPOT: Item<Jackpot> = Item::new(JACKPOT_KEY);
+
+pub const JACKPOT_WINNERS_KEY: &str = "jackpot_winners";
+pub const JACKPOT_WINNERS: Map<u64, JackpotWinner> = Map::new(JACKPOT_WINNERS_KEY);
+
+/// Funds owed to players (e.g. open lottery pots), not available to back new bets
+pub const RESERVED_KEY: &str = "reserved";
+pub const RESERVED: Item<Uint128> = Item::new(RESERVED_KEY);
+
+pub const LOTTERY_ROUNDS_KEY: &str = "lottery_rounds";
+pub const LOTTERY_ROUNDS: Map<u64, LotteryRound> = Map::new(LOTTERY_ROUNDS_KEY);
+
+/// Ticket blocks keyed by (round id, cumulative ticket count after the purchase)
+pub const LOTTERY_TICKETS_KEY: &str = "lottery_tickets";
+pub const LOTTERY_TICKETS: Map<(u64, u64), Addr> = Map::new(LOTTERY_TICKETS_KEY);
+
+/// Ticket blocks paid from the ledger balance, whose prizes are credited back to it
+pub const LOTTERY_BALANCE_TICKETS_KEY: &str = "lottery_balance_tickets";
+pub const LOTTERY_BALANCE_TICKETS: Map<(u64, u64), Empty> = Map::new(LOTTERY_BALANCE_TICKETS_KEY);
+
+pub const LOTTERY_PLAYER_TICKETS_KEY: &str = "lottery_player_tickets";
+pub const LOTTERY_PLAYER_TICKETS: Map<(u64, &Addr), u64> = Map::new(LOTTERY_PLAYER_TICKETS_KEY);
+
+/// Blackjack games in progress, one per player
+pub const BLACKJACK_GAMES_KEY: &str = "blackjack_games";
+pub const BLACKJACK_GAMES: Map<&Addr, BlackjackGame> = Map::new(BLACKJACK_GAMES_KEY);
+
+pub const JHISTORY_KEY: &str = "jhistory";
+pub const JHISTORY: Map<u64, JHistory> = Map::new(JHISTORY_KEY);
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SlotsConfig {
+    /// Symbol strip of every reel, left to right
+    pub reels: Vec<Vec<u8>>,
+    /// Visible rows of each reel
+    pub rows: u8,
+    /// Row index on each reel for every payline
+    pub paylines: Vec<Vec<u8>>,
+    pub paytable: Vec<SlotsPay>
+}
+
+pub const SLOTS_CONFIG_KEY: &str = "slots_config";
+pub const SLOTS_CONFIG: Item<SlotsConfig> = Item::new(SLOTS_CONFIG_KEY);
+
+pub const SHISTORY_KEY: &str = "shistory";
+pub const SHISTORY: Map<u64, SHistory> = Map::new(SHISTORY_KEY);
+
+/// Mines games in progress, one per player
+pub const MINES_GAMES_KEY: &str = "mines_games";
+pub const MINES_GAMES: Map<&Addr, MinesGame> = Map::new(MINES_GAMES_KEY);
+
+/// Layout seeds of the mines games in progress, kept out of `MinesGame` so no query returns them
+pub const MINES_SEEDS_KEY: &str = "mines_seeds";
+pub const MINES_SEEDS: Map<&Addr, u64> = Map::new(MINES_SEEDS_KEY);
+
+pub const MHISTORY_KEY: &str = "mhistory";
+pub const MHISTORY: Map<u64, MHistory> = Map::new(MHISTORY_KEY);
+
+/// Hi-Lo games in progress, one per player
+pub const HILO_GAMES_KEY: &str = "hilo_games";
+pub const HILO_GAMES: Map<&Addr, HiloGame> = Map::new(HILO_GAMES_KEY);
+
+pub const HHISTORY_KEY: &str = "hhistory";
+pub const HHISTORY: Map<u64, HHistory> = Map::new(HHISTORY_KEY);
+
+pub const WHISTORY_KEY: &str = "whistory";
+pub const WHISTORY: Map<u64, WHistory> = Map::new(WHISTORY_KEY);
+
+/// Wheel segments keyed by preset id
+pub const WHEEL_PRESETS_KEY: &str = "wheel_presets";
+pub const WHEEL_PRESETS: Map<u8, Vec<WheelSegment>> = Map::new(WHEEL_PRESETS_KEY);
+
+/// Player balances held by the contract, see `ledger`
+pub const BALANCES_KEY: &str = "balances";
+pub const BALANCES: Map<&Addr, Uint128> = Map::new(BALANCES_KEY);
+
+pub const TOTAL_BALANCE_KEY: &str = "total_balance";
+pub const TOTAL_BALANCE: Item<Uint128> = Item::new(TOTAL_BALANCE_KEY);
+
+/// Share of the house fee credited to a player's referrer, in basis points
+pub const REFERRAL_SHARE_KEY: &str = "referral_share";
+pub const REFERRAL_SHARE: Item<u64> = Item::new(REFERRAL_SHARE_KEY);
+
+/// Referrer of every referred player, set once
+pub const REFERRERS_KEY: &str = "referrers";
+pub const REFERRERS: Map<&Addr, Addr> = Map::new(REFERRERS_KEY);
+
+/// Referred players keyed by (referrer, player), holding the time of the referral
+pub const REFERRALS_KEY: &str = "referrals";
+pub const REFERRALS: Map<(&Addr, &Addr), u64> = Map::new(REFERRALS_KEY);
+
+pub const REFERRAL_EARNINGS_KEY: &str = "referral_earnings";
+pub const REFERRAL_EARNINGS: Map<&Addr, ReferrerEarnings> = Map::new(REFERRAL_EARNINGS_KEY);
+
+/// Referrers keyed by (total earned, referrer) for the top referrers query
+pub const REFERRAL_RANKING_KEY: &str = "referral_ranking";
+pub const REFERRAL_RANKING: Map<(u128, &Addr), Empty> = Map::new(REFERRAL_RANKING_KEY);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct LoyaltyConfig {
+    /// Points earned per wagered token, scaled by `MULTIPLY`
+    pub points_rate: u64,
+    /// Fewest points a redemption can spend
+    pub min_redeem_points: Uint128,
+    /// Ascending by threshold
+    pub tiers: Vec<VipTier>
+}
+
+pub const LOYALTY_CONFIG_KEY: &str = "loyalty_config";
+pub const LOYALTY_CONFIG: Item<LoyaltyConfig> = Item::new(LOYALTY_CONFIG_KEY);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PlayerLoyalty {
+    /// Volume wagered over all games
+    pub wagered: Uint128,
+    /// Shares of `rakeback`, each point is worth the same part of it
+    pub points: Uint128,
+    /// Rakeback accrued and not redeemed yet
+    pub rakeback: Uint128,
+    pub redeemed: Uint128
+}
+
+pub const LOYALTY_KEY: &str = "loyalty";
+pub const LOYALTY: Map<&Addr, PlayerLoyalty> = Map::new(LOYALTY_KEY);
+
+/// Unused vouchers keyed by id, removed once used or revoked
+pub const VOUCHERS_KEY: &str = "vouchers";
+pub const VOUCHERS: Map<u64, Voucher> = Map::new(VOUCHERS_KEY);
+
+/// Ids of the unused vouchers of every player
+pub const PLAYER_VOUCHERS_KEY: &str = "player_vouchers";
+pub const PLAYER_VOUCHERS: Map<(&Addr, u64), Empty> = Map::new(PLAYER_VOUCHERS_KEY);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PlayerLimits {
+    /// Limits in force
+    pub limits: BetLimits,
+    /// Looser limits the player asked for, in force from `pending_at`
+    pub pending: Option<BetLimits>,
+    pub pending_at: u64,
+    pub excluded_until: u64
+}
+
+/// Responsible-gambling limits set by players on themselves
+pub const PLAYER_LIMITS_KEY: &str = "player_limits";
+pub const PLAYER_LIMITS: Map<&Addr, PlayerLimits> = Map::new(PLAYER_LIMITS_KEY);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PlayerActivity {
+    pub last_bet: u64,
+    pub daily: LimitWindow,
+    pub weekly: LimitWindow,
+    pub monthly: LimitWindow
+}
+
+pub const PLAYER_ACTIVITY_KEY: &str = "player_activity";
+pub const PLAYER_ACTIVITY: Map<&Addr, PlayerActivity> = Map::new(PLAYER_ACTIVITY_KEY);
+
+/// Addresses the owner blocked from betting and receiving payouts
+pub const BLOCKLIST_KEY: &str = "blocklist";
+pub const BLOCKLIST: Map<&Addr, Empty> = Map::new(BLOCKLIST_KEY);
+
+/// External contract every address is also screened against, see `screening`
+pub const SCREENING_CONTRACT_KEY: &str = "screening_contract";
+pub const SCREENING_CONTRACT: Item<Addr> = Item::new(SCREENING_CONTRACT_KEY);
+
+pub const RATE_LIMITS_KEY: &str = "rate_limits";
+pub const RATE_LIMITS: Item<RateLimits> = Item::new(RATE_LIMITS_KEY);
+
+/// Bets of every address in its latest block and window
+pub const RATE_USAGE_KEY: &str = "rate_usage";
+pub const RATE_USAGE: Map<&Addr, RateUsage> = Map::new(RATE_USAGE_KEY);
+
+/// Bets of all addresses in the latest block, only `height` and `block_count` are used
+pub const GLOBAL_RATE_KEY: &str = "global_rate";
+pub const GLOBAL_RATE: Item<RateUsage> = Item::new(GLOBAL_RATE_KEY);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct BreakerConfig {
+    /// Share of the bankroll the house may lose within a window, 0 turns the breaker off
+    pub max_drawdown_bps: u64,
+    /// Length of the rolling window in seconds
+    pub window: u64
+}
+
+pub const BREAKER_CONFIG_KEY: &str = "breaker_config";
+pub const BREAKER_CONFIG: Item<BreakerConfig> = Item::new(BREAKER_CONFIG_KEY);
+
+/// House PnL of every bucket still in the window
+pub const BREAKER_PNL_KEY: &str = "breaker_pnl";
+pub const BREAKER_PNL: Map<u64, Int128> = Map::new(BREAKER_PNL_KEY);
+
+/// Set while betting is halted, until the owner resets the breaker
+pub const BREAKER_TRIP_KEY: &str = "breaker_trip";
+pub const BREAKER_TRIP: Item<BreakerTrip> = Item::new(BREAKER_TRIP_KEY);
+
+pub const PAYOUT_CAPS_KEY: &str = "payout_caps";
+pub const PAYOUT_CAPS: Item<PayoutCaps> = Item::new(PAYOUT_CAPS_KEY);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct DailyPayout {
+    /// Days since the epoch
+    pub day: u64,
+    pub paid: Uint128
+}
+
+pub const PLAYER_PAYOUTS_KEY: &str = "player_payouts";
+pub const PLAYER_PAYOUTS: Map<&Addr, DailyPayout> = Map::new(PLAYER_PAYOUTS_KEY);
+
+pub const GLOBAL_PAYOUT_KEY: &str = "global_payout";
+pub const GLOBAL_PAYOUT: Item<DailyPayout> = Item::new(GLOBAL_PAYOUT_KEY);
+
+/// Largest payouts still possible from open blackjack, mines and hi-lo games
+pub const PENDING_PAYOUTS_KEY: &str = "pending_payouts";
+pub const PENDING_PAYOUTS: Map<&Addr, Uint128> = Map::new(PENDING_PAYOUTS_KEY);
+
+pub const GLOBAL_PENDING_PAYOUT_KEY: &str = "global_pending_payout";
+pub const GLOBAL_PENDING_PAYOUT: Item<Uint128> = Item::new(GLOBAL_PENDING_PAYOUT_KEY);
+
+/// Running aggregates of every game, keyed by its name
+pub const GAME_STATS_KEY: &str = "game_stats";
+pub const GAME_STATS: Map<&str, GameStats> = Map::new(GAME_STATS_KEY);
+
+pub const PLAYER_STATS_KEY: &str = "player_stats";
+pub const PLAYER_STATS: Map<(&Addr, &str), PlayerStats> = Map::new(PLAYER_STATS_KEY);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct LeaderboardTotals {
+    pub wagered: Uint128,
+    /// Payouts less stakes
+    pub profit: Int128
+}
+
+/// Totals of the players on either ranking of a board, keyed by period, period index and address
+pub const LEADERBOARD_TOTALS_KEY: &str = "leaderboard_totals";
+pub const LEADERBOARD_TOTALS: Map<(u8, u64, &Addr), LeaderboardTotals> = Map::new(LEADERBOARD_TOTALS_KEY);
+
+/// Rankings of every period, keyed by (period, index), score and address.
+/// Each holds at most `LEADERBOARD_SIZE` players.
+pub const WAGERED_RANKING_KEY: &str = "wagered_ranking";
+pub const WAGERED_RANKING: Map<((u8, u64), u128, &Addr), Empty> = Map::new(WAGERED_RANKING_KEY);
+
+pub const PROFIT_RANKING_KEY: &str = "profit_ranking";
+pub const PROFIT_RANKING: Map<((u8, u64), u128, &Addr), Empty> = Map::new(PROFIT_RANKING_KEY);
+
+/// Players on a ranking, keyed by period, kind and period index
+pub const LEADERBOARD_SIZES_KEY: &str = "leaderboard_sizes";
+pub const LEADERBOARD_SIZES: Map<(u8, u8, u64), u32> = Map::new(LEADERBOARD_SIZES_KEY);
+
+/// Prizes paid for a board, keyed by period, kind and period index
+pub const LEADERBOARD_PAID_KEY: &str = "leaderboard_paid";
+pub const LEADERBOARD_PAID: Map<(u8, u8, u64), Uint128> = Map::new(LEADERBOARD_PAID_KEY);
+
+/// History records of every game by time, keyed by game, timestamp and count
+pub const HISTORY_BY_TIME_KEY: &str = "history_by_time";
+pub const HISTORY_BY_TIME: Map<(&str, u64, u64), Empty> = Map::new(HISTORY_BY_TIME_KEY);
+
+/// History records of every game by outcome, keyed by game, win and count
+pub const HISTORY_BY_WIN_KEY: &str = "history_by_win";
+pub const HISTORY_BY_WIN: Map<(&str, u8, u64), Empty> = Map::new(HISTORY_BY_WIN_KEY);
+
+pub const HISTORY_RETENTION_KEY: &str = "history_retention";
+pub const HISTORY_RETENTION: Item<HistoryRetention> = Item::new(HISTORY_RETENTION_KEY);
+
+/// Count of the oldest retained record of every game, records before it were pruned
+pub const HISTORY_START_KEY: &str = "history_start";
+pub const HISTORY_START: Map<&str, u64> = Map::new(HISTORY_START_KEY);
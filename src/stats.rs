@@ -0,0 +1,109 @@
+use cosmwasm_std::{Addr, Deps, Int128, Order, StdResult, Storage, Uint128};
+use crate::msg::{PlayerGameStats, PlayerStatsResponse, StatsResponse};
+use crate::state::{GAME_STATS, PLAYER_STATS};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Tie
+}
+
+impl Outcome {
+    /// Outcome of a game recording 0 for a win and anything else for a loss
+    pub fn two_way(win: Option<u8>) -> Outcome {
+        match win {
+            Some(0) => Outcome::Win,
+            _ => Outcome::Loss
+        }
+    }
+
+    /// Outcome of a game recording 0 for a win, 1 for a tie and 2 for a loss
+    pub fn three_way(win: Option<u8>) -> Outcome {
+        match win {
+            Some(0) => Outcome::Win,
+            Some(1) => Outcome::Tie,
+            _ => Outcome::Loss
+        }
+    }
+
+    /// Outcome of a bet on `game` recorded with `win`
+    pub fn of(game: &str, win: Option<u8>) -> Outcome {
+        if has_ties(game) {
+            Outcome::three_way(win)
+        } else {
+            Outcome::two_way(win)
+        }
+    }
+}
+
+/// Rock paper scissors and blackjack record ties, every other game only wins and losses
+pub fn has_ties(game: &str) -> bool {
+    game == "rps" || game == "blackjack"
+}
+
+/// Adds a settled bet to the aggregates of `game` and those of `player` in it. `paid` is what
+/// the bankroll paid the player, jackpots aside, so the house PnL is the stake less the fee and the payout.
+pub fn record(
+    storage: &mut dyn Storage,
+    player: &Addr,
+    game: &str,
+    wagered: Uint128,
+    fee: Uint128,
+    paid: Uint128,
+    outcome: Outcome
+) -> StdResult<()> {
+    let mut stats = GAME_STATS.may_load(storage, game)?.unwrap_or_default();
+    stats.bets += 1;
+    stats.wagered += wagered;
+    stats.paid_out += paid;
+    stats.fees += fee;
+    stats.house_pnl = Int128::new(stats.house_pnl.i128() + wagered.u128() as i128 - fee.u128() as i128 - paid.u128() as i128);
+    match outcome {
+        Outcome::Win => {
+            stats.wins += 1;
+            stats.largest_win = stats.largest_win.max(paid);
+        },
+        Outcome::Loss => stats.losses += 1,
+        Outcome::Tie => stats.ties += 1
+    }
+    GAME_STATS.save(storage, game, &stats)?;
+
+    // Ties leave the streak as it is
+    let mut stats = PLAYER_STATS.may_load(storage, (player, game))?.unwrap_or_default();
+    stats.bets += 1;
+    stats.wagered += wagered;
+    stats.won += paid;
+    stats.net_pnl = Int128::new(stats.net_pnl.i128() + paid.u128() as i128 - wagered.u128() as i128);
+    match outcome {
+        Outcome::Win => {
+            stats.biggest_win = stats.biggest_win.max(paid);
+            stats.current_streak += 1;
+            stats.longest_streak = stats.longest_streak.max(stats.current_streak);
+        },
+        Outcome::Loss => stats.current_streak = 0,
+        Outcome::Tie => {}
+    }
+    PLAYER_STATS.save(storage, (player, game), &stats)
+}
+
+pub fn query_stats(deps: Deps, game: String) -> StdResult<StatsResponse> {
+    let stats = GAME_STATS.may_load(deps.storage, &game)?.unwrap_or_default();
+    Ok(StatsResponse {
+        game,
+        stats
+    })
+}
+
+/// Aggregates of every game `address` has played
+pub fn query_player_stats(deps: Deps, address: Addr) -> StdResult<PlayerStatsResponse> {
+    let games = PLAYER_STATS.prefix(&address)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(game, stats)| PlayerGameStats { game, stats }))
+        .collect::<StdResult<Vec<PlayerGameStats>>>()?;
+
+    Ok(PlayerStatsResponse {
+        address,
+        games
+    })
+}
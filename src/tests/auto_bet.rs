@@ -0,0 +1,139 @@
+use cosmwasm_std::{from_json, Uint128};
+use crate::msg::{AutoBetResponse, AutoBetStop, AutoStrategy, BatchGame, ExecuteMsg};
+use crate::constants::TREASURY_ADDR;
+use crate::ContractError;
+use super::*;
+
+const BASE: u128 = 10_000;
+
+fn auto_bet(game: BatchGame, strategy: AutoStrategy, rounds: u32, stop_loss: Option<u128>, take_profit: Option<u128>) -> ExecuteMsg {
+    ExecuteMsg::AutoBet {
+        game,
+        selection: 0,
+        strategy,
+        base_stake: Uint128::new(BASE),
+        rounds,
+        stop_loss: stop_loss.map(Uint128::new),
+        take_profit: take_profit.map(Uint128::new),
+        from_balance: None
+    }
+}
+
+fn play(deps: &mut TestDeps, n: u64, budget: u128, msg: ExecuteMsg) -> (cosmwasm_std::Response, AutoBetResponse) {
+    let res = run(deps, &env_at(n), PLAYER, budget, msg).unwrap();
+    let data = from_json(res.data.as_ref().unwrap()).unwrap();
+    (res, data)
+}
+
+/// Checks the totals and transfers of a run and that nothing stays reserved
+fn check_settlement(deps: &TestDeps, res: &cosmwasm_std::Response, data: &AutoBetResponse, budget: u128) {
+    let stake: Uint128 = data.rounds.iter().map(|r| r.bet_amount).sum();
+    let payout: Uint128 = data.rounds.iter().map(|r| r.payout + r.jackpot).sum();
+    let fees: u128 = data.rounds.iter().map(|r| fee(r.bet_amount.u128())).sum();
+    assert_eq!(data.rounds_played as usize, data.rounds.len());
+    assert_eq!(data.total_stake, stake);
+    assert_eq!(data.total_payout, payout);
+    assert_eq!(data.net_profit.i128(), payout.u128() as i128 - stake.u128() as i128);
+    assert_eq!(sent_to(res, PLAYER), Uint128::new(budget) + payout - stake);
+    assert_eq!(sent_to(res, TREASURY_ADDR), Uint128::new(fees));
+    assert_eq!(reserved(deps), Uint128::zero());
+}
+
+#[test]
+fn runs_are_validated() {
+    let mut deps = setup();
+
+    let invalid = vec![
+        auto_bet(BatchGame::Flip, AutoStrategy::Flat, 0, None, None),
+        auto_bet(BatchGame::Flip, AutoStrategy::Flat, 101, None, None),
+        auto_bet(BatchGame::Flip, AutoStrategy::Flat, 5, Some(0), None),
+        auto_bet(BatchGame::Flip, AutoStrategy::Flat, 5, None, Some(0)),
+        ExecuteMsg::AutoBet {
+            game: BatchGame::Flip, selection: 0, strategy: AutoStrategy::Flat, base_stake: Uint128::zero(),
+            rounds: 5, stop_loss: None, take_profit: None, from_balance: None
+        },
+    ];
+    for msg in invalid {
+        assert_eq!(run(&mut deps, &mock_env(), PLAYER, BASE, msg).unwrap_err(), ContractError::InvalidInput {});
+    }
+    let msg = ExecuteMsg::AutoBet {
+        game: BatchGame::Rps, selection: 3, strategy: AutoStrategy::Flat, base_stake: Uint128::new(BASE),
+        rounds: 5, stop_loss: None, take_profit: None, from_balance: None
+    };
+    assert_eq!(run(&mut deps, &mock_env(), PLAYER, BASE, msg).unwrap_err(), ContractError::InvalidBet {});
+    let err = run(&mut deps, &mock_env(), PLAYER, BASE - 1, auto_bet(BatchGame::Flip, AutoStrategy::Flat, 5, None, None)).unwrap_err();
+    assert_eq!(err, ContractError::StakeMismatch { expected: Uint128::new(BASE) });
+}
+
+#[test]
+fn strategies_follow_their_progression() {
+    let strategies = [AutoStrategy::Flat, AutoStrategy::Martingale, AutoStrategy::AntiMartingale, AutoStrategy::DAlembert];
+    for (n, strategy) in strategies.iter().copied().enumerate() {
+        let mut deps = setup();
+        let budget = 1000 * BASE;
+        let (res, data) = play(&mut deps, n as u64, budget, auto_bet(BatchGame::Rps, strategy, 30, None, None));
+        check_settlement(&deps, &res, &data, budget);
+        assert_eq!(data.stop, AutoBetStop::Rounds);
+        assert_eq!(data.rounds_played, 30);
+
+        let mut stake = BASE;
+        for round in data.rounds.iter() {
+            assert_eq!(round.bet_amount.u128(), stake);
+            stake = match (round.win, strategy) {
+                (1, _) => stake,
+                (_, AutoStrategy::Flat) => BASE,
+                (0, AutoStrategy::Martingale) | (2, AutoStrategy::AntiMartingale) => BASE,
+                (_, AutoStrategy::Martingale) | (_, AutoStrategy::AntiMartingale) => stake * 2,
+                (0, AutoStrategy::DAlembert) => stake.saturating_sub(BASE).max(BASE),
+                (_, AutoStrategy::DAlembert) => stake + BASE,
+            };
+        }
+    }
+}
+
+#[test]
+fn runs_stop_at_the_loss_or_profit_limit() {
+    let budget = 100 * BASE;
+    let mut stops = vec![];
+    for n in 0..10 {
+        let mut deps = setup();
+        let (res, data) = play(&mut deps, n, budget, auto_bet(BatchGame::Flip, AutoStrategy::Flat, 100, Some(3 * BASE), Some(3 * BASE)));
+        check_settlement(&deps, &res, &data, budget);
+
+        let net = data.net_profit.i128();
+        match data.stop {
+            AutoBetStop::StopLoss => assert!(net <= -3 * BASE as i128),
+            AutoBetStop::TakeProfit => assert!(net >= 3 * BASE as i128),
+            stop => panic!("stopped on {:?}", stop)
+        }
+        // The limit was not reached before the last round
+        let before: i128 = data.rounds[..data.rounds.len() - 1].iter()
+            .map(|r| r.payout.u128() as i128 - r.bet_amount.u128() as i128)
+            .sum();
+        assert!(before > -3 * BASE as i128 && before < 3 * BASE as i128);
+        stops.push(data.stop);
+    }
+    assert!(stops.contains(&AutoBetStop::StopLoss) && stops.contains(&AutoBetStop::TakeProfit));
+}
+
+#[test]
+fn runs_stop_when_the_budget_or_bankroll_runs_out() {
+    let mut deps = setup();
+    let (res, data) = play(&mut deps, 0, 3 * BASE, auto_bet(BatchGame::Flip, AutoStrategy::Martingale, 100, None, None));
+    check_settlement(&deps, &res, &data, 3 * BASE);
+    assert_eq!(data.stop, AutoBetStop::Budget);
+
+    // The mock balance does not grow with the attached budget, so it is added by hand
+    let mut deps = setup();
+    let budget = 100 * BASE;
+    set_bankroll(&mut deps, budget + 3 * BASE);
+    let (res, data) = play(&mut deps, 0, budget, auto_bet(BatchGame::Flip, AutoStrategy::Flat, 100, None, None));
+    check_settlement(&deps, &res, &data, budget);
+    assert_eq!(data.stop, AutoBetStop::Bankroll);
+
+    // Every round releases its stake and holds back the fee and whatever it returned
+    let bankroll: i128 = 3 * BASE as i128 + data.rounds.iter()
+        .map(|r| (r.bet_amount.u128() - fee(r.bet_amount.u128())) as i128 - (r.payout + r.jackpot).u128() as i128)
+        .sum::<i128>();
+    assert!(bankroll < (2 * BASE - fee(BASE)) as i128);
+}
@@ -0,0 +1,105 @@
+use cosmwasm_std::{from_json, Uint128};
+use crate::msg::{BatchBetResponse, BatchGame, ExecuteMsg, FHistoryResponse, QueryMsg};
+use crate::constants::TREASURY_ADDR;
+use crate::util;
+use crate::ContractError;
+use super::*;
+
+const STAKE: u128 = 100_000;
+
+fn batch(game: BatchGame, selection: u64, rounds: u32, from_balance: Option<u128>) -> ExecuteMsg {
+    ExecuteMsg::BatchBet {
+        game,
+        selection,
+        rounds,
+        stake_per_round: Uint128::new(STAKE),
+        from_balance: from_balance.map(Uint128::new)
+    }
+}
+
+#[test]
+fn batches_are_validated() {
+    let mut deps = setup();
+
+    for rounds in [0, 101] {
+        let err = run(&mut deps, &mock_env(), PLAYER, STAKE * rounds as u128, batch(BatchGame::Flip, 0, rounds, None)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidInput {});
+    }
+    let msg = ExecuteMsg::BatchBet { game: BatchGame::Flip, selection: 0, rounds: 2, stake_per_round: Uint128::zero(), from_balance: None };
+    assert_eq!(run(&mut deps, &mock_env(), PLAYER, 1, msg).unwrap_err(), ContractError::InvalidInput {});
+    for (game, selection) in [(BatchGame::Flip, 2), (BatchGame::Dice, 2), (BatchGame::Rps, 3), (BatchGame::Roulette, 49)] {
+        let err = run(&mut deps, &mock_env(), PLAYER, STAKE, batch(game, selection, 1, None)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidBet {});
+    }
+    let err = run(&mut deps, &mock_env(), PLAYER, STAKE, batch(BatchGame::Flip, 0, 2, None)).unwrap_err();
+    assert_eq!(err, ContractError::StakeMismatch { expected: Uint128::new(2 * STAKE) });
+
+    set_bankroll(&mut deps, 10 * STAKE);
+    let err = run(&mut deps, &mock_env(), PLAYER, 10 * STAKE, batch(BatchGame::Flip, 0, 10, None)).unwrap_err();
+    assert_eq!(err, ContractError::InsufficientFunds {});
+}
+
+#[test]
+fn rounds_pay_wins_ties_and_losses() {
+    let mut deps = setup();
+
+    let res = run(&mut deps, &mock_env(), PLAYER, 50 * STAKE, batch(BatchGame::Rps, 1, 50, None)).unwrap();
+    let data: BatchBetResponse = from_json(res.data.as_ref().unwrap()).unwrap();
+    assert_eq!(data.rounds.len(), 50);
+    assert_eq!(data.total_stake, Uint128::new(50 * STAKE));
+
+    let mut seen = [0; 3];
+    for (i, round) in data.rounds.iter().enumerate() {
+        assert_eq!(round.id, i as u64 + 1);
+        assert_eq!(round.win, util::get_winner(1, round.result).unwrap());
+        let payout = match round.win {
+            0 => 2 * STAKE - fee(STAKE),
+            1 => STAKE - fee(STAKE),
+            _ => 0
+        };
+        assert_eq!(round.payout, Uint128::new(payout));
+        seen[round.win as usize] += 1;
+    }
+    assert!(seen.iter().all(|s| *s > 0), "{:?}", seen);
+
+    let total: Uint128 = data.rounds.iter().map(|r| r.payout).sum();
+    assert_eq!(data.total_payout, total);
+    // One transfer for the whole batch
+    assert_eq!(res.messages.iter().filter(|m| matches!(&m.msg, cosmwasm_std::CosmosMsg::Bank(cosmwasm_std::BankMsg::Send { to_address, .. }) if to_address == PLAYER)).count(), 1);
+    assert_eq!(sent_to(&res, PLAYER), total);
+    assert_eq!(sent_to(&res, TREASURY_ADDR), Uint128::new(50 * fee(STAKE)));
+}
+
+#[test]
+fn every_round_is_written_to_history() {
+    let mut deps = setup();
+    run(&mut deps, &mock_env(), PLAYER, STAKE, ExecuteMsg::Flip { level: 0, from_balance: None }).unwrap();
+
+    let res = run(&mut deps, &mock_env(), PLAYER, 5 * STAKE, batch(BatchGame::Flip, 1, 5, None)).unwrap();
+    let data: BatchBetResponse = from_json(res.data.as_ref().unwrap()).unwrap();
+
+    let history: FHistoryResponse = query_as(&deps, QueryMsg::FistoryMsg { count: 10 });
+    assert_eq!(history.list.len(), 6);
+    for (record, round) in history.list.iter().rev().skip(1).zip(data.rounds.iter()) {
+        assert_eq!(record.id, round.id);
+        assert_eq!(record.win, Some(round.win));
+        assert_eq!(record.level, 1);
+        assert_eq!(record.win == Some(0), round.result == 0);
+    }
+    // Rounds of one batch draw different results
+    assert!(data.rounds.iter().any(|r| r.result != data.rounds[0].result));
+}
+
+#[test]
+fn batches_from_the_balance_debit_the_exact_total() {
+    let mut deps = setup();
+    run(&mut deps, &mock_env(), PLAYER, 1_000_000, ExecuteMsg::Deposit {}).unwrap();
+
+    let res = run(&mut deps, &mock_env(), PLAYER, 0, batch(BatchGame::Dice, 0, 4, Some(4 * STAKE))).unwrap();
+    let data: BatchBetResponse = from_json(res.data.as_ref().unwrap()).unwrap();
+    assert_eq!(sent_to(&res, PLAYER), Uint128::zero());
+    assert_eq!(balance(&deps, PLAYER), Uint128::new(1_000_000 - 4 * STAKE) + data.total_payout);
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, batch(BatchGame::Dice, 0, 4, Some(3 * STAKE))).unwrap_err();
+    assert_eq!(err, ContractError::StakeMismatch { expected: Uint128::new(4 * STAKE) });
+}
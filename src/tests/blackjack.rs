@@ -0,0 +1,221 @@
+use cosmwasm_std::{Addr, Uint128};
+use crate::msg::{BlackjackGameResponse, ExecuteMsg, JHistory, JHistoryResponse, QueryMsg};
+use crate::constants::BLACKJACK_TIMEOUT;
+use crate::util;
+use crate::ContractError;
+use super::*;
+
+const STAKE: u128 = 1_000_000;
+
+fn deal() -> ExecuteMsg {
+    ExecuteMsg::BlackjackDeal { from_balance: None }
+}
+
+fn value(cards: &[u8]) -> u8 {
+    let total: u8 = cards.iter().map(|c| util::card_rank(*c).min(10)).sum();
+    let ace = cards.iter().any(|c| util::card_rank(*c) == 1);
+    if ace && total + 10 <= 21 { total + 10 } else { total }
+}
+
+fn game(deps: &TestDeps, address: &str) -> BlackjackGameResponse {
+    query_as(deps, QueryMsg::BlackjackGame { address: Addr::unchecked(address) })
+}
+
+fn last_record(deps: &TestDeps) -> JHistory {
+    let history: JHistoryResponse = query_as(deps, QueryMsg::JistoryMsg { count: 1 });
+    history.list[0].clone()
+}
+
+/// Gross return of a settled game under the house rules
+fn expected_gross(record: &JHistory) -> u128 {
+    let natural = record.hands.len() == 1 && record.hands[0].cards.len() == 2 && value(&record.hands[0].cards) == 21;
+    let dealer = value(&record.dealer);
+    let dealer_blackjack = record.dealer.len() == 2 && dealer == 21;
+    record.hands.iter().map(|hand| {
+        let stake = hand.stake.u128();
+        let player = value(&hand.cards);
+        if player > 21 {
+            0
+        } else if natural {
+            if dealer_blackjack { stake } else { stake * 5 / 2 }
+        } else if dealer_blackjack || (player < dealer && dealer <= 21) {
+            0
+        } else if player == dealer {
+            stake
+        } else {
+            stake * 2
+        }
+    }).sum()
+}
+
+#[test]
+fn settlement_pays_win_push_and_loss() {
+    let mut deps = setup();
+
+    let mut outcomes = [0; 3];
+    for n in 0..120u64 {
+        let player = format!("player{}", n);
+        let env = env_at(n);
+        let res = run(&mut deps, &env, &player, STAKE, deal()).unwrap();
+
+        let res = if game(&deps, &player).game.is_some() {
+            assert_eq!(reserved(&deps), Uint128::new(2 * STAKE));
+            // Hit once on odd rounds to cover busts and multi-card hands
+            if n % 2 == 1 {
+                let hit = run(&mut deps, &env, &player, 0, ExecuteMsg::BlackjackHit {}).unwrap();
+                if game(&deps, &player).game.is_none() {
+                    hit
+                } else {
+                    run(&mut deps, &env, &player, 0, ExecuteMsg::BlackjackStand {}).unwrap()
+                }
+            } else {
+                run(&mut deps, &env, &player, 0, ExecuteMsg::BlackjackStand {}).unwrap()
+            }
+        } else {
+            res
+        };
+
+        assert!(game(&deps, &player).game.is_none());
+        assert_eq!(reserved(&deps), Uint128::zero());
+
+        let record = last_record(&deps);
+        assert_eq!(record.id, n + 1);
+        assert!(value(&record.dealer) >= 17 || record.hands.iter().all(|h| value(&h.cards) > 21) || record.dealer.len() == 2);
+
+        let gross = expected_gross(&record);
+        let payout = gross.saturating_sub(fee(STAKE));
+        assert_eq!(record.payout, Uint128::new(payout));
+        assert_eq!(sent_to(&res, &player), Uint128::new(payout));
+        let win = match gross {
+            g if g > STAKE => 0,
+            g if g == STAKE => 1,
+            _ => 2
+        };
+        assert_eq!(record.win, Some(win));
+        outcomes[win as usize] += 1;
+    }
+    assert!(outcomes.iter().all(|o| *o > 0), "{:?}", outcomes);
+}
+
+#[test]
+fn actions_need_an_active_game() {
+    let mut deps = setup();
+
+    for msg in [ExecuteMsg::BlackjackHit {}, ExecuteMsg::BlackjackStand {}, ExecuteMsg::BlackjackDouble {}, ExecuteMsg::BlackjackSplit {}] {
+        assert_eq!(run(&mut deps, &mock_env(), PLAYER, 0, msg).unwrap_err(), ContractError::NoActiveGame {});
+    }
+    let err = run(&mut deps, &mock_env(), OWNER, 0, ExecuteMsg::BlackjackTimeout { address: Addr::unchecked(PLAYER) }).unwrap_err();
+    assert_eq!(err, ContractError::NoActiveGame {});
+}
+
+/// Deals to fresh players until an open hand matching `keep` comes up
+fn deal_until(deps: &mut TestDeps, keep: impl Fn(&[u8]) -> bool) -> String {
+    for n in 0..500 {
+        let player = format!("player{}", n);
+        run(deps, &mock_env(), &player, STAKE, deal()).unwrap();
+        if let Some(game) = game(deps, &player).game {
+            if keep(&game.hands[0].cards) {
+                return player;
+            }
+            run(deps, &mock_env(), &player, 0, ExecuteMsg::BlackjackStand {}).unwrap();
+        }
+    }
+    panic!("no matching hand dealt");
+}
+
+#[test]
+fn open_games_expire() {
+    let mut deps = setup();
+    let player = deal_until(&mut deps, |_| true);
+
+    let err = run(&mut deps, &mock_env(), &player, STAKE, deal()).unwrap_err();
+    assert_eq!(err, ContractError::GameInProgress {});
+
+    let late = env_at(BLACKJACK_TIMEOUT);
+    let err = run(&mut deps, &late, &player, 0, ExecuteMsg::BlackjackHit {}).unwrap_err();
+    assert_eq!(err, ContractError::GameExpired {});
+
+    let expires_at = mock_env().block.time.seconds() + BLACKJACK_TIMEOUT;
+    let err = run(&mut deps, &mock_env(), OWNER, 0, ExecuteMsg::BlackjackTimeout { address: Addr::unchecked(&player) }).unwrap_err();
+    assert_eq!(err, ContractError::GameNotExpired { expires_at });
+
+    run(&mut deps, &late, OWNER, 0, ExecuteMsg::BlackjackTimeout { address: Addr::unchecked(&player) }).unwrap();
+    assert!(game(&deps, &player).game.is_none());
+    assert_eq!(reserved(&deps), Uint128::zero());
+    assert_eq!(last_record(&deps).hands[0].cards.len(), 2);
+}
+
+#[test]
+fn double_takes_one_card_for_twice_the_stake() {
+    let mut deps = setup();
+    let player = deal_until(&mut deps, |_| true);
+
+    let err = run(&mut deps, &mock_env(), &player, STAKE / 2, ExecuteMsg::BlackjackDouble {}).unwrap_err();
+    assert_eq!(err, ContractError::StakeMismatch { expected: Uint128::new(STAKE) });
+
+    let res = run(&mut deps, &mock_env(), &player, STAKE, ExecuteMsg::BlackjackDouble {}).unwrap();
+    assert_eq!(attr(&res, "action"), "blackjack_settle");
+    assert_eq!(reserved(&deps), Uint128::zero());
+
+    let record = last_record(&deps);
+    assert!(record.hands[0].doubled);
+    assert_eq!(record.hands[0].cards.len(), 3);
+    assert_eq!(record.bet_amount, Uint128::new(2 * STAKE));
+    let payout = expected_gross(&record).saturating_sub(fee(2 * STAKE));
+    assert_eq!(sent_to(&res, &player), Uint128::new(payout));
+}
+
+#[test]
+fn double_only_on_two_cards() {
+    let mut deps = setup();
+    let player = deal_until(&mut deps, |cards| value(cards) <= 11);
+
+    run(&mut deps, &mock_env(), &player, 0, ExecuteMsg::BlackjackHit {}).unwrap();
+    let err = run(&mut deps, &mock_env(), &player, STAKE, ExecuteMsg::BlackjackDouble {}).unwrap_err();
+    assert_eq!(err, ContractError::InvalidAction {});
+}
+
+#[test]
+fn split_plays_two_hands() {
+    let mut deps = setup();
+
+    let player = deal_until(&mut deps, |cards| util::card_rank(cards[0]) != util::card_rank(cards[1]));
+    let err = run(&mut deps, &mock_env(), &player, STAKE, ExecuteMsg::BlackjackSplit {}).unwrap_err();
+    assert_eq!(err, ContractError::InvalidAction {});
+    run(&mut deps, &mock_env(), &player, 0, ExecuteMsg::BlackjackStand {}).unwrap();
+
+    let player = deal_until(&mut deps, |cards| util::card_rank(cards[0]) == util::card_rank(cards[1]));
+    let res = run(&mut deps, &mock_env(), &player, STAKE, ExecuteMsg::BlackjackSplit {}).unwrap();
+    let res = match game(&deps, &player).game {
+        Some(game) => {
+            assert_eq!(game.hands.len(), 2);
+            assert_eq!(reserved(&deps), Uint128::new(4 * STAKE));
+            let mut res = res;
+            while game_open(&deps, &player) {
+                res = run(&mut deps, &mock_env(), &player, 0, ExecuteMsg::BlackjackStand {}).unwrap();
+            }
+            res
+        },
+        None => res
+    };
+    assert_eq!(reserved(&deps), Uint128::zero());
+
+    let record = last_record(&deps);
+    assert_eq!(record.hands.len(), 2);
+    assert_eq!(record.bet_amount, Uint128::new(2 * STAKE));
+    let payout = expected_gross(&record).saturating_sub(fee(2 * STAKE));
+    assert_eq!(sent_to(&res, &player), Uint128::new(payout));
+}
+
+fn game_open(deps: &TestDeps, address: &str) -> bool {
+    game(deps, address).game.is_some()
+}
+
+#[test]
+fn deal_needs_the_bankroll_for_a_natural() {
+    let mut deps = setup();
+    set_bankroll(&mut deps, STAKE * 5 / 2 - 1);
+
+    let err = run(&mut deps, &mock_env(), PLAYER, STAKE, deal()).unwrap_err();
+    assert_eq!(err, ContractError::InsufficientFunds {});
+}
@@ -0,0 +1,75 @@
+use cosmwasm_std::Uint128;
+use crate::msg::{BreakerResponse, ExecuteMsg, QueryMsg};
+use crate::ContractError;
+use super::*;
+
+const STAKE: u128 = 1_000_000;
+
+fn flip(deps: &mut TestDeps, n: u64) -> Result<cosmwasm_std::Response, ContractError> {
+    run(deps, &env_at(n), PLAYER, STAKE, ExecuteMsg::Flip { level: 0, from_balance: None })
+}
+
+fn breaker(deps: &TestDeps, n: u64) -> BreakerResponse {
+    query_at(deps, &env_at(n), QueryMsg::Breaker {})
+}
+
+#[test]
+fn updates_are_validated() {
+    let mut deps = setup();
+    let update = |max_drawdown_bps, window| ExecuteMsg::UpdateBreaker { max_drawdown_bps, window };
+
+    assert_eq!(run(&mut deps, &mock_env(), PLAYER, 0, update(1000, 3600)).unwrap_err(), ContractError::Unauthorized {});
+    assert_eq!(owner(&mut deps, update(10001, 3600)).unwrap_err(), ContractError::InvalidInput {});
+    assert_eq!(owner(&mut deps, update(1000, 0)).unwrap_err(), ContractError::InvalidInput {});
+    owner(&mut deps, update(0, 0)).unwrap();
+}
+
+#[test]
+fn window_pnl_tracks_settlements() {
+    let mut deps = setup();
+    owner(&mut deps, ExecuteMsg::UpdateBreaker { max_drawdown_bps: 5000, window: 3600 }).unwrap();
+
+    let mut pnl = 0i128;
+    for n in 0..10 {
+        let res = flip(&mut deps, n).unwrap();
+        // The fee leaves the contract as well, so only a loss nets the house anything
+        pnl += if attr(&res, "win") == "0" { -(STAKE as i128) } else { (STAKE - fee(STAKE)) as i128 };
+    }
+    assert_eq!(breaker(&deps, 10).pnl.i128(), pnl);
+    // Buckets older than the window drop out
+    assert_eq!(breaker(&deps, 2 * 3600).pnl.i128(), 0);
+}
+
+#[test]
+fn a_drawdown_halts_betting_until_reset() {
+    let mut deps = setup();
+    set_bankroll(&mut deps, 5 * STAKE);
+    owner(&mut deps, ExecuteMsg::UpdateBreaker { max_drawdown_bps: 1000, window: 3600 }).unwrap();
+
+    let mut tripped_at = None;
+    for n in 0..20 {
+        let res = flip(&mut deps, n).unwrap();
+        if let Some(event) = res.events.iter().find(|e| e.ty == "circuit_breaker") {
+            assert_eq!(attr(&res, "win"), "0");
+            let at = event.attributes.iter().find(|a| a.key == "at").unwrap().value.clone();
+            tripped_at = Some(at.parse::<u64>().unwrap());
+            break;
+        }
+    }
+    let at = tripped_at.expect("breaker never tripped");
+
+    let state = breaker(&deps, 0);
+    let trip = state.trip.unwrap();
+    assert_eq!(trip.at, at);
+    assert!(trip.loss * Uint128::new(10000) > trip.bankroll * Uint128::new(1000));
+
+    assert_eq!(flip(&mut deps, 100).unwrap_err(), ContractError::Halted { at });
+    let err = run(&mut deps, &mock_env(), PLAYER, STAKE, ExecuteMsg::HiloStart { from_balance: None }).unwrap_err();
+    assert_eq!(err, ContractError::Halted { at });
+
+    assert_eq!(run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::ResetBreaker {}).unwrap_err(), ContractError::Unauthorized {});
+    owner(&mut deps, ExecuteMsg::ResetBreaker {}).unwrap();
+    let state = breaker(&deps, 100);
+    assert_eq!((state.trip, state.pnl.i128()), (None, 0));
+    flip(&mut deps, 100).unwrap();
+}
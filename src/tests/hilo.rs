@@ -0,0 +1,146 @@
+use cosmwasm_std::{Addr, Uint128};
+use crate::msg::{ExecuteMsg, HHistoryResponse, HiloChoice, HiloGameResponse, QueryMsg};
+use crate::constants::{HILO_TIMEOUT, DECK_SIZE};
+use crate::state::HILO_GAMES;
+use crate::util;
+use crate::ContractError;
+use super::*;
+
+const STAKE: u128 = 1_000_000;
+
+fn start() -> ExecuteMsg {
+    ExecuteMsg::HiloStart { from_balance: None }
+}
+
+fn guess(choice: HiloChoice) -> ExecuteMsg {
+    ExecuteMsg::HiloGuess { choice }
+}
+
+/// The likelier side for the last card
+fn best_choice(cards: &[u8]) -> HiloChoice {
+    if util::card_rank(*cards.last().unwrap()) <= 7 { HiloChoice::Higher } else { HiloChoice::Lower }
+}
+
+fn factor(cards: &[u8], choice: HiloChoice) -> u128 {
+    let rank = util::card_rank(*cards.last().unwrap());
+    let remaining: Vec<u8> = (0..DECK_SIZE).filter(|c| !cards.contains(c)).collect();
+    let winning = remaining.iter().filter(|c| match choice {
+        HiloChoice::Higher => util::card_rank(**c) >= rank,
+        HiloChoice::Lower => util::card_rank(**c) <= rank,
+    }).count() as u128;
+    1000000 * remaining.len() as u128 / winning * 990000 / 1000000
+}
+
+fn game(deps: &TestDeps, address: &str) -> HiloGameResponse {
+    query_as(deps, QueryMsg::HiloGame { address: Addr::unchecked(address) })
+}
+
+#[test]
+fn actions_are_validated() {
+    let mut deps = setup();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, guess(HiloChoice::Higher)).unwrap_err();
+    assert_eq!(err, ContractError::NoActiveGame {});
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::HiloCashout {}).unwrap_err();
+    assert_eq!(err, ContractError::NoActiveGame {});
+    let err = run(&mut deps, &mock_env(), OWNER, 0, ExecuteMsg::HiloTimeout { address: Addr::unchecked(PLAYER) }).unwrap_err();
+    assert_eq!(err, ContractError::NoActiveGame {});
+
+    run(&mut deps, &mock_env(), PLAYER, STAKE, start()).unwrap();
+    let err = run(&mut deps, &mock_env(), PLAYER, STAKE, start()).unwrap_err();
+    assert_eq!(err, ContractError::GameInProgress {});
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::HiloCashout {}).unwrap_err();
+    assert_eq!(err, ContractError::InvalidAction {});
+
+    let expires_at = mock_env().block.time.seconds() + HILO_TIMEOUT;
+    let err = run(&mut deps, &mock_env(), OWNER, 0, ExecuteMsg::HiloTimeout { address: Addr::unchecked(PLAYER) }).unwrap_err();
+    assert_eq!(err, ContractError::GameNotExpired { expires_at });
+    let err = run(&mut deps, &env_at(HILO_TIMEOUT), PLAYER, 0, guess(HiloChoice::Higher)).unwrap_err();
+    assert_eq!(err, ContractError::GameExpired {});
+}
+
+#[test]
+fn streaks_multiply_and_cash_out() {
+    let mut deps = setup();
+
+    let (mut cashed, mut lost) = (0, 0);
+    for n in 0..40u64 {
+        let player = format!("player{}", n);
+        run(&mut deps, &env_at(n), &player, STAKE, start()).unwrap();
+
+        let mut expected = 1000000u128;
+        let mut step = 0;
+        let res = loop {
+            let cards = game(&deps, &player).game.unwrap().cards;
+            let choice = best_choice(&cards);
+            let next = expected * factor(&cards, choice) / 1000000;
+            let res = run(&mut deps, &env_at(n * 10 + step), &player, 0, guess(choice)).unwrap();
+            step += 1;
+
+            match game(&deps, &player).game {
+                None => { lost += 1; break res },
+                Some(open) => {
+                    expected = next;
+                    assert_eq!(open.multiplier as u128, expected);
+                    assert_eq!(reserved(&deps), Uint128::new(STAKE * expected / 1000000));
+                    if step == 3 {
+                        cashed += 1;
+                        break run(&mut deps, &env_at(n), &player, 0, ExecuteMsg::HiloCashout {}).unwrap();
+                    }
+                }
+            }
+        };
+        assert_eq!(reserved(&deps), Uint128::zero());
+
+        let history: HHistoryResponse = query_as(&deps, QueryMsg::HiloHistory { count: 1 });
+        let record = &history.list[0];
+        let payout = if attr(&res, "multiplier") == "0" {
+            assert_eq!(record.win, Some(1));
+            0
+        } else {
+            assert_eq!(record.multiplier as u128, expected);
+            let payout = (STAKE * expected / 1000000).saturating_sub(fee(STAKE));
+            // Cashing out below the stake after the fee is no win
+            assert_eq!(record.win, Some(if payout > STAKE { 0 } else { 1 }));
+            payout
+        };
+        assert_eq!(record.payout, Uint128::new(payout));
+        assert_eq!(sent_to(&res, &player), Uint128::new(payout));
+    }
+    assert!(cashed > 0 && lost > 0, "{} {}", cashed, lost);
+}
+
+#[test]
+fn expired_games_are_cashed_out() {
+    let mut deps = setup();
+
+    for n in 0..20u64 {
+        let player = format!("player{}", n);
+        run(&mut deps, &env_at(n), &player, STAKE, start()).unwrap();
+        let cards = game(&deps, &player).game.unwrap().cards;
+        run(&mut deps, &env_at(n), &player, 0, guess(best_choice(&cards))).unwrap();
+        let Some(open) = game(&deps, &player).game else { continue };
+
+        let res = run(&mut deps, &env_at(n + HILO_TIMEOUT), OWNER, 0, ExecuteMsg::HiloTimeout { address: Addr::unchecked(&player) }).unwrap();
+        assert!(game(&deps, &player).game.is_none());
+        assert_eq!(reserved(&deps), Uint128::zero());
+        let payout = (STAKE * open.multiplier as u128 / 1000000).saturating_sub(fee(STAKE));
+        assert_eq!(sent_to(&res, &player), Uint128::new(payout));
+        return;
+    }
+    panic!("every first guess lost");
+}
+
+#[test]
+fn multiplier_overflow_is_rejected() {
+    let mut deps = setup();
+    run(&mut deps, &mock_env(), PLAYER, STAKE, start()).unwrap();
+
+    let address = Addr::unchecked(PLAYER);
+    let mut open = HILO_GAMES.load(&deps.storage, &address).unwrap();
+    open.multiplier = u64::MAX;
+    HILO_GAMES.save(&mut deps.storage, &address, &open).unwrap();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, guess(best_choice(&open.cards))).unwrap_err();
+    assert_eq!(err, ContractError::MultiplierOverflow {});
+}
@@ -0,0 +1,229 @@
+use cosmwasm_std::Uint128;
+use crate::msg::{
+    ExecuteMsg, FHistory, HistoryFilter, HistoryOutcome, HistoryPage, HistoryRetention, OldestHistoryResponse, QueryMsg,
+    RHistory, StatsResponse
+};
+use crate::constants::{HISTORY_MAX_SCAN, MAX_PRUNE_LIMIT};
+use crate::ContractError;
+use super::*;
+
+const STAKE: u128 = 10_000;
+
+fn filtered<T: DeserializeOwned>(deps: &TestDeps, game: &str, filter: &HistoryFilter, start_after: Option<u64>, limit: Option<u32>) -> HistoryPage<T> {
+    query_as(deps, QueryMsg::FilteredHistory { game: game.to_string(), filter: filter.clone(), start_after, limit })
+}
+
+/// Every flip record matching `filter`, following `next` from page to page
+fn walk(deps: &TestDeps, filter: &HistoryFilter, limit: u32) -> Vec<FHistory> {
+    let mut list = vec![];
+    let mut start_after = None;
+    loop {
+        let page: HistoryPage<FHistory> = filtered(deps, "flip", filter, start_after, Some(limit));
+        assert!(page.list.len() <= limit as usize);
+        list.extend(page.list);
+        match page.next {
+            Some(next) => start_after = Some(next),
+            None => return list
+        }
+    }
+}
+
+/// Flips every 10 seconds with stakes of 1 to 3 times `STAKE`, newest record first
+fn play(deps: &mut TestDeps, count: u64) -> Vec<FHistory> {
+    for n in 0..count {
+        let stake = STAKE * (n as u128 % 3 + 1);
+        run(deps, &env_at(n * 10), PLAYER, stake, ExecuteMsg::Flip { level: 0, from_balance: None }).unwrap();
+    }
+    let all = walk(deps, &HistoryFilter::default(), 30);
+    assert_eq!(all.iter().map(|r| r.id).collect::<Vec<u64>>(), (1..=count).rev().collect::<Vec<u64>>());
+    all
+}
+
+fn retain(deps: &mut TestDeps, keep_last: Option<u64>, max_age: Option<u64>) {
+    owner(deps, ExecuteMsg::UpdateHistoryRetention { retention: HistoryRetention { keep_last, max_age } }).unwrap();
+}
+
+/// Prunes flips as someone other than the owner, returning how many went
+fn prune(deps: &mut TestDeps, n: u64, limit: Option<u32>) -> u64 {
+    let res = run(deps, &env_at(n), PLAYER, 0, ExecuteMsg::PruneHistory { game: "flip".to_string(), limit }).unwrap();
+    // With nothing retained the id is the one the next record will get
+    if let Some(id) = oldest(deps).oldest_id {
+        assert_eq!(attr(&res, "oldest_id"), id.to_string());
+    }
+    attr(&res, "pruned").parse().unwrap()
+}
+
+fn oldest(deps: &TestDeps) -> OldestHistoryResponse {
+    query_as(deps, QueryMsg::OldestHistory { game: "flip".to_string() })
+}
+
+#[test]
+fn pages_follow_the_next_cursor() {
+    let mut deps = setup();
+    let all = play(&mut deps, 25);
+
+    let first: HistoryPage<FHistory> = filtered(&deps, "flip", &HistoryFilter::default(), None, Some(10));
+    assert_eq!(first.list, all[..10].to_vec());
+    assert_eq!(first.next, Some(all[9].id));
+    let second: HistoryPage<FHistory> = filtered(&deps, "flip", &HistoryFilter::default(), first.next, Some(10));
+    assert_eq!(second.list, all[10..20].to_vec());
+    let last: HistoryPage<FHistory> = filtered(&deps, "flip", &HistoryFilter::default(), second.next, Some(10));
+    assert_eq!((last.list, last.next), (all[20..].to_vec(), None));
+
+    // Past the oldest record
+    let page: HistoryPage<FHistory> = filtered(&deps, "flip", &HistoryFilter::default(), Some(1), None);
+    assert_eq!((page.list, page.next), (vec![], None));
+}
+
+#[test]
+fn filters_combine() {
+    let mut deps = setup();
+    let all = play(&mut deps, 40);
+    let start = all.last().unwrap().timestamp;
+
+    let expect = |keep: &dyn Fn(&FHistory) -> bool| all.iter().filter(|r| keep(r)).cloned().collect::<Vec<FHistory>>();
+
+    let range = HistoryFilter { from: Some(start + 55), to: Some(start + 200), ..HistoryFilter::default() };
+    assert_eq!(walk(&deps, &range, 4), expect(&|r| r.timestamp >= start + 55 && r.timestamp <= start + 200));
+
+    let wins = HistoryFilter { outcome: Some(HistoryOutcome::Win), ..HistoryFilter::default() };
+    let won = walk(&deps, &wins, 4);
+    assert!(!won.is_empty());
+    assert_eq!(won, expect(&|r| r.win == Some(0)));
+    let losses = HistoryFilter { outcome: Some(HistoryOutcome::Lose), ..HistoryFilter::default() };
+    assert_eq!(walk(&deps, &losses, 4), expect(&|r| r.win == Some(1)));
+    // Flips have no ties
+    let ties = HistoryFilter { outcome: Some(HistoryOutcome::Tie), ..HistoryFilter::default() };
+    assert_eq!(walk(&deps, &ties, 4), vec![]);
+
+    let big = HistoryFilter { min_bet: Some(Uint128::new(2 * STAKE)), ..HistoryFilter::default() };
+    assert_eq!(walk(&deps, &big, 4), expect(&|r| r.bet_amount.u128() >= 2 * STAKE));
+
+    let all_of_them = HistoryFilter { from: Some(start + 100), to: None, outcome: Some(HistoryOutcome::Lose), min_bet: Some(Uint128::new(3 * STAKE)) };
+    assert_eq!(walk(&deps, &all_of_them, 2), expect(&|r| r.timestamp >= start + 100 && r.win == Some(1) && r.bet_amount.u128() >= 3 * STAKE));
+
+    // A range with no record
+    let empty = HistoryFilter { from: Some(start + 1), to: Some(start + 9), ..HistoryFilter::default() };
+    assert_eq!(walk(&deps, &empty, 4), vec![]);
+}
+
+#[test]
+fn rps_ties_are_their_own_outcome() {
+    let mut deps = setup();
+    for n in 0..30u64 {
+        run(&mut deps, &env_at(n), PLAYER, STAKE, ExecuteMsg::Rps { level: n % 3, from_balance: None }).unwrap();
+    }
+    for (outcome, code) in [(HistoryOutcome::Win, 0), (HistoryOutcome::Tie, 1), (HistoryOutcome::Lose, 2)] {
+        let filter = HistoryFilter { outcome: Some(outcome), ..HistoryFilter::default() };
+        let page: HistoryPage<RHistory> = filtered(&deps, "rps", &filter, None, Some(30));
+        assert!(!page.list.is_empty());
+        assert!(page.list.iter().all(|r| r.win == Some(code)));
+    }
+}
+
+#[test]
+fn scans_stop_with_a_cursor() {
+    let mut deps = setup();
+    let count = HISTORY_MAX_SCAN as u64 + 10;
+    play(&mut deps, count);
+
+    // Nothing matches, so every record is looked at
+    let filter = HistoryFilter { min_bet: Some(Uint128::new(4 * STAKE)), ..HistoryFilter::default() };
+    let page: HistoryPage<FHistory> = filtered(&deps, "flip", &filter, None, None);
+    assert_eq!((page.list.len(), page.next), (0, Some(count - HISTORY_MAX_SCAN as u64 + 1)));
+    let page: HistoryPage<FHistory> = filtered(&deps, "flip", &filter, page.next, None);
+    assert_eq!((page.list.len(), page.next), (0, None));
+}
+
+#[test]
+fn unknown_games_are_rejected() {
+    let deps = setup();
+    let msg = QueryMsg::FilteredHistory { game: "poker".to_string(), filter: HistoryFilter::default(), start_after: None, limit: None };
+    assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+}
+
+#[test]
+fn retention_is_owner_managed() {
+    let mut deps = setup();
+    assert_eq!(query_as::<HistoryRetention>(&deps, QueryMsg::HistoryRetention {}), HistoryRetention::default());
+
+    let msg = ExecuteMsg::UpdateHistoryRetention { retention: HistoryRetention { keep_last: Some(1), max_age: None } };
+    assert_eq!(run(&mut deps, &mock_env(), PLAYER, 0, msg).unwrap_err(), ContractError::Unauthorized {});
+    retain(&mut deps, Some(5), Some(100));
+    let retention: HistoryRetention = query_as(&deps, QueryMsg::HistoryRetention {});
+    assert_eq!(retention, HistoryRetention { keep_last: Some(5), max_age: Some(100) });
+
+    let msg = ExecuteMsg::PruneHistory { game: "poker".to_string(), limit: None };
+    assert_eq!(run(&mut deps, &mock_env(), PLAYER, 0, msg).unwrap_err(), ContractError::InvalidInput {});
+    assert!(query(deps.as_ref(), mock_env(), QueryMsg::OldestHistory { game: "poker".to_string() }).is_err());
+    let res = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::PruneHistory { game: "wheel".to_string(), limit: None }).unwrap();
+    assert_eq!(attr(&res, "pruned"), "0");
+}
+
+#[test]
+fn without_retention_nothing_is_pruned() {
+    let mut deps = setup();
+    assert_eq!(oldest(&deps), OldestHistoryResponse { game: "flip".to_string(), oldest_id: None, retained: 0 });
+    play(&mut deps, 10);
+
+    assert_eq!(prune(&mut deps, 1000, None), 0);
+    assert_eq!((oldest(&deps).oldest_id, oldest(&deps).retained), (Some(1), 10));
+}
+
+#[test]
+fn the_latest_records_are_kept_in_chunks() {
+    let mut deps = setup();
+    let all = play(&mut deps, 20);
+    retain(&mut deps, Some(5), None);
+
+    assert_eq!(prune(&mut deps, 0, Some(10)), 10);
+    assert_eq!((oldest(&deps).oldest_id, oldest(&deps).retained), (Some(11), 10));
+    assert_eq!(prune(&mut deps, 0, Some(10)), 5);
+    assert_eq!((oldest(&deps).oldest_id, oldest(&deps).retained), (Some(16), 5));
+    assert_eq!(prune(&mut deps, 0, Some(10)), 0);
+
+    // Pruned records are gone from every index
+    assert_eq!(walk(&deps, &HistoryFilter::default(), 2), all[..5].to_vec());
+    let wins = HistoryFilter { outcome: Some(HistoryOutcome::Win), ..HistoryFilter::default() };
+    assert_eq!(walk(&deps, &wins, 2), all[..5].iter().filter(|r| r.win == Some(0)).cloned().collect::<Vec<FHistory>>());
+    let pruned = HistoryFilter { to: Some(all[5].timestamp), ..HistoryFilter::default() };
+    assert_eq!(walk(&deps, &pruned, 2), vec![]);
+
+    // Statistics outlive the records
+    let stats: StatsResponse = query_as(&deps, QueryMsg::Stats { game: "flip".to_string() });
+    assert_eq!(stats.stats.bets, 20);
+
+    // New records keep counting from the last id
+    run(&mut deps, &env_at(1000), PLAYER, STAKE, ExecuteMsg::Flip { level: 0, from_balance: None }).unwrap();
+    assert_eq!((oldest(&deps).oldest_id, oldest(&deps).retained), (Some(16), 6));
+    assert_eq!(walk(&deps, &HistoryFilter::default(), 30)[0].id, 21);
+}
+
+#[test]
+fn old_records_age_out() {
+    let mut deps = setup();
+    play(&mut deps, 20);
+    retain(&mut deps, None, Some(100));
+
+    // Flips were made every 10 seconds, those of the last 100 seconds stay
+    assert_eq!(prune(&mut deps, 200, None), 11);
+    assert_eq!((oldest(&deps).oldest_id, oldest(&deps).retained), (Some(12), 9));
+
+    // With both rules a record stays if either keeps it
+    retain(&mut deps, Some(5), Some(100));
+    assert_eq!(prune(&mut deps, 10_000, None), 4);
+    assert_eq!((oldest(&deps).oldest_id, oldest(&deps).retained), (Some(16), 5));
+}
+
+#[test]
+fn everything_can_be_pruned() {
+    let mut deps = setup();
+    let count = MAX_PRUNE_LIMIT as u64 + 20;
+    play(&mut deps, count);
+    retain(&mut deps, Some(0), None);
+
+    assert_eq!(prune(&mut deps, 0, Some(1000)), MAX_PRUNE_LIMIT as u64);
+    assert_eq!(prune(&mut deps, 0, Some(1000)), 20);
+    assert_eq!(oldest(&deps), OldestHistoryResponse { game: "flip".to_string(), oldest_id: None, retained: 0 });
+    assert_eq!(walk(&deps, &HistoryFilter::default(), 30), vec![]);
+}
@@ -0,0 +1,63 @@
+use cosmwasm_std::Uint128;
+use crate::msg::{ExecuteMsg, JackpotResponse, JackpotWinnersResponse, QueryMsg};
+use crate::ContractError;
+use super::*;
+
+fn flip() -> ExecuteMsg {
+    ExecuteMsg::Flip { level: 0, from_balance: None }
+}
+
+#[test]
+fn owner_controls_are_validated() {
+    let mut deps = setup();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::UpdateJackpot { contribution_bps: 100, odds: 10 }).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = run(&mut deps, &mock_env(), PLAYER, 1000, ExecuteMsg::SeedJackpot {}).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = owner(&mut deps, ExecuteMsg::UpdateJackpot { contribution_bps: 10001, odds: 10 }).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+
+    owner(&mut deps, ExecuteMsg::UpdateJackpot { contribution_bps: 100, odds: 10 }).unwrap();
+    run(&mut deps, &mock_env(), OWNER, 5000, ExecuteMsg::SeedJackpot {}).unwrap();
+    let jackpot: JackpotResponse = query_as(&deps, QueryMsg::Jackpot {});
+    assert_eq!(jackpot, JackpotResponse { amount: Uint128::new(5000), contribution_bps: 100, odds: 10, win_count: 0 });
+}
+
+#[test]
+fn bets_grow_the_pool_without_a_draw() {
+    let mut deps = setup();
+    owner(&mut deps, ExecuteMsg::UpdateJackpot { contribution_bps: 100, odds: 0 }).unwrap();
+
+    for n in 0..5 {
+        let res = run(&mut deps, &env_at(n), PLAYER, 1_000_000, flip()).unwrap();
+        assert_eq!(attr(&res, "jackpot"), "0");
+    }
+    let jackpot: JackpotResponse = query_as(&deps, QueryMsg::Jackpot {});
+    assert_eq!(jackpot.amount, Uint128::new(50_000));
+    assert_eq!(jackpot.win_count, 0);
+}
+
+#[test]
+fn a_draw_pays_and_empties_the_pool() {
+    let mut deps = setup();
+    owner(&mut deps, ExecuteMsg::UpdateJackpot { contribution_bps: 100, odds: 1 }).unwrap();
+    run(&mut deps, &mock_env(), OWNER, 40_000, ExecuteMsg::SeedJackpot {}).unwrap();
+
+    let amount = 1_000_000u128;
+    let res = run(&mut deps, &mock_env(), PLAYER, amount, flip()).unwrap();
+    assert_eq!(attr(&res, "jackpot"), "50000");
+    let reward = if attr(&res, "win") == "0" { 2 * amount - fee(amount) } else { 0 };
+    assert_eq!(sent_to(&res, PLAYER), Uint128::new(50_000 + reward));
+
+    let jackpot: JackpotResponse = query_as(&deps, QueryMsg::Jackpot {});
+    assert_eq!(jackpot.amount, Uint128::zero());
+    assert_eq!(jackpot.win_count, 1);
+
+    let winners: JackpotWinnersResponse = query_as(&deps, QueryMsg::JackpotWinners { count: 10 });
+    assert_eq!(winners.list.len(), 1);
+    assert_eq!(winners.list[0].id, 1);
+    assert_eq!(winners.list[0].address.as_str(), PLAYER);
+    assert_eq!(winners.list[0].game, "flip");
+    assert_eq!(winners.list[0].amount, Uint128::new(50_000));
+}
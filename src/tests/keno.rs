@@ -0,0 +1,90 @@
+use cosmwasm_std::Uint128;
+use crate::msg::{ExecuteMsg, KHistoryResponse, KenoTableResponse, QueryMsg};
+use crate::util;
+use crate::ContractError;
+use super::*;
+
+const TABLE: [u64; 3] = [0, 1000000, 9000000];
+
+fn keno(picks: Vec<u8>) -> ExecuteMsg {
+    ExecuteMsg::Keno { picks, from_balance: None }
+}
+
+#[test]
+fn table_updates_are_validated() {
+    let mut deps = setup();
+
+    let update = |picks, multipliers: &[u64]| ExecuteMsg::UpdateKenoTable { picks, multipliers: multipliers.to_vec() };
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, update(2, &TABLE)).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = owner(&mut deps, update(3, &TABLE)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    let err = owner(&mut deps, update(11, &[0; 12])).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    let err = owner(&mut deps, update(2, &[0, 3000000, 9000000])).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidRtp { .. }));
+
+    owner(&mut deps, update(2, &TABLE)).unwrap();
+    let table: KenoTableResponse = query_as(&deps, QueryMsg::KenoTable { picks: 2 });
+    assert_eq!(table.multipliers, TABLE.to_vec());
+    // 1 hit with 300/780 odds, 2 hits with 45/780
+    assert_eq!(table.rtp, util::keno_rtp(&TABLE));
+    assert_eq!(table.rtp, (300 * 1000000 + 45 * 9000000) / 780);
+}
+
+#[test]
+fn picks_are_validated() {
+    let mut deps = setup();
+    owner(&mut deps, ExecuteMsg::UpdateKenoTable { picks: 2, multipliers: TABLE.to_vec() }).unwrap();
+
+    for picks in [vec![], vec![1; 11], vec![0, 1], vec![1, 41], vec![5, 5]] {
+        let err = run(&mut deps, &mock_env(), PLAYER, 1000, keno(picks)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidBet {});
+    }
+    let err = run(&mut deps, &mock_env(), PLAYER, 1000, keno(vec![1, 2, 3])).unwrap_err();
+    assert_eq!(err, ContractError::KenoTableNotSet { picks: 3 });
+}
+
+#[test]
+fn draws_and_payouts_are_recorded() {
+    let mut deps = setup();
+    owner(&mut deps, ExecuteMsg::UpdateKenoTable { picks: 2, multipliers: TABLE.to_vec() }).unwrap();
+
+    let amount = 1_000_000u128;
+    let mut seen = [false; 3];
+    for n in 0..60 {
+        let res = run(&mut deps, &env_at(n), PLAYER, amount, keno(vec![7, 33])).unwrap();
+
+        let history: KHistoryResponse = query_as(&deps, QueryMsg::KistoryMsg { count: 1 });
+        let record = &history.list[0];
+        assert_eq!(record.id, n + 1);
+        assert_eq!(record.picks, vec![7, 33]);
+        assert_eq!(record.draws.len(), 10);
+        let mut draws = record.draws.clone();
+        draws.sort_unstable();
+        draws.dedup();
+        assert_eq!(draws.len(), 10);
+        assert!(draws.iter().all(|d| (1..=40).contains(d)));
+
+        let hits = record.draws.iter().filter(|d| **d == 7 || **d == 33).count();
+        assert_eq!(record.hits as usize, hits);
+        assert_eq!(attr(&res, "hits"), hits.to_string());
+
+        let expected = (amount * TABLE[hits] as u128 / 1000000).saturating_sub(fee(amount));
+        assert_eq!(record.payout, Uint128::new(expected));
+        assert_eq!(sent_to(&res, PLAYER), Uint128::new(expected));
+        assert_eq!(record.win, Some(if hits == 2 { 0 } else { 1 }));
+        seen[hits] = true;
+    }
+    assert!(seen[0] && seen[1]);
+}
+
+#[test]
+fn bankroll_must_cover_the_top_multiplier() {
+    let mut deps = setup();
+    owner(&mut deps, ExecuteMsg::UpdateKenoTable { picks: 2, multipliers: TABLE.to_vec() }).unwrap();
+    set_bankroll(&mut deps, 8_000_000);
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 1_000_000, keno(vec![1, 2])).unwrap_err();
+    assert_eq!(err, ContractError::InsufficientFunds {});
+}
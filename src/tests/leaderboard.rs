@@ -0,0 +1,157 @@
+use cosmwasm_std::{Addr, Env, Uint128};
+use crate::msg::{ExecuteMsg, LeaderboardKind, LeaderboardPeriod, LeaderboardResponse, QueryMsg};
+use cosmwasm_std::{Empty, Order};
+use cw_storage_plus::Map;
+use crate::constants::{DAY, LEADERBOARD_SIZE, WEEK};
+use crate::state::{LEADERBOARD_TOTALS, PROFIT_RANKING, WAGERED_RANKING};
+use crate::ContractError;
+use super::*;
+
+const STAKE: u128 = 10_000;
+
+/// `secs` into the week after the mock time, so that every day of it is in the same week
+fn at(secs: u64) -> Env {
+    let now = mock_env().block.time.seconds();
+    env_at((now / WEEK + 1) * WEEK - now + secs)
+}
+
+fn board(deps: &TestDeps, env: &Env, period: LeaderboardPeriod, kind: LeaderboardKind, index: Option<u64>) -> LeaderboardResponse {
+    query_at(deps, env, QueryMsg::Leaderboard { period, kind, index, limit: None })
+}
+
+fn ranked(board: &LeaderboardResponse) -> Vec<(u32, &str)> {
+    board.list.iter().map(|entry| (entry.rank, entry.address.as_str())).collect()
+}
+
+/// Flips `stakes` as `player`, returning its profit
+fn play(deps: &mut TestDeps, env: &Env, player: &str, stakes: &[u128]) -> i128 {
+    let mut profit = 0;
+    for stake in stakes {
+        let res = run(deps, env, player, *stake, ExecuteMsg::Flip { level: 0, from_balance: None }).unwrap();
+        let paid = if attr(&res, "win") == "0" { 2 * stake - fee(*stake) } else { 0 };
+        profit += paid as i128 - *stake as i128;
+    }
+    profit
+}
+
+fn pay(deps: &mut TestDeps, env: &Env, sender: &str, index: u64, prizes: &[u128]) -> Result<Response, ContractError> {
+    let prizes = prizes.iter().copied().map(Uint128::new).collect();
+    run(deps, env, sender, 0, ExecuteMsg::PayLeaderboard { period: LeaderboardPeriod::Daily, kind: LeaderboardKind::Wagered, index, prizes })
+}
+
+#[test]
+fn boards_rank_wagers_and_profits() {
+    let mut deps = setup();
+    let env = at(0);
+
+    let mut profits = vec![
+        ("alice", play(&mut deps, &env, "alice", &[3 * STAKE, STAKE])),
+        ("bob", play(&mut deps, &env, "bob", &[STAKE])),
+        ("carl", play(&mut deps, &env, "carl", &[2 * STAKE, STAKE])),
+    ];
+
+    let wagered = board(&deps, &env, LeaderboardPeriod::Daily, LeaderboardKind::Wagered, None);
+    assert_eq!(ranked(&wagered), vec![(1, "alice"), (2, "carl"), (3, "bob")]);
+    assert_eq!(wagered.list[0].wagered, Uint128::new(4 * STAKE));
+    let index = env.block.time.seconds() / DAY;
+    assert_eq!((wagered.index, wagered.starts_at, wagered.ends_at), (index, index * DAY, (index + 1) * DAY));
+    assert_eq!(wagered.paid, None);
+
+    // Losses rank below gains
+    profits.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(a.0)));
+    let profit = board(&deps, &env, LeaderboardPeriod::Daily, LeaderboardKind::Profit, None);
+    let listed: Vec<(&str, i128)> = profit.list.iter().map(|entry| (entry.address.as_str(), entry.profit.i128())).collect();
+    assert_eq!(listed, profits);
+}
+
+#[test]
+fn periods_start_over() {
+    let mut deps = setup();
+
+    play(&mut deps, &at(0), "alice", &[STAKE]);
+    play(&mut deps, &at(DAY), "bob", &[2 * STAKE]);
+
+    let today = board(&deps, &at(DAY), LeaderboardPeriod::Daily, LeaderboardKind::Wagered, None);
+    assert_eq!(ranked(&today), vec![(1, "bob")]);
+    let yesterday = board(&deps, &at(DAY), LeaderboardPeriod::Daily, LeaderboardKind::Wagered, Some(today.index - 1));
+    assert_eq!(ranked(&yesterday), vec![(1, "alice")]);
+    let week = board(&deps, &at(DAY), LeaderboardPeriod::Weekly, LeaderboardKind::Wagered, None);
+    assert_eq!(ranked(&week), vec![(1, "bob"), (2, "alice")]);
+    assert_eq!(week.list[1].profit, board(&deps, &at(0), LeaderboardPeriod::Daily, LeaderboardKind::Profit, None).list[0].profit);
+}
+
+#[test]
+fn finished_boards_are_paid_once() {
+    let mut deps = setup();
+    play(&mut deps, &at(0), "alice", &[2 * STAKE]);
+    play(&mut deps, &at(0), "bob", &[STAKE]);
+    let index = at(0).block.time.seconds() / DAY;
+    let later = at(DAY);
+
+    assert_eq!(pay(&mut deps, &later, PLAYER, index, &[100]).unwrap_err(), ContractError::Unauthorized {});
+    assert_eq!(pay(&mut deps, &later, OWNER, index, &[]).unwrap_err(), ContractError::InvalidInput {});
+    assert_eq!(pay(&mut deps, &at(0), OWNER, index, &[100]).unwrap_err(), ContractError::InvalidInput {});
+
+    set_bankroll(&mut deps, 250);
+    assert_eq!(pay(&mut deps, &later, OWNER, index, &[200, 100, 50]).unwrap_err(), ContractError::InsufficientFunds {});
+    set_bankroll(&mut deps, BANKROLL);
+
+    // The third prize has no one to go to
+    let res = pay(&mut deps, &later, OWNER, index, &[200, 100, 50]).unwrap();
+    assert_eq!((sent_to(&res, "alice"), sent_to(&res, "bob")), (Uint128::new(200), Uint128::new(100)));
+    assert_eq!((attr(&res, "winners"), attr(&res, "amount")), ("2", "300"));
+    let paid = board(&deps, &later, LeaderboardPeriod::Daily, LeaderboardKind::Wagered, Some(index)).paid;
+    assert_eq!(paid, Some(Uint128::new(300)));
+
+    assert_eq!(pay(&mut deps, &later, OWNER, index, &[200]).unwrap_err(), ContractError::LeaderboardAlreadyPaid {});
+    // Each kind is paid on its own
+    let msg = ExecuteMsg::PayLeaderboard { period: LeaderboardPeriod::Daily, kind: LeaderboardKind::Profit, index, prizes: vec![Uint128::new(10)] };
+    run(&mut deps, &later, OWNER, 0, msg).unwrap();
+}
+
+#[test]
+fn prizes_of_blocked_winners_are_frozen() {
+    let mut deps = setup();
+    play(&mut deps, &at(0), "alice", &[2 * STAKE]);
+    play(&mut deps, &at(0), "bob", &[STAKE]);
+    owner(&mut deps, ExecuteMsg::UpdateBlocklist { add: vec![Addr::unchecked("alice")], remove: vec![] }).unwrap();
+    let index = at(0).block.time.seconds() / DAY;
+
+    let res = pay(&mut deps, &at(DAY), OWNER, index, &[200, 100]).unwrap();
+    assert_eq!((sent_to(&res, "alice"), sent_to(&res, "bob")), (Uint128::zero(), Uint128::new(100)));
+    assert_eq!((attr(&res, "amount"), attr(&res, "frozen")), ("300", "200"));
+    assert_eq!(balance(&deps, "alice"), Uint128::new(200));
+    assert_eq!(pay(&mut deps, &at(DAY), OWNER, index, &[200]).unwrap_err(), ContractError::LeaderboardAlreadyPaid {});
+}
+
+#[test]
+fn rankings_keep_only_the_top_players() {
+    let mut deps = setup();
+    let size = LEADERBOARD_SIZE as usize;
+    let players: Vec<String> = (0..=size).map(|n| format!("player{:03}", n)).collect();
+    for (n, player) in players.iter().enumerate() {
+        play(&mut deps, &at(n as u64), player, &[(n as u128 + 1) * 100]);
+    }
+
+    for period in [LeaderboardPeriod::Daily, LeaderboardPeriod::Weekly] {
+        let board = (period as u8, at(0).block.time.seconds() / period.length());
+        let listed = |ranking: Map<'static, ((u8, u64), u128, &'static Addr), Empty>| -> Vec<Addr> {
+            ranking.sub_prefix(board).keys(&deps.storage, None, None, Order::Ascending).map(|key| key.unwrap().1).collect()
+        };
+        let wagered = listed(WAGERED_RANKING);
+        let profit = listed(PROFIT_RANKING);
+        assert_eq!((wagered.len(), profit.len()), (size, size));
+        // The smallest wager is the one left out
+        assert!(!wagered.contains(&Addr::unchecked(&players[0])));
+
+        // Only players still ranked keep their totals
+        let kept: Vec<Addr> = LEADERBOARD_TOTALS.prefix(board)
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .map(|key| key.unwrap())
+            .collect();
+        let mut ranked: Vec<Addr> = wagered.into_iter().chain(profit).collect();
+        ranked.sort();
+        ranked.dedup();
+        assert_eq!(kept, ranked);
+    }
+}
@@ -0,0 +1,73 @@
+use cosmwasm_std::{Addr, Uint128};
+use crate::msg::{BalancesResponse, ExecuteMsg, QueryMsg};
+use crate::ContractError;
+use super::*;
+
+#[test]
+fn deposits_and_withdrawals_move_the_balance() {
+    let mut deps = setup();
+
+    run(&mut deps, &mock_env(), PLAYER, 5000, ExecuteMsg::Deposit {}).unwrap();
+    let res = run(&mut deps, &mock_env(), PLAYER, 2000, ExecuteMsg::Deposit {}).unwrap();
+    assert_eq!(attr(&res, "balance"), "7000");
+    assert_eq!(balance(&deps, PLAYER), Uint128::new(7000));
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::WithdrawBalance { amount: Uint128::zero() }).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::WithdrawBalance { amount: Uint128::new(7001) }).unwrap_err();
+    assert_eq!(err, ContractError::InsufficientBalance { balance: Uint128::new(7000) });
+
+    let res = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::WithdrawBalance { amount: Uint128::new(3000) }).unwrap();
+    assert_eq!(sent_to(&res, PLAYER), Uint128::new(3000));
+    assert_eq!(balance(&deps, PLAYER), Uint128::new(4000));
+
+    run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::WithdrawBalance { amount: Uint128::new(4000) }).unwrap();
+    let balances: BalancesResponse = query_as(&deps, QueryMsg::Balances { start_after: None, limit: None });
+    assert!(balances.list.is_empty());
+    assert_eq!(balances.total, Uint128::zero());
+}
+
+#[test]
+fn bets_from_the_balance_settle_internally() {
+    let mut deps = setup();
+    run(&mut deps, &mock_env(), PLAYER, 10_000_000, ExecuteMsg::Deposit {}).unwrap();
+
+    let bet = |amount: u128| ExecuteMsg::Flip { level: 0, from_balance: Some(Uint128::new(amount)) };
+    let err = run(&mut deps, &mock_env(), PLAYER, 1000, bet(1000)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, bet(0)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, bet(10_000_001)).unwrap_err();
+    assert_eq!(err, ContractError::InsufficientBalance { balance: Uint128::new(10_000_000) });
+
+    let amount = 1_000_000u128;
+    let mut expected = 10_000_000u128;
+    for n in 0..10 {
+        let res = run(&mut deps, &env_at(n), PLAYER, 0, bet(amount)).unwrap();
+        assert_eq!(sent_to(&res, PLAYER), Uint128::zero());
+        expected -= amount;
+        if attr(&res, "win") == "0" {
+            expected += 2 * amount - fee(amount);
+        }
+        assert_eq!(balance(&deps, PLAYER), Uint128::new(expected));
+    }
+}
+
+#[test]
+fn balances_are_listed_and_kept_out_of_the_bankroll() {
+    let mut deps = setup();
+    for player in ["carol", "alice", "bob"] {
+        run(&mut deps, &mock_env(), player, 1000, ExecuteMsg::Deposit {}).unwrap();
+    }
+
+    let page: BalancesResponse = query_as(&deps, QueryMsg::Balances { start_after: None, limit: Some(2) });
+    assert_eq!(page.list.iter().map(|b| b.address.as_str()).collect::<Vec<_>>(), vec!["alice", "bob"]);
+    assert_eq!(page.total, Uint128::new(3000));
+    let page: BalancesResponse = query_as(&deps, QueryMsg::Balances { start_after: Some(Addr::unchecked("bob")), limit: Some(2) });
+    assert_eq!(page.list.iter().map(|b| b.address.as_str()).collect::<Vec<_>>(), vec!["carol"]);
+
+    // Deposits back balances, not bets
+    let err = owner(&mut deps, ExecuteMsg::Withdraw { amount: Uint128::new(BANKROLL) }).unwrap_err();
+    assert_eq!(err, ContractError::NotEnoughCoins { contract_amount: Uint128::new(BANKROLL - 3000) });
+    owner(&mut deps, ExecuteMsg::Withdraw { amount: Uint128::new(BANKROLL - 3000) }).unwrap();
+}
@@ -0,0 +1,127 @@
+use cosmwasm_std::{Addr, Uint128};
+use crate::msg::{BetLimits, ExecuteMsg, LimitsResponse, QueryMsg};
+use crate::constants::{DAY, LIMITS_DELAY};
+use crate::ContractError;
+use super::*;
+
+const STAKE: u128 = 1_000_000;
+
+fn set(deps: &mut TestDeps, env: &cosmwasm_std::Env, limits: BetLimits) -> Result<cosmwasm_std::Response, ContractError> {
+    run(deps, env, PLAYER, 0, ExecuteMsg::SetLimits { limits })
+}
+
+fn flip(deps: &mut TestDeps, env: &cosmwasm_std::Env) -> Result<cosmwasm_std::Response, ContractError> {
+    run(deps, env, PLAYER, STAKE, ExecuteMsg::Flip { level: 0, from_balance: None })
+}
+
+fn limits(deps: &TestDeps, env: &cosmwasm_std::Env) -> LimitsResponse {
+    query_at(deps, env, QueryMsg::Limits { address: Addr::unchecked(PLAYER) })
+}
+
+fn daily_wager(amount: u128) -> BetLimits {
+    BetLimits { daily_wager: Some(Uint128::new(amount)), ..BetLimits::default() }
+}
+
+#[test]
+fn zero_caps_are_rejected() {
+    let mut deps = setup();
+    let limits = BetLimits { weekly_loss: Some(Uint128::zero()), ..BetLimits::default() };
+    assert_eq!(set(&mut deps, &mock_env(), limits).unwrap_err(), ContractError::InvalidInput {});
+}
+
+#[test]
+fn wager_caps_reset_with_the_window() {
+    let mut deps = setup();
+    set(&mut deps, &mock_env(), daily_wager(2 * STAKE)).unwrap();
+
+    flip(&mut deps, &mock_env()).unwrap();
+    flip(&mut deps, &env_at(1)).unwrap();
+    let err = flip(&mut deps, &env_at(2)).unwrap_err();
+    assert_eq!(err, ContractError::WagerLimitExceeded { period: "daily".to_string(), limit: Uint128::new(2 * STAKE) });
+    assert_eq!(limits(&deps, &env_at(2)).daily.wagered, Uint128::new(2 * STAKE));
+
+    // The next day starts a fresh window, the week keeps counting
+    let next_day = env_at(DAY);
+    assert_eq!(limits(&deps, &next_day).daily.wagered, Uint128::zero());
+    flip(&mut deps, &next_day).unwrap();
+}
+
+#[test]
+fn returns_offset_losses() {
+    let mut deps = setup();
+    let limit = 3 * STAKE;
+    set(&mut deps, &mock_env(), BetLimits { daily_loss: Some(Uint128::new(limit)), ..BetLimits::default() }).unwrap();
+
+    for n in 0..50 {
+        let before = limits(&deps, &env_at(n)).daily;
+        match flip(&mut deps, &env_at(n)) {
+            Ok(_) => {
+                let after = limits(&deps, &env_at(n)).daily;
+                assert_eq!(after.wagered, before.wagered + Uint128::new(STAKE));
+                assert!(after.returned == before.returned || after.returned == before.returned + Uint128::new(2 * STAKE - fee(STAKE)));
+            },
+            Err(err) => {
+                assert_eq!(err, ContractError::LossLimitExceeded { period: "daily".to_string(), limit: Uint128::new(limit) });
+                assert!(before.wagered.saturating_sub(before.returned).u128() + STAKE > limit);
+                return;
+            }
+        }
+    }
+    panic!("loss limit never reached");
+}
+
+#[test]
+fn tightening_is_immediate_and_loosening_delayed() {
+    let mut deps = setup();
+    set(&mut deps, &mock_env(), daily_wager(5 * STAKE)).unwrap();
+    set(&mut deps, &mock_env(), daily_wager(STAKE)).unwrap();
+    assert_eq!(limits(&deps, &mock_env()).limits, daily_wager(STAKE));
+
+    let res = set(&mut deps, &mock_env(), daily_wager(3 * STAKE)).unwrap();
+    let pending_at = mock_env().block.time.seconds() + LIMITS_DELAY;
+    assert_eq!(attr(&res, "pending_at"), pending_at.to_string());
+
+    let now = limits(&deps, &mock_env());
+    assert_eq!(now.limits, daily_wager(STAKE));
+    assert_eq!(now.pending, Some(daily_wager(3 * STAKE)));
+
+    flip(&mut deps, &mock_env()).unwrap();
+    let err = flip(&mut deps, &env_at(1)).unwrap_err();
+    assert_eq!(err, ContractError::WagerLimitExceeded { period: "daily".to_string(), limit: Uint128::new(STAKE) });
+
+    let later = limits(&deps, &env_at(LIMITS_DELAY));
+    assert_eq!(later.limits, daily_wager(3 * STAKE));
+    assert_eq!(later.pending, None);
+
+    // Dropping a cap altogether is a loosening too
+    set(&mut deps, &env_at(LIMITS_DELAY), BetLimits::default()).unwrap();
+    assert_eq!(limits(&deps, &env_at(LIMITS_DELAY)).limits, daily_wager(3 * STAKE));
+}
+
+#[test]
+fn cooldown_spaces_out_bets() {
+    let mut deps = setup();
+    set(&mut deps, &mock_env(), BetLimits { cooldown: 60, ..BetLimits::default() }).unwrap();
+
+    flip(&mut deps, &mock_env()).unwrap();
+    let until = mock_env().block.time.seconds() + 60;
+    assert_eq!(flip(&mut deps, &env_at(59)).unwrap_err(), ContractError::CooldownActive { until });
+    flip(&mut deps, &env_at(60)).unwrap();
+}
+
+#[test]
+fn self_exclusion_blocks_every_game_and_only_extends() {
+    let mut deps = setup();
+    let until = mock_env().block.time.seconds() + 1000;
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::SelfExclude { until: mock_env().block.time.seconds() }).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::SelfExclude { until }).unwrap();
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::SelfExclude { until: until - 1 }).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+
+    assert_eq!(flip(&mut deps, &mock_env()).unwrap_err(), ContractError::SelfExcluded { until });
+    let err = run(&mut deps, &mock_env(), PLAYER, STAKE, ExecuteMsg::HiloStart { from_balance: None }).unwrap_err();
+    assert_eq!(err, ContractError::SelfExcluded { until });
+    flip(&mut deps, &env_at(1000)).unwrap();
+}
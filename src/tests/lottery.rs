@@ -0,0 +1,172 @@
+use cosmwasm_std::{Addr, Uint128};
+use crate::msg::{ExecuteMsg, LotteryRound, LotteryRoundsResponse, LotteryTicketsResponse, QueryMsg};
+use crate::constants::TREASURY_ADDR;
+use crate::ContractError;
+use super::*;
+
+const PRICE: u128 = 1000;
+
+fn open(end_time: u64, prize_tiers: Vec<u64>) -> ExecuteMsg {
+    ExecuteMsg::OpenLottery { ticket_price: Uint128::new(PRICE), end_time, prize_tiers }
+}
+
+fn end_time() -> u64 {
+    mock_env().block.time.seconds() + 100
+}
+
+#[test]
+fn rounds_are_validated() {
+    let mut deps = setup();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, open(end_time(), vec![10000])).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    for tiers in [vec![], vec![0], vec![6000, 5000]] {
+        assert_eq!(owner(&mut deps, open(end_time(), tiers)).unwrap_err(), ContractError::InvalidInput {});
+    }
+    let err = owner(&mut deps, open(mock_env().block.time.seconds(), vec![10000])).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+
+    owner(&mut deps, open(end_time(), vec![10000])).unwrap();
+    let err = owner(&mut deps, open(end_time(), vec![10000])).unwrap_err();
+    assert_eq!(err, ContractError::LotteryRoundOpen { round_id: 1 });
+}
+
+#[test]
+fn tickets_need_an_open_round_and_exact_payment() {
+    let mut deps = setup();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, PRICE, ExecuteMsg::BuyTickets { count: 1, from_balance: None }).unwrap_err();
+    assert_eq!(err, ContractError::LotteryClosed {});
+
+    owner(&mut deps, open(end_time(), vec![10000])).unwrap();
+    let err = run(&mut deps, &mock_env(), PLAYER, PRICE, ExecuteMsg::BuyTickets { count: 2, from_balance: None }).unwrap_err();
+    assert_eq!(err, ContractError::TicketPaymentMismatch { expected: Uint128::new(2 * PRICE) });
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::BuyTickets { count: 0, from_balance: None }).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+
+    run(&mut deps, &mock_env(), PLAYER, 3 * PRICE, ExecuteMsg::BuyTickets { count: 3, from_balance: None }).unwrap();
+    run(&mut deps, &mock_env(), PLAYER, 2 * PRICE, ExecuteMsg::BuyTickets { count: 2, from_balance: None }).unwrap();
+    let tickets: LotteryTicketsResponse = query_as(&deps, QueryMsg::LotteryTickets { round_id: 1, address: Addr::unchecked(PLAYER) });
+    assert_eq!(tickets.tickets, 5);
+    assert_eq!(reserved(&deps), Uint128::new(5 * PRICE));
+
+    let err = run(&mut deps, &env_at(100), PLAYER, PRICE, ExecuteMsg::BuyTickets { count: 1, from_balance: None }).unwrap_err();
+    assert_eq!(err, ContractError::LotteryClosed {});
+}
+
+#[test]
+fn anyone_draws_after_the_end() {
+    let mut deps = setup();
+    owner(&mut deps, open(end_time(), vec![10000])).unwrap();
+    run(&mut deps, &mock_env(), PLAYER, PRICE, ExecuteMsg::BuyTickets { count: 1, from_balance: None }).unwrap();
+
+    let err = run(&mut deps, &env_at(99), PLAYER, 0, ExecuteMsg::DrawLottery { round_id: 1 }).unwrap_err();
+    assert_eq!(err, ContractError::LotteryNotEnded { end_time: end_time() });
+
+    run(&mut deps, &env_at(100), "anyone", 0, ExecuteMsg::DrawLottery { round_id: 1 }).unwrap();
+    let err = run(&mut deps, &env_at(100), OWNER, 0, ExecuteMsg::DrawLottery { round_id: 1 }).unwrap_err();
+    assert_eq!(err, ContractError::LotteryAlreadyDrawn {});
+}
+
+#[test]
+fn winners_are_fixed_when_sales_close() {
+    let sell = |deps: &mut TestDeps| {
+        owner(deps, open(end_time(), vec![5000, 3000, 2000])).unwrap();
+        let mut seeds = vec![query_as::<LotteryRound>(deps, QueryMsg::LotteryRound { round_id: 1 }).seed];
+        for (n, buyer) in ["alice", "bob", "carl", "dan"].iter().enumerate() {
+            run(deps, &env_at(n as u64), buyer, 2 * PRICE, ExecuteMsg::BuyTickets { count: 2, from_balance: None }).unwrap();
+            seeds.push(query_as::<LotteryRound>(deps, QueryMsg::LotteryRound { round_id: 1 }).seed);
+        }
+        // Every sale moves the seed
+        let mut distinct = seeds.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), seeds.len());
+    };
+
+    // The same sales drawn by someone else in a much later block pick the same tickets
+    let mut deps = setup();
+    sell(&mut deps);
+    run(&mut deps, &env_at(100), "alice", 0, ExecuteMsg::DrawLottery { round_id: 1 }).unwrap();
+    let mut other = setup();
+    sell(&mut other);
+    run(&mut other, &env_at(5000), "dan", 0, ExecuteMsg::DrawLottery { round_id: 1 }).unwrap();
+
+    let round: LotteryRound = query_as(&deps, QueryMsg::LotteryRound { round_id: 1 });
+    assert_eq!(round.winners.len(), 3);
+    assert_eq!(round, query_as::<LotteryRound>(&other, QueryMsg::LotteryRound { round_id: 1 }));
+}
+
+#[test]
+fn draw_pays_the_tiers_and_releases_the_pot() {
+    let mut deps = setup();
+    owner(&mut deps, open(end_time(), vec![5000, 3000, 1000])).unwrap();
+    run(&mut deps, &mock_env(), "alice", 6 * PRICE, ExecuteMsg::BuyTickets { count: 6, from_balance: None }).unwrap();
+    run(&mut deps, &mock_env(), "bob", 4 * PRICE, ExecuteMsg::BuyTickets { count: 4, from_balance: None }).unwrap();
+
+    let res = run(&mut deps, &env_at(100), PLAYER, 0, ExecuteMsg::DrawLottery { round_id: 1 }).unwrap();
+    assert_eq!(reserved(&deps), Uint128::zero());
+
+    let pot = 10 * PRICE;
+    let fee = fee(pot);
+    let prize_pool = pot - fee;
+    assert_eq!(sent_to(&res, TREASURY_ADDR), Uint128::new(fee));
+
+    let round: LotteryRound = query_as(&deps, QueryMsg::LotteryRound { round_id: 1 });
+    assert!(round.drawn);
+    assert_eq!(round.pot, Uint128::new(pot));
+    assert_eq!(round.fee, Uint128::new(fee));
+    assert_eq!(round.winners.len(), 3);
+
+    let mut tickets: Vec<u64> = round.winners.iter().map(|w| w.ticket).collect();
+    tickets.sort_unstable();
+    tickets.dedup();
+    assert_eq!(tickets.len(), 3);
+    for (winner, share) in round.winners.iter().zip([5000u128, 3000, 1000]) {
+        assert_eq!(winner.amount, Uint128::new(prize_pool * share / 10000));
+        // Tickets 0..6 were bought by alice, 6..10 by bob
+        assert_eq!(winner.address.as_str(), if winner.ticket < 6 { "alice" } else { "bob" });
+    }
+    let paid: Uint128 = round.winners.iter().map(|w| w.amount).sum();
+    assert_eq!(sent_to(&res, "alice") + sent_to(&res, "bob"), paid);
+
+    owner(&mut deps, open(end_time() + 100, vec![10000])).unwrap();
+    let rounds: LotteryRoundsResponse = query_as(&deps, QueryMsg::LotteryRounds { count: 5 });
+    assert_eq!(rounds.list.iter().map(|r| r.id).collect::<Vec<_>>(), vec![2, 1]);
+}
+
+#[test]
+fn tiers_without_a_ticket_stay_with_the_house() {
+    let mut deps = setup();
+    owner(&mut deps, open(end_time(), vec![5000, 3000])).unwrap();
+    run(&mut deps, &mock_env(), PLAYER, PRICE, ExecuteMsg::BuyTickets { count: 1, from_balance: None }).unwrap();
+
+    let res = run(&mut deps, &env_at(100), PLAYER, 0, ExecuteMsg::DrawLottery { round_id: 1 }).unwrap();
+    let round: LotteryRound = query_as(&deps, QueryMsg::LotteryRound { round_id: 1 });
+    assert_eq!(round.winners.len(), 1);
+    assert_eq!(sent_to(&res, PLAYER), Uint128::new((PRICE - fee(PRICE)) / 2));
+}
+
+#[test]
+fn tickets_from_balance_win_into_it() {
+    let mut deps = setup();
+    owner(&mut deps, open(end_time(), vec![5000, 3000, 1000])).unwrap();
+    run(&mut deps, &mock_env(), "alice", 6 * PRICE, ExecuteMsg::Deposit {}).unwrap();
+
+    let buy = |count: u64, amount: u128| ExecuteMsg::BuyTickets { count, from_balance: Some(Uint128::new(amount)) };
+    let err = run(&mut deps, &mock_env(), "alice", 0, buy(6, 5 * PRICE)).unwrap_err();
+    assert_eq!(err, ContractError::TicketPaymentMismatch { expected: Uint128::new(6 * PRICE) });
+    assert_eq!(run(&mut deps, &mock_env(), "alice", PRICE, buy(1, PRICE)).unwrap_err(), ContractError::InvalidInput {});
+    run(&mut deps, &mock_env(), "alice", 0, buy(6, 6 * PRICE)).unwrap();
+    assert_eq!(balance(&deps, "alice"), Uint128::zero());
+    run(&mut deps, &mock_env(), "bob", 4 * PRICE, ExecuteMsg::BuyTickets { count: 4, from_balance: None }).unwrap();
+    assert_eq!(reserved(&deps), Uint128::new(10 * PRICE));
+
+    let res = run(&mut deps, &env_at(100), PLAYER, 0, ExecuteMsg::DrawLottery { round_id: 1 }).unwrap();
+    let round: LotteryRound = query_as(&deps, QueryMsg::LotteryRound { round_id: 1 });
+    let won = |address: &str| round.winners.iter().filter(|w| w.address.as_str() == address).map(|w| w.amount).sum::<Uint128>();
+    assert_eq!(sent_to(&res, "alice"), Uint128::zero());
+    assert_eq!(balance(&deps, "alice"), won("alice"));
+    assert_eq!(sent_to(&res, "bob"), won("bob"));
+    assert_eq!(balance(&deps, "bob"), Uint128::zero());
+}
@@ -0,0 +1,127 @@
+use cosmwasm_std::{Addr, Uint128};
+use crate::msg::{ExecuteMsg, LoyaltyResponse, QueryMsg, VipTier};
+use crate::constants::{LOYALTY_MAX_POINTS_RATE, TREASURY_ADDR};
+use crate::ContractError;
+use super::*;
+
+const STAKE: u128 = 1_000_000;
+
+fn tier(threshold: u128, rakeback_bps: u64) -> VipTier {
+    VipTier { threshold: Uint128::new(threshold), rakeback_bps }
+}
+
+fn update(points_rate: u64, min_redeem_points: u128, tiers: Vec<VipTier>) -> ExecuteMsg {
+    ExecuteMsg::UpdateLoyalty { points_rate, min_redeem_points: Uint128::new(min_redeem_points), tiers }
+}
+
+fn redeem(points: u128) -> ExecuteMsg {
+    ExecuteMsg::RedeemPoints { points: Uint128::new(points) }
+}
+
+fn loyalty(deps: &TestDeps, address: &str) -> LoyaltyResponse {
+    query_as(deps, QueryMsg::Loyalty { address: Addr::unchecked(address) })
+}
+
+fn flip(deps: &mut TestDeps, n: u64) -> cosmwasm_std::Response {
+    run(deps, &env_at(n), PLAYER, STAKE, ExecuteMsg::Flip { level: 0, from_balance: None }).unwrap()
+}
+
+#[test]
+fn updates_are_validated() {
+    let mut deps = setup();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, update(1000000, 0, vec![])).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let invalid = vec![
+        update(LOYALTY_MAX_POINTS_RATE + 1, 0, vec![]),
+        update(1000000, 0, (1..=11).map(|i| tier(i, 0)).collect()),
+        update(1000000, 0, vec![tier(1, 10001)]),
+        update(1000000, 0, vec![tier(10, 100), tier(10, 200)]),
+        update(1000000, 0, vec![tier(10, 200), tier(20, 100)]),
+    ];
+    for msg in invalid {
+        assert_eq!(owner(&mut deps, msg).unwrap_err(), ContractError::InvalidInput {});
+    }
+    owner(&mut deps, update(LOYALTY_MAX_POINTS_RATE, 0, vec![tier(10, 100), tier(20, 100)])).unwrap();
+}
+
+#[test]
+fn wagers_earn_points_and_tiered_rakeback() {
+    let mut deps = setup();
+    owner(&mut deps, update(2500000, 0, vec![tier(STAKE, 1000), tier(2 * STAKE, 5000)])).unwrap();
+
+    // The tier is the one reached before the bet
+    let mut rakeback = 0;
+    for (n, bps) in [0u128, 1000, 5000, 5000].iter().copied().enumerate() {
+        let res = flip(&mut deps, n as u64);
+        let accrued = fee(STAKE) * bps / 10000;
+        assert_eq!(sent_to(&res, TREASURY_ADDR), Uint128::new(fee(STAKE) - accrued));
+        rakeback += accrued;
+    }
+
+    let player = loyalty(&deps, PLAYER);
+    assert_eq!(player.wagered, Uint128::new(4 * STAKE));
+    assert_eq!(player.points, Uint128::new(10 * STAKE));
+    assert_eq!((player.tier, player.rakeback_bps), (2, 5000));
+    assert_eq!(player.claimable, Uint128::new(rakeback));
+    assert_eq!(reserved(&deps), Uint128::new(rakeback));
+}
+
+#[test]
+fn redemption_is_gated_by_points() {
+    let mut deps = setup();
+    owner(&mut deps, update(1000000, 3 * STAKE, vec![tier(STAKE, 1000)])).unwrap();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, redeem(2 * STAKE)).unwrap_err();
+    assert_eq!(err, ContractError::NotEnoughPoints { min: Uint128::new(3 * STAKE) });
+    for n in 0..2 {
+        flip(&mut deps, n);
+    }
+    // Only the points held can be spent
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, redeem(3 * STAKE)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    flip(&mut deps, 2);
+
+    let claimable = loyalty(&deps, PLAYER).claimable;
+    assert_eq!(claimable, Uint128::new(2 * fee(STAKE) / 10));
+    let res = run(&mut deps, &mock_env(), PLAYER, 0, redeem(3 * STAKE)).unwrap();
+    assert_eq!(sent_to(&res, PLAYER), claimable);
+    assert_eq!(attr(&res, "points"), (3 * STAKE).to_string());
+    assert_eq!(reserved(&deps), Uint128::zero());
+
+    let player = loyalty(&deps, PLAYER);
+    assert_eq!((player.points, player.claimable, player.redeemed), (Uint128::zero(), Uint128::zero(), claimable));
+}
+
+#[test]
+fn points_without_rakeback_have_nothing_to_redeem() {
+    let mut deps = setup();
+    owner(&mut deps, update(1000000, 0, vec![])).unwrap();
+    flip(&mut deps, 0);
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, redeem(STAKE)).unwrap_err();
+    assert_eq!(err, ContractError::NothingToClaim {});
+}
+
+#[test]
+fn points_redeem_their_share_of_the_rakeback() {
+    let mut deps = setup();
+    owner(&mut deps, update(1000000, 0, vec![tier(0, 1000)])).unwrap();
+    for n in 0..4 {
+        flip(&mut deps, n);
+    }
+    let rakeback = 4 * (fee(STAKE) / 10);
+    assert_eq!(loyalty(&deps, PLAYER).claimable, Uint128::new(rakeback));
+    assert_eq!(run(&mut deps, &mock_env(), PLAYER, 0, redeem(0)).unwrap_err(), ContractError::NotEnoughPoints { min: Uint128::zero() });
+
+    // A quarter of the points pays a quarter of the rakeback
+    let res = run(&mut deps, &mock_env(), PLAYER, 0, redeem(STAKE)).unwrap();
+    assert_eq!(sent_to(&res, PLAYER), Uint128::new(rakeback / 4));
+    let player = loyalty(&deps, PLAYER);
+    assert_eq!((player.points, player.claimable), (Uint128::new(3 * STAKE), Uint128::new(rakeback - rakeback / 4)));
+
+    let res = run(&mut deps, &mock_env(), PLAYER, 0, redeem(3 * STAKE)).unwrap();
+    assert_eq!(sent_to(&res, PLAYER), Uint128::new(rakeback - rakeback / 4));
+    assert_eq!(loyalty(&deps, PLAYER).redeemed, Uint128::new(rakeback));
+    assert_eq!(reserved(&deps), Uint128::zero());
+}
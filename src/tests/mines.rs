@@ -0,0 +1,207 @@
+use cosmwasm_std::{Addr, Uint128};
+use crate::msg::{ExecuteMsg, MHistory, MHistoryResponse, MinesGameResponse, QueryMsg};
+use crate::constants::MINES_TIMEOUT;
+use crate::state::MINES_GAMES;
+use crate::util;
+use crate::ContractError;
+use super::*;
+
+const STAKE: u128 = 10_000;
+
+fn start(grid: u8, mines: u8) -> ExecuteMsg {
+    ExecuteMsg::MinesStart { grid, mines, from_balance: None }
+}
+
+fn multiplier(grid: u8, mines: u8, safe: u32) -> u128 {
+    let (mut numerator, mut denominator) = (1000000u128, 1u128);
+    for i in 0..safe as u128 {
+        numerator *= grid as u128 - i;
+        denominator *= (grid - mines) as u128 - i;
+    }
+    numerator / denominator
+}
+
+fn game(deps: &TestDeps, address: &str) -> MinesGameResponse {
+    query_as(deps, QueryMsg::MinesGame { address: Addr::unchecked(address) })
+}
+
+fn last_record(deps: &TestDeps) -> MHistory {
+    let history: MHistoryResponse = query_as(deps, QueryMsg::MistoryMsg { count: 1 });
+    history.list[0].clone()
+}
+
+#[test]
+fn start_is_validated() {
+    let mut deps = setup();
+
+    for (grid, mines) in [(8, 1), (26, 1), (9, 0), (9, 9)] {
+        let err = run(&mut deps, &mock_env(), PLAYER, STAKE, start(grid, mines)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidBet {});
+    }
+    run(&mut deps, &mock_env(), PLAYER, STAKE, start(9, 1)).unwrap();
+    let err = run(&mut deps, &mock_env(), PLAYER, STAKE, start(9, 1)).unwrap_err();
+    assert_eq!(err, ContractError::GameInProgress {});
+}
+
+#[test]
+fn actions_are_validated() {
+    let mut deps = setup();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::MinesReveal { tile: 0 }).unwrap_err();
+    assert_eq!(err, ContractError::NoActiveGame {});
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::MinesCashout {}).unwrap_err();
+    assert_eq!(err, ContractError::NoActiveGame {});
+    let err = run(&mut deps, &mock_env(), OWNER, 0, ExecuteMsg::MinesTimeout { address: Addr::unchecked(PLAYER) }).unwrap_err();
+    assert_eq!(err, ContractError::NoActiveGame {});
+
+    run(&mut deps, &mock_env(), PLAYER, STAKE, start(25, 1)).unwrap();
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::MinesCashout {}).unwrap_err();
+    assert_eq!(err, ContractError::InvalidAction {});
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::MinesReveal { tile: 25 }).unwrap_err();
+    assert_eq!(err, ContractError::InvalidAction {});
+
+    let expires_at = mock_env().block.time.seconds() + MINES_TIMEOUT;
+    let err = run(&mut deps, &mock_env(), OWNER, 0, ExecuteMsg::MinesTimeout { address: Addr::unchecked(PLAYER) }).unwrap_err();
+    assert_eq!(err, ContractError::GameNotExpired { expires_at });
+    let err = run(&mut deps, &env_at(MINES_TIMEOUT), PLAYER, 0, ExecuteMsg::MinesReveal { tile: 0 }).unwrap_err();
+    assert_eq!(err, ContractError::GameExpired {});
+}
+
+#[test]
+fn open_games_keep_the_layout_hidden() {
+    let mut deps = setup();
+    let res = run(&mut deps, &mock_env(), PLAYER, STAKE, start(25, 3)).unwrap();
+
+    let open = game(&deps, PLAYER).game.unwrap();
+    assert_eq!(attr(&res, "commitment"), open.commitment.to_string());
+    assert_eq!(open.revealed, 0);
+    // Nothing in the stored game says where the mines are
+    let stored = cosmwasm_std::to_json_string(&open).unwrap();
+    assert!(!stored.contains("layout") && !stored.contains("seed"));
+}
+
+#[test]
+fn settled_games_open_their_commitment() {
+    let mut deps = setup();
+
+    for n in 0..10u64 {
+        let player = format!("player{}", n);
+        let res = run(&mut deps, &env_at(n), &player, STAKE, start(25, 5)).unwrap();
+        let commitment = attr(&res, "commitment").to_string();
+        // Revealing in order until a mine shows the layout was there from the start
+        let mut tile = 0;
+        while game(&deps, &player).game.is_some() {
+            run(&mut deps, &env_at(n), &player, 0, ExecuteMsg::MinesReveal { tile }).unwrap();
+            tile += 1;
+        }
+
+        let record = last_record(&deps);
+        assert_eq!(util::calculate_hash(&(record.seed, record.layout)).to_string(), commitment);
+        assert_eq!(record.layout.count_ones(), 5);
+        assert_eq!(record.layout.trailing_zeros(), tile as u32 - 1);
+        assert_eq!(record.revealed, (1u64 << (tile - 1)) - 1);
+    }
+}
+
+#[test]
+fn a_tampered_commitment_cannot_settle() {
+    let mut deps = setup();
+    run(&mut deps, &mock_env(), PLAYER, STAKE, start(25, 1)).unwrap();
+
+    let mut open = game(&deps, PLAYER).game.unwrap();
+    open.commitment ^= 1;
+    MINES_GAMES.save(deps.as_mut().storage, &Addr::unchecked(PLAYER), &open).unwrap();
+    let err = run(&mut deps, &env_at(MINES_TIMEOUT), OWNER, 0, ExecuteMsg::MinesTimeout { address: Addr::unchecked(PLAYER) }).unwrap_err();
+    assert_eq!(err, ContractError::CommitmentMismatch {});
+}
+
+#[test]
+fn cashout_pays_the_multiplier_and_releases_the_reservation() {
+    let mut deps = setup();
+
+    let mut cashed = 0;
+    for n in 0..40u64 {
+        let player = format!("player{}", n);
+        run(&mut deps, &env_at(n), &player, STAKE, start(25, 3)).unwrap();
+        assert_eq!(reserved(&deps), Uint128::new(STAKE * multiplier(25, 3, 22) / 1000000));
+
+        let mut tile = 0;
+        let res = loop {
+            let res = run(&mut deps, &env_at(n + tile as u64), &player, 0, ExecuteMsg::MinesReveal { tile }).unwrap();
+            if game(&deps, &player).game.is_none() {
+                break res;
+            }
+            tile += 1;
+            assert_eq!(game(&deps, &player).multiplier as u128, multiplier(25, 3, tile as u32));
+            if tile == 3 {
+                cashed += 1;
+                break run(&mut deps, &env_at(n), &player, 0, ExecuteMsg::MinesCashout {}).unwrap();
+            }
+        };
+        assert_eq!(reserved(&deps), Uint128::zero());
+
+        let record = last_record(&deps);
+        assert_eq!(record.layout.count_ones(), 3);
+        assert_eq!(record.layout & record.revealed, 0);
+        let expected = if attr(&res, "multiplier") == "0" {
+            // Busted on the tile after the revealed ones
+            assert_ne!(record.layout & (1u64 << tile), 0);
+            assert_eq!(record.win, Some(1));
+            0
+        } else {
+            let mult = multiplier(25, 3, record.revealed.count_ones());
+            assert_eq!(record.multiplier as u128, mult);
+            assert_eq!(record.win, Some(0));
+            (STAKE * mult / 1000000).saturating_sub(fee(STAKE))
+        };
+        assert_eq!(record.payout, Uint128::new(expected));
+        assert_eq!(sent_to(&res, &player), Uint128::new(expected));
+    }
+    assert!(cashed > 0 && cashed < 40);
+}
+
+#[test]
+fn clearing_every_safe_tile_settles() {
+    let mut deps = setup();
+
+    for n in 0..200u64 {
+        let player = format!("player{}", n);
+        run(&mut deps, &env_at(n), &player, STAKE, start(9, 1)).unwrap();
+        let mut res = None;
+        for tile in 0..9u8 {
+            res = Some(run(&mut deps, &env_at(n), &player, 0, ExecuteMsg::MinesReveal { tile }).unwrap());
+            if game(&deps, &player).game.is_none() {
+                break;
+            }
+        }
+        let record = last_record(&deps);
+        if record.revealed.count_ones() == 8 {
+            assert_eq!(record.multiplier, 9000000);
+            assert_eq!(record.layout, !record.revealed & 0x1ff);
+            assert_eq!(sent_to(&res.unwrap(), &player), Uint128::new(9 * STAKE - fee(STAKE)));
+            return;
+        }
+    }
+    panic!("no game cleared the grid");
+}
+
+#[test]
+fn expired_games_are_cashed_out() {
+    let mut deps = setup();
+
+    for n in 0..20u64 {
+        let player = format!("player{}", n);
+        run(&mut deps, &env_at(n), &player, STAKE, start(25, 1)).unwrap();
+        run(&mut deps, &env_at(n), &player, 0, ExecuteMsg::MinesReveal { tile: 0 }).unwrap();
+        if game(&deps, &player).game.is_none() {
+            continue;
+        }
+        let res = run(&mut deps, &env_at(n + MINES_TIMEOUT), OWNER, 0, ExecuteMsg::MinesTimeout { address: Addr::unchecked(&player) }).unwrap();
+        assert!(game(&deps, &player).game.is_none());
+        assert_eq!(reserved(&deps), Uint128::zero());
+        let expected = (STAKE * multiplier(25, 1, 1) / 1000000).saturating_sub(fee(STAKE));
+        assert_eq!(sent_to(&res, &player), Uint128::new(expected));
+        return;
+    }
+    panic!("every first reveal busted");
+}
@@ -0,0 +1,105 @@
+use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{coins, from_json, BankMsg, CosmosMsg, Env, OwnedDeps, Response, Uint128};
+use serde::de::DeserializeOwned;
+use crate::contract::{execute, instantiate, query};
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::ContractError;
+
+mod plinko;
+mod keno;
+mod jackpot;
+mod lottery;
+mod blackjack;
+mod slots;
+mod mines;
+mod hilo;
+mod wheel;
+mod ledger;
+mod batch;
+mod roulette;
+mod auto_bet;
+mod referral;
+mod loyalty;
+mod voucher;
+mod limits;
+mod screening;
+mod rate_limit;
+mod breaker;
+mod payout_cap;
+mod stats;
+mod leaderboard;
+mod history;
+
+pub const DENOM: &str = "ujuno";
+pub const OWNER: &str = "owner";
+pub const PLAYER: &str = "player";
+pub const BANKROLL: u128 = 1_000_000_000;
+
+pub type TestDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+/// Instantiated contract holding `BANKROLL`, owned by `OWNER`
+pub fn setup() -> TestDeps {
+    let mut deps = mock_dependencies_with_balance(&coins(BANKROLL, DENOM));
+    instantiate(deps.as_mut(), mock_env(), mock_info(OWNER, &coins(1, DENOM)), InstantiateMsg {}).unwrap();
+    deps
+}
+
+pub fn set_bankroll(deps: &mut TestDeps, amount: u128) {
+    deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(amount, DENOM));
+}
+
+/// Block `n` blocks and seconds after the default mock block
+pub fn env_at(n: u64) -> Env {
+    let mut env = mock_env();
+    env.block.height += n;
+    env.block.time = env.block.time.plus_seconds(n);
+    env
+}
+
+pub fn run(deps: &mut TestDeps, env: &Env, sender: &str, funds: u128, msg: ExecuteMsg) -> Result<Response, ContractError> {
+    let funds = if funds == 0 { vec![] } else { coins(funds, DENOM) };
+    execute(deps.as_mut(), env.clone(), mock_info(sender, &funds), msg)
+}
+
+pub fn owner(deps: &mut TestDeps, msg: ExecuteMsg) -> Result<Response, ContractError> {
+    run(deps, &mock_env(), OWNER, 0, msg)
+}
+
+pub fn query_at<T: DeserializeOwned>(deps: &TestDeps, env: &Env, msg: QueryMsg) -> T {
+    from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap()
+}
+
+pub fn query_as<T: DeserializeOwned>(deps: &TestDeps, msg: QueryMsg) -> T {
+    query_at(deps, &mock_env(), msg)
+}
+
+/// Total of the transfers to `address` in a response
+pub fn sent_to(res: &Response, address: &str) -> Uint128 {
+    res.messages.iter()
+        .filter_map(|m| match &m.msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) if to_address == address => {
+                Some(amount.iter().filter(|c| c.denom == DENOM).map(|c| c.amount).sum::<Uint128>())
+            },
+            _ => None
+        })
+        .sum()
+}
+
+pub fn attr<'a>(res: &'a Response, key: &str) -> &'a str {
+    &res.attributes.iter().find(|a| a.key == key).unwrap_or_else(|| panic!("no attribute {}", key)).value
+}
+
+/// Fee the house keeps on a stake
+pub fn fee(amount: u128) -> u128 {
+    amount * crate::constants::OWNER_RATE as u128 / crate::constants::MULTIPLY as u128
+}
+
+/// Bankroll currently set aside for open games and rounds
+pub fn reserved(deps: &TestDeps) -> Uint128 {
+    crate::state::RESERVED.may_load(&deps.storage).unwrap().unwrap_or_default()
+}
+
+pub fn balance(deps: &TestDeps, address: &str) -> Uint128 {
+    let res: crate::msg::BalanceResponse = query_as(deps, crate::msg::QueryMsg::Balance { address: cosmwasm_std::Addr::unchecked(address) });
+    res.balance
+}
@@ -0,0 +1,128 @@
+use cosmwasm_std::{Addr, Uint128};
+use crate::msg::{ExecuteMsg, PayoutAllowanceResponse, PayoutCaps, QueryMsg};
+use crate::constants::DAY;
+use crate::ContractError;
+use super::*;
+
+const STAKE: u128 = 10_000;
+
+fn caps(player_daily: Option<u128>, global_daily: Option<u128>) -> ExecuteMsg {
+    ExecuteMsg::UpdatePayoutCaps {
+        caps: PayoutCaps { player_daily: player_daily.map(Uint128::new), global_daily: global_daily.map(Uint128::new) }
+    }
+}
+
+fn flip(deps: &mut TestDeps, sender: &str, n: u64) -> Result<Response, ContractError> {
+    run(deps, &env_at(n), sender, STAKE, ExecuteMsg::Flip { level: 0, from_balance: None })
+}
+
+fn allowance(deps: &TestDeps, address: &str, n: u64) -> PayoutAllowanceResponse {
+    query_at(deps, &env_at(n), QueryMsg::PayoutAllowance { address: Addr::unchecked(address) })
+}
+
+fn flip_reward() -> u128 {
+    2 * STAKE - fee(STAKE)
+}
+
+#[test]
+fn only_the_owner_sets_caps() {
+    let mut deps = setup();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, caps(Some(1), None)).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    owner(&mut deps, caps(Some(1), Some(2))).unwrap();
+    let stored: PayoutCaps = query_as(&deps, QueryMsg::PayoutCaps {});
+    assert_eq!(stored, PayoutCaps { player_daily: Some(Uint128::new(1)), global_daily: Some(Uint128::new(2)) });
+}
+
+#[test]
+fn the_player_cap_holds_for_the_day() {
+    let mut deps = setup();
+    owner(&mut deps, caps(Some(2 * flip_reward()), None)).unwrap();
+
+    let mut wins = 0;
+    let mut n = 0;
+    while wins < 2 {
+        let res = flip(&mut deps, PLAYER, n).unwrap();
+        if attr(&res, "win") == "0" {
+            wins += 1;
+        }
+        n += 1;
+    }
+
+    let err = flip(&mut deps, PLAYER, n).unwrap_err();
+    assert_eq!(err, ContractError::PlayerPayoutCapExceeded { remaining: Uint128::zero() });
+    let state = allowance(&deps, PLAYER, n);
+    assert_eq!(state.player_paid, Uint128::new(2 * flip_reward()));
+    assert_eq!(state.player_remaining, Some(Uint128::zero()));
+    assert_eq!((state.global_paid, state.global_remaining), (state.player_paid, None));
+    let now = env_at(n).block.time.seconds();
+    assert_eq!(state.resets_at, (now / DAY + 1) * DAY);
+
+    // Other players keep their own allowance
+    flip(&mut deps, "other", n).unwrap();
+
+    // Totals start over the next day
+    let later = state.resets_at - mock_env().block.time.seconds();
+    assert_eq!(allowance(&deps, PLAYER, later).player_paid, Uint128::zero());
+    flip(&mut deps, PLAYER, later).unwrap();
+}
+
+#[test]
+fn the_global_cap_holds_for_everyone() {
+    let mut deps = setup();
+    owner(&mut deps, caps(None, Some(flip_reward()))).unwrap();
+
+    for n in 0..20u64 {
+        let res = flip(&mut deps, &format!("player{}", n), n).unwrap();
+        if attr(&res, "win") == "0" {
+            let err = flip(&mut deps, PLAYER, n).unwrap_err();
+            assert_eq!(err, ContractError::GlobalPayoutCapExceeded { remaining: Uint128::zero() });
+            assert_eq!(allowance(&deps, PLAYER, n).global_remaining, Some(Uint128::zero()));
+            return;
+        }
+    }
+    panic!("no flip was won");
+}
+
+#[test]
+fn open_games_count_toward_the_caps() {
+    let mut deps = setup();
+    let max_reward = 25 * STAKE;
+    let start = ExecuteMsg::MinesStart { grid: 25, mines: 1, from_balance: None };
+
+    owner(&mut deps, caps(Some(max_reward - 1), None)).unwrap();
+    let err = run(&mut deps, &mock_env(), PLAYER, STAKE, start.clone()).unwrap_err();
+    assert_eq!(err, ContractError::PlayerPayoutCapExceeded { remaining: Uint128::new(max_reward - 1) });
+
+    owner(&mut deps, caps(Some(max_reward + flip_reward() - 1), None)).unwrap();
+    for n in 0..20u64 {
+        let player = format!("player{}", n);
+        run(&mut deps, &env_at(n), &player, STAKE, start.clone()).unwrap();
+        let state = allowance(&deps, &player, n);
+        assert_eq!((state.player_pending, state.global_pending), (Uint128::new(max_reward), Uint128::new(max_reward)));
+        assert_eq!(state.player_remaining, Some(Uint128::new(flip_reward() - 1)));
+
+        let err = flip(&mut deps, &player, n).unwrap_err();
+        assert_eq!(err, ContractError::PlayerPayoutCapExceeded { remaining: Uint128::new(flip_reward() - 1) });
+
+        run(&mut deps, &env_at(n), &player, 0, ExecuteMsg::MinesReveal { tile: 0 }).unwrap();
+        let res = match run(&mut deps, &env_at(n), &player, 0, ExecuteMsg::MinesCashout {}) {
+            Ok(res) => res,
+            // Busted on the first tile, which settles and holds nothing either
+            Err(ContractError::NoActiveGame {}) => {
+                let state = allowance(&deps, &player, n);
+                assert_eq!((state.player_pending, state.global_pending), (Uint128::zero(), Uint128::zero()));
+                continue;
+            }
+            Err(err) => panic!("{}", err)
+        };
+
+        let paid = sent_to(&res, &player);
+        let state = allowance(&deps, &player, n);
+        assert_eq!((state.player_pending, state.global_pending), (Uint128::zero(), Uint128::zero()));
+        assert_eq!((state.player_paid, state.global_paid), (paid, paid));
+        return;
+    }
+    panic!("every first reveal busted");
+}
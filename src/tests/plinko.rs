@@ -0,0 +1,79 @@
+use cosmwasm_std::Uint128;
+use crate::msg::{ExecuteMsg, PlinkoRisk, PlinkoTableResponse, QueryMsg};
+use crate::util;
+use crate::ContractError;
+use super::*;
+
+const TABLE: [u64; 9] = [5600000, 2100000, 1100000, 1000000, 500000, 1000000, 1100000, 2100000, 5600000];
+
+fn update(multipliers: Vec<u64>, rows: u8) -> ExecuteMsg {
+    ExecuteMsg::UpdatePlinkoTable { rows, risk: PlinkoRisk::Low, multipliers }
+}
+
+fn plinko(rows: u8) -> ExecuteMsg {
+    ExecuteMsg::Plinko { rows, risk: PlinkoRisk::Low, from_balance: None }
+}
+
+#[test]
+fn table_updates_are_validated() {
+    let mut deps = setup();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, update(TABLE.to_vec(), 8)).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = owner(&mut deps, update(TABLE[1..].to_vec(), 8)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    let err = owner(&mut deps, update(vec![1000000; 8], 7)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    let err = owner(&mut deps, update(vec![1100000; 9], 8)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidRtp { rtp: 1100000 });
+
+    owner(&mut deps, update(TABLE.to_vec(), 8)).unwrap();
+    let table: PlinkoTableResponse = query_as(&deps, QueryMsg::PlinkoTable { rows: 8, risk: PlinkoRisk::Low });
+    assert_eq!(table.multipliers, TABLE.to_vec());
+    assert_eq!(table.rtp, util::plinko_rtp(&TABLE));
+    assert!(table.rtp < 1000000);
+}
+
+#[test]
+fn bets_need_a_table_and_valid_rows() {
+    let mut deps = setup();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 1000, plinko(8)).unwrap_err();
+    assert_eq!(err, ContractError::PlinkoTableNotSet { rows: 8 });
+    let err = run(&mut deps, &mock_env(), PLAYER, 1000, plinko(17)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidBet {});
+}
+
+#[test]
+fn payout_follows_the_slot_multiplier() {
+    let mut deps = setup();
+    owner(&mut deps, update(TABLE.to_vec(), 8)).unwrap();
+
+    let amount = 1_000_000u128;
+    let (mut wins, mut losses) = (0, 0);
+    for n in 0..40 {
+        let res = run(&mut deps, &env_at(n), PLAYER, amount, plinko(8)).unwrap();
+        let slot: usize = attr(&res, "slot").parse().unwrap();
+        let multiplier = TABLE[slot] as u128;
+        assert_eq!(attr(&res, "multiplier"), multiplier.to_string());
+
+        let expected = (amount * multiplier / 1000000).saturating_sub(fee(amount));
+        assert_eq!(sent_to(&res, PLAYER), Uint128::new(expected));
+        assert_eq!(sent_to(&res, crate::constants::TREASURY_ADDR), Uint128::new(fee(amount)));
+        match attr(&res, "win") {
+            "0" => { assert!(multiplier > 1000000); wins += 1 },
+            _ => { assert!(multiplier <= 1000000); losses += 1 }
+        }
+    }
+    assert!(wins > 0 && losses > 0);
+}
+
+#[test]
+fn bankroll_must_cover_the_top_slot() {
+    let mut deps = setup();
+    owner(&mut deps, update(TABLE.to_vec(), 8)).unwrap();
+    set_bankroll(&mut deps, 5_000_000);
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 1_000_000, plinko(8)).unwrap_err();
+    assert_eq!(err, ContractError::InsufficientFunds {});
+}
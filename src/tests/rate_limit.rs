@@ -0,0 +1,98 @@
+use cosmwasm_std::{from_json, Addr, Uint128};
+use crate::msg::{AutoBetResponse, AutoBetStop, AutoStrategy, BatchGame, ExecuteMsg, QueryMsg, RateLimits, RateUsageResponse};
+use crate::ContractError;
+use super::*;
+
+const STAKE: u128 = 10_000;
+
+fn limits(per_block: u32, per_window: u32, window: u64, global_per_block: u32) -> ExecuteMsg {
+    ExecuteMsg::UpdateRateLimits { limits: RateLimits { per_block, per_window, window, global_per_block } }
+}
+
+fn flip(deps: &mut TestDeps, env: &cosmwasm_std::Env, sender: &str) -> Result<cosmwasm_std::Response, ContractError> {
+    run(deps, env, sender, STAKE, ExecuteMsg::Flip { level: 0, from_balance: None })
+}
+
+fn batch(deps: &mut TestDeps, env: &cosmwasm_std::Env, rounds: u32) -> Result<cosmwasm_std::Response, ContractError> {
+    let msg = ExecuteMsg::BatchBet { game: BatchGame::Flip, selection: 0, rounds, stake_per_round: Uint128::new(STAKE), from_balance: None };
+    run(deps, env, PLAYER, STAKE * rounds as u128, msg)
+}
+
+fn usage(deps: &TestDeps, env: &cosmwasm_std::Env, address: &str) -> RateUsageResponse {
+    query_at(deps, env, QueryMsg::RateUsage { address: Addr::unchecked(address) })
+}
+
+#[test]
+fn updates_are_validated() {
+    let mut deps = setup();
+    assert_eq!(run(&mut deps, &mock_env(), PLAYER, 0, limits(1, 0, 0, 0)).unwrap_err(), ContractError::Unauthorized {});
+    assert_eq!(owner(&mut deps, limits(0, 5, 0, 0)).unwrap_err(), ContractError::InvalidInput {});
+}
+
+#[test]
+fn bets_are_capped_per_block_window_and_globally() {
+    let mut deps = setup();
+    owner(&mut deps, limits(2, 3, 60, 4)).unwrap();
+
+    flip(&mut deps, &mock_env(), PLAYER).unwrap();
+    flip(&mut deps, &mock_env(), PLAYER).unwrap();
+    assert_eq!(flip(&mut deps, &mock_env(), PLAYER).unwrap_err(), ContractError::BlockRateLimited {});
+
+    flip(&mut deps, &mock_env(), "other").unwrap();
+    flip(&mut deps, &mock_env(), "other").unwrap();
+    assert_eq!(flip(&mut deps, &mock_env(), "third").unwrap_err(), ContractError::GlobalRateLimited {});
+
+    flip(&mut deps, &env_at(1), PLAYER).unwrap();
+    let until = mock_env().block.time.seconds() + 60;
+    assert_eq!(flip(&mut deps, &env_at(2), PLAYER).unwrap_err(), ContractError::AddressRateLimited { until });
+    flip(&mut deps, &env_at(60), PLAYER).unwrap();
+}
+
+#[test]
+fn batches_count_every_round() {
+    let mut deps = setup();
+    owner(&mut deps, limits(5, 0, 0, 0)).unwrap();
+
+    assert_eq!(batch(&mut deps, &mock_env(), 6).unwrap_err(), ContractError::BlockRateLimited {});
+    assert_eq!(usage(&deps, &mock_env(), PLAYER).usage.block_count, 0);
+
+    batch(&mut deps, &mock_env(), 4).unwrap();
+    assert_eq!(usage(&deps, &mock_env(), PLAYER).usage.block_count, 4);
+    assert_eq!(batch(&mut deps, &mock_env(), 2).unwrap_err(), ContractError::BlockRateLimited {});
+    batch(&mut deps, &mock_env(), 1).unwrap();
+}
+
+#[test]
+fn auto_bets_stop_at_the_rate_limit() {
+    let mut deps = setup();
+    owner(&mut deps, limits(3, 0, 0, 0)).unwrap();
+
+    let msg = ExecuteMsg::AutoBet {
+        game: BatchGame::Flip, selection: 0, strategy: AutoStrategy::Flat, base_stake: Uint128::new(STAKE),
+        rounds: 10, stop_loss: None, take_profit: None, from_balance: None
+    };
+    let res = run(&mut deps, &mock_env(), PLAYER, 10 * STAKE, msg).unwrap();
+    let data: AutoBetResponse = from_json(res.data.as_ref().unwrap()).unwrap();
+    assert_eq!(data.rounds_played, 3);
+    assert_eq!(data.stop, AutoBetStop::Limit);
+    assert_eq!(usage(&deps, &mock_env(), PLAYER).usage.block_count, 3);
+}
+
+#[test]
+fn idle_usage_is_pruned_in_chunks() {
+    let mut deps = setup();
+    owner(&mut deps, limits(0, 10, 60, 0)).unwrap();
+    for player in ["a", "b", "c"] {
+        flip(&mut deps, &mock_env(), player).unwrap();
+    }
+    flip(&mut deps, &env_at(30), "d").unwrap();
+
+    let prune = |start_after: Option<&str>| ExecuteMsg::PruneRateUsage { start_after: start_after.map(Addr::unchecked), limit: Some(2) };
+    let res = run(&mut deps, &env_at(60), PLAYER, 0, prune(None)).unwrap();
+    assert_eq!((attr(&res, "scanned"), attr(&res, "pruned"), attr(&res, "last")), ("2", "2", "b"));
+    let res = run(&mut deps, &env_at(60), PLAYER, 0, prune(Some("b"))).unwrap();
+    // d is still inside its window
+    assert_eq!((attr(&res, "scanned"), attr(&res, "pruned"), attr(&res, "last")), ("2", "1", "d"));
+
+    assert_eq!(crate::state::RATE_USAGE.keys(&deps.storage, None, None, cosmwasm_std::Order::Ascending).count(), 1);
+}
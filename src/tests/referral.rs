@@ -0,0 +1,84 @@
+use cosmwasm_std::{Addr, Uint128};
+use crate::msg::{ExecuteMsg, QueryMsg, ReferralsResponse, ReferrerEarnings, ReferrerResponse, TopReferrersResponse};
+use crate::constants::TREASURY_ADDR;
+use crate::ContractError;
+use super::*;
+
+const STAKE: u128 = 1_000_000;
+
+fn set_referrer(deps: &mut TestDeps, player: &str, referrer: &str) -> Result<cosmwasm_std::Response, ContractError> {
+    run(deps, &mock_env(), player, 0, ExecuteMsg::SetReferrer { referrer: Addr::unchecked(referrer) })
+}
+
+fn earnings(deps: &TestDeps, address: &str) -> ReferrerEarnings {
+    query_as(deps, QueryMsg::ReferrerEarnings { address: Addr::unchecked(address) })
+}
+
+#[test]
+fn referrers_are_set_once() {
+    let mut deps = setup();
+
+    assert_eq!(set_referrer(&mut deps, "alice", "alice").unwrap_err(), ContractError::InvalidReferrer {});
+    set_referrer(&mut deps, "alice", "bob").unwrap();
+    assert_eq!(set_referrer(&mut deps, "alice", "carol").unwrap_err(), ContractError::ReferrerAlreadySet {});
+    assert_eq!(set_referrer(&mut deps, "bob", "alice").unwrap_err(), ContractError::InvalidReferrer {});
+
+    let referrer: ReferrerResponse = query_as(&deps, QueryMsg::Referrer { address: Addr::unchecked("alice") });
+    assert_eq!(referrer.referrer, Some(Addr::unchecked("bob")));
+    assert_eq!(earnings(&deps, "bob").referrals, 1);
+}
+
+#[test]
+fn referrals_are_paged() {
+    let mut deps = setup();
+    for player in ["dave", "carol", "alice"] {
+        set_referrer(&mut deps, player, "bob").unwrap();
+    }
+
+    let page = |start_after: Option<&str>| -> Vec<String> {
+        let res: ReferralsResponse = query_as(&deps, QueryMsg::Referrals {
+            referrer: Addr::unchecked("bob"),
+            start_after: start_after.map(Addr::unchecked),
+            limit: Some(2)
+        });
+        res.list.into_iter().map(|a| a.to_string()).collect()
+    };
+    assert_eq!(page(None), vec!["alice", "carol"]);
+    assert_eq!(page(Some("carol")), vec!["dave"]);
+}
+
+#[test]
+fn commissions_accrue_and_are_claimed() {
+    let mut deps = setup();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::UpdateReferralShare { share_bps: 2000 }).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = owner(&mut deps, ExecuteMsg::UpdateReferralShare { share_bps: 10001 }).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    owner(&mut deps, ExecuteMsg::UpdateReferralShare { share_bps: 2000 }).unwrap();
+
+    set_referrer(&mut deps, "alice", "bob").unwrap();
+    set_referrer(&mut deps, "carol", "dave").unwrap();
+
+    let commission = fee(STAKE) * 2000 / 10000;
+    for n in 0..3 {
+        let res = run(&mut deps, &env_at(n), "alice", STAKE, ExecuteMsg::Flip { level: 0, from_balance: None }).unwrap();
+        assert_eq!(sent_to(&res, TREASURY_ADDR), Uint128::new(fee(STAKE) - commission));
+    }
+    run(&mut deps, &mock_env(), "carol", STAKE, ExecuteMsg::Flip { level: 0, from_balance: None }).unwrap();
+
+    assert_eq!(earnings(&deps, "bob").earned, Uint128::new(3 * commission));
+    assert_eq!(reserved(&deps), Uint128::new(4 * commission));
+
+    let top: TopReferrersResponse = query_as(&deps, QueryMsg::TopReferrers { limit: None });
+    assert_eq!(top.list.iter().map(|t| (t.address.as_str(), t.earned.u128())).collect::<Vec<_>>(),
+        vec![("bob", 3 * commission), ("dave", commission)]);
+
+    let res = run(&mut deps, &mock_env(), "bob", 0, ExecuteMsg::ClaimReferral {}).unwrap();
+    assert_eq!(sent_to(&res, "bob"), Uint128::new(3 * commission));
+    assert_eq!(earnings(&deps, "bob").claimed, Uint128::new(3 * commission));
+    assert_eq!(reserved(&deps), Uint128::new(commission));
+
+    let err = run(&mut deps, &mock_env(), "bob", 0, ExecuteMsg::ClaimReferral {}).unwrap_err();
+    assert_eq!(err, ContractError::NothingToClaim {});
+}
@@ -0,0 +1,43 @@
+use cosmwasm_std::Uint128;
+use crate::msg::ExecuteMsg;
+use crate::util;
+use super::*;
+
+const STAKE: u128 = 10_000;
+
+#[test]
+fn columns_pay_as_they_always_did() {
+    // The first two columns keep the wrapping checks of the original if-chain
+    assert!(util::roulette_hit(37, 0));
+    assert!(util::roulette_hit(38, 1));
+    assert!(!util::roulette_hit(38, 0));
+    assert!(util::roulette_hit(37, 1) && util::roulette_hit(37, 34));
+    assert!(util::roulette_hit(38, 2) && util::roulette_hit(38, 35));
+    assert!(util::roulette_hit(39, 0) && util::roulette_hit(39, 36));
+    assert!(!util::roulette_hit(39, 1));
+}
+
+#[test]
+fn other_bets_cover_their_numbers() {
+    for number in 0..37u64 {
+        assert!(util::roulette_hit(number, number));
+        assert!(!util::roulette_hit((number + 1) % 37, number));
+        assert_eq!(util::roulette_hit(40, number), (1..=12).contains(&number));
+        assert_eq!(util::roulette_hit(43, number), (1..=18).contains(&number));
+        assert_eq!(util::roulette_hit(45, number), number % 2 == 0);
+        assert_eq!(util::roulette_hit(47, number) || util::roulette_hit(48, number), number != 0);
+    }
+}
+
+#[test]
+fn spins_pay_the_hits() {
+    let mut deps = setup();
+
+    for n in 0..40u64 {
+        let res = run(&mut deps, &env_at(n), PLAYER, STAKE, ExecuteMsg::Roulette { level: 45, from_balance: None }).unwrap();
+        // The attribute holds the number drawn
+        let number: u64 = attr(&res, "win").parse().unwrap();
+        let expected = if number % 2 == 0 { 2 * STAKE - fee(STAKE) } else { 0 };
+        assert_eq!(sent_to(&res, PLAYER), Uint128::new(expected + attr(&res, "jackpot").parse::<u128>().unwrap()));
+    }
+}
@@ -0,0 +1,126 @@
+use cosmwasm_std::{from_json, to_json_binary, Addr, ContractResult, SystemResult, Uint128, WasmQuery};
+use crate::msg::{BlockedResponse, BlocklistResponse, ExecuteMsg, LotteryRound, MinesGameResponse, QueryMsg, ScreeningQueryMsg, ScreeningResponse};
+use crate::constants::MINES_TIMEOUT;
+use crate::ContractError;
+use super::*;
+
+const STAKE: u128 = 10_000;
+
+fn block(deps: &mut TestDeps, address: &str) {
+    owner(deps, ExecuteMsg::UpdateBlocklist { add: vec![Addr::unchecked(address)], remove: vec![] }).unwrap();
+}
+
+fn blocked(address: &str) -> ContractError {
+    ContractError::AddressBlocked { address: address.to_string() }
+}
+
+#[test]
+fn blocklist_is_owner_managed_and_paged() {
+    let mut deps = setup();
+
+    let msg = ExecuteMsg::UpdateBlocklist { add: vec![Addr::unchecked("eve")], remove: vec![] };
+    assert_eq!(run(&mut deps, &mock_env(), PLAYER, 0, msg).unwrap_err(), ContractError::Unauthorized {});
+    for address in ["eve", "carl", "dan"] {
+        block(&mut deps, address);
+    }
+    owner(&mut deps, ExecuteMsg::UpdateBlocklist { add: vec![], remove: vec![Addr::unchecked("dan")] }).unwrap();
+
+    let page: BlocklistResponse = query_as(&deps, QueryMsg::Blocklist { start_after: None, limit: Some(1) });
+    assert_eq!(page.list, vec![Addr::unchecked("carl")]);
+    let page: BlocklistResponse = query_as(&deps, QueryMsg::Blocklist { start_after: Some(Addr::unchecked("carl")), limit: None });
+    assert_eq!(page.list, vec![Addr::unchecked("eve")]);
+}
+
+#[test]
+fn blocked_senders_cannot_bet_or_deposit() {
+    let mut deps = setup();
+    block(&mut deps, PLAYER);
+
+    let err = run(&mut deps, &mock_env(), PLAYER, STAKE, ExecuteMsg::Flip { level: 0, from_balance: None }).unwrap_err();
+    assert_eq!(err, blocked(PLAYER));
+    let err = run(&mut deps, &mock_env(), PLAYER, STAKE, ExecuteMsg::MinesStart { grid: 9, mines: 1, from_balance: None }).unwrap_err();
+    assert_eq!(err, blocked(PLAYER));
+    let err = run(&mut deps, &mock_env(), PLAYER, STAKE, ExecuteMsg::Deposit {}).unwrap_err();
+    assert_eq!(err, blocked(PLAYER));
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::WithdrawBalance { amount: Uint128::new(1) }).unwrap_err();
+    assert_eq!(err, blocked(PLAYER));
+}
+
+#[test]
+fn screening_contract_is_queried() {
+    let mut deps = setup();
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { contract_addr, msg } if contract_addr == "screener" => {
+            let ScreeningQueryMsg::IsBlocked { address } = from_json(msg).unwrap();
+            SystemResult::Ok(ContractResult::Ok(to_json_binary(&ScreeningResponse { blocked: address == "flagged" }).unwrap()))
+        },
+        _ => panic!("unexpected query")
+    });
+
+    let msg = ExecuteMsg::UpdateScreening { contract: Some(Addr::unchecked("screener")) };
+    assert_eq!(run(&mut deps, &mock_env(), PLAYER, 0, msg.clone()).unwrap_err(), ContractError::Unauthorized {});
+    owner(&mut deps, msg).unwrap();
+
+    let res: BlockedResponse = query_as(&deps, QueryMsg::IsBlocked { address: Addr::unchecked("flagged") });
+    assert!(res.blocked);
+    let err = run(&mut deps, &mock_env(), "flagged", STAKE, ExecuteMsg::Flip { level: 0, from_balance: None }).unwrap_err();
+    assert_eq!(err, blocked("flagged"));
+    run(&mut deps, &mock_env(), PLAYER, STAKE, ExecuteMsg::Flip { level: 0, from_balance: None }).unwrap();
+
+    owner(&mut deps, ExecuteMsg::UpdateScreening { contract: None }).unwrap();
+    run(&mut deps, &mock_env(), "flagged", STAKE, ExecuteMsg::Flip { level: 0, from_balance: None }).unwrap();
+}
+
+#[test]
+fn lottery_draw_freezes_a_blocked_winners_prize() {
+    let mut deps = setup();
+    let end_time = mock_env().block.time.seconds() + 100;
+    owner(&mut deps, ExecuteMsg::OpenLottery { ticket_price: Uint128::new(STAKE), end_time, prize_tiers: vec![10000] }).unwrap();
+    run(&mut deps, &mock_env(), PLAYER, STAKE, ExecuteMsg::BuyTickets { count: 1, from_balance: None }).unwrap();
+    block(&mut deps, PLAYER);
+
+    let res = run(&mut deps, &env_at(100), OWNER, 0, ExecuteMsg::DrawLottery { round_id: 1 }).unwrap();
+    let prize = Uint128::new(STAKE - fee(STAKE));
+    assert_eq!(sent_to(&res, PLAYER), Uint128::zero());
+    assert_eq!(attr(&res, "frozen"), prize.to_string());
+    assert_eq!(reserved(&deps), Uint128::zero());
+
+    let round: LotteryRound = query_as(&deps, QueryMsg::LotteryRound { round_id: 1 });
+    assert!(round.drawn);
+    assert_eq!(round.winners[0].address, Addr::unchecked(PLAYER));
+    assert_eq!(round.winners[0].amount, prize);
+
+    // The prize waits in the balance, out of the bankroll, until the player is unblocked
+    assert_eq!(balance(&deps, PLAYER), prize);
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::WithdrawBalance { amount: prize }).unwrap_err();
+    assert_eq!(err, blocked(PLAYER));
+    owner(&mut deps, ExecuteMsg::UpdateBlocklist { add: vec![], remove: vec![Addr::unchecked(PLAYER)] }).unwrap();
+    let res = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::WithdrawBalance { amount: prize }).unwrap();
+    assert_eq!(sent_to(&res, PLAYER), prize);
+}
+
+#[test]
+fn open_games_of_a_blocked_player_still_settle() {
+    let mut deps = setup();
+
+    for n in 0..20u64 {
+        let player = format!("player{}", n);
+        run(&mut deps, &env_at(n), &player, STAKE, ExecuteMsg::MinesStart { grid: 25, mines: 1, from_balance: None }).unwrap();
+        run(&mut deps, &env_at(n), &player, 0, ExecuteMsg::MinesReveal { tile: 0 }).unwrap();
+        let game: MinesGameResponse = query_as(&deps, QueryMsg::MinesGame { address: Addr::unchecked(&player) });
+        if game.game.is_none() {
+            continue;
+        }
+        block(&mut deps, &player);
+
+        let res = run(&mut deps, &env_at(n + MINES_TIMEOUT), OWNER, 0, ExecuteMsg::MinesTimeout { address: Addr::unchecked(&player) }).unwrap();
+        assert_eq!(sent_to(&res, &player), Uint128::zero());
+        assert_eq!(attr(&res, "frozen"), attr(&res, "payout"));
+        assert_eq!(balance(&deps, &player).to_string(), attr(&res, "payout"));
+        assert_eq!(reserved(&deps), Uint128::zero());
+        let game: MinesGameResponse = query_as(&deps, QueryMsg::MinesGame { address: Addr::unchecked(&player) });
+        assert!(game.game.is_none());
+        return;
+    }
+    panic!("every first reveal busted");
+}
@@ -0,0 +1,119 @@
+use cosmwasm_std::Uint128;
+use crate::msg::{ExecuteMsg, QueryMsg, SHistoryResponse, SlotsConfigResponse, SlotsPay};
+use crate::ContractError;
+use super::*;
+
+fn reels() -> Vec<Vec<u8>> {
+    vec![vec![0, 0, 1, 1]; 3]
+}
+
+fn paytable() -> Vec<SlotsPay> {
+    vec![
+        SlotsPay { symbol: 0, count: 2, multiplier: 1000000 },
+        SlotsPay { symbol: 0, count: 3, multiplier: 3000000 },
+        SlotsPay { symbol: 1, count: 3, multiplier: 2000000 },
+    ]
+}
+
+fn update(reels: Vec<Vec<u8>>, rows: u8, paylines: Vec<Vec<u8>>, paytable: Vec<SlotsPay>) -> ExecuteMsg {
+    ExecuteMsg::UpdateSlots { reels, rows, paylines, paytable }
+}
+
+fn slots() -> ExecuteMsg {
+    ExecuteMsg::Slots { from_balance: None }
+}
+
+/// Multiplier of one line, taken from the longest paying run from the left
+fn line_pay(symbols: &[u8]) -> u64 {
+    let run = symbols.iter().take_while(|s| **s == symbols[0]).count() as u8;
+    paytable().iter()
+        .filter(|p| p.symbol == symbols[0] && p.count <= run)
+        .max_by_key(|p| p.count)
+        .map(|p| p.multiplier)
+        .unwrap_or_default()
+}
+
+#[test]
+fn updates_are_validated_and_report_rtp() {
+    let mut deps = setup();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, update(reels(), 1, vec![vec![0; 3]], paytable())).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let invalid = vec![
+        update(vec![vec![0, 1]; 2], 1, vec![vec![0; 2]], paytable()),
+        update(vec![vec![0, 1]; 6], 1, vec![vec![0; 6]], paytable()),
+        update(vec![vec![0], vec![], vec![0]], 1, vec![vec![0; 3]], paytable()),
+        update(reels(), 0, vec![vec![0; 3]], paytable()),
+        update(reels(), 5, vec![vec![0; 3]], paytable()),
+        update(reels(), 1, vec![], paytable()),
+        update(reels(), 1, vec![vec![0; 2]], paytable()),
+        update(reels(), 1, vec![vec![0, 1, 0]], paytable()),
+        update(reels(), 1, vec![vec![0; 3]], vec![SlotsPay { symbol: 0, count: 4, multiplier: 1 }]),
+    ];
+    for msg in invalid {
+        assert_eq!(owner(&mut deps, msg).unwrap_err(), ContractError::InvalidInput {});
+    }
+    let generous = vec![SlotsPay { symbol: 0, count: 1, multiplier: 2100000 }];
+    let err = owner(&mut deps, update(reels(), 1, vec![vec![0; 3]], generous)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidRtp { rtp: 1050000 });
+
+    let res = owner(&mut deps, update(reels(), 1, vec![vec![0; 3]], paytable())).unwrap();
+    // Three 0s and three 1s show 1 in 8 each, two 0s then a 1 also 1 in 8
+    assert_eq!(attr(&res, "rtp"), "750000");
+    let config: SlotsConfigResponse = query_as(&deps, QueryMsg::SlotsConfig {});
+    assert_eq!(config.rtp, 750000);
+    assert_eq!(config.reels, reels());
+}
+
+#[test]
+fn spin_needs_a_configuration() {
+    let mut deps = setup();
+    let err = run(&mut deps, &mock_env(), PLAYER, 1000, slots()).unwrap_err();
+    assert_eq!(err, ContractError::SlotsNotSet {});
+}
+
+#[test]
+fn spins_pay_the_average_over_paylines() {
+    let mut deps = setup();
+    let paylines = vec![vec![0, 0, 0], vec![1, 1, 1], vec![0, 1, 0]];
+    owner(&mut deps, update(reels(), 2, paylines.clone(), paytable())).unwrap();
+
+    let amount = 1_000_000u128;
+    let (mut wins, mut losses) = (0, 0);
+    for n in 0..40 {
+        let res = run(&mut deps, &env_at(n), PLAYER, amount, slots()).unwrap();
+        let history: SHistoryResponse = query_as(&deps, QueryMsg::SistoryMsg { count: 1 });
+        let record = &history.list[0];
+
+        let total: u64 = paylines.iter().map(|line| {
+            let symbols: Vec<u8> = line.iter().enumerate()
+                .map(|(i, row)| reels()[i][((record.stops[i] + *row as u64) % 4) as usize])
+                .collect();
+            line_pay(&symbols)
+        }).sum();
+        let multiplier = total / paylines.len() as u64;
+        assert_eq!(record.multiplier, multiplier);
+
+        let expected = (amount * multiplier as u128 / 1000000).saturating_sub(fee(amount));
+        assert_eq!(record.payout, Uint128::new(expected));
+        assert_eq!(sent_to(&res, PLAYER), Uint128::new(expected));
+        if multiplier > 1000000 {
+            assert_eq!(record.win, Some(0));
+            wins += 1;
+        } else {
+            assert_eq!(record.win, Some(1));
+            losses += 1;
+        }
+    }
+    assert!(wins > 0 && losses > 0);
+}
+
+#[test]
+fn bankroll_must_cover_the_top_line() {
+    let mut deps = setup();
+    owner(&mut deps, update(reels(), 1, vec![vec![0; 3]], paytable())).unwrap();
+    set_bankroll(&mut deps, 2_000_000);
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 1_000_000, slots()).unwrap_err();
+    assert_eq!(err, ContractError::InsufficientFunds {});
+}
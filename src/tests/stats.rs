@@ -0,0 +1,132 @@
+use cosmwasm_std::{Addr, Int128, Uint128};
+use crate::msg::{ExecuteMsg, GameStats, PlayerStats, PlayerStatsResponse, PlinkoRisk, QueryMsg, StatsResponse};
+use super::*;
+
+const STAKE: u128 = 10_000;
+
+fn stats(deps: &TestDeps, game: &str) -> GameStats {
+    let res: StatsResponse = query_as(deps, QueryMsg::Stats { game: game.to_string() });
+    assert_eq!(res.game, game);
+    res.stats
+}
+
+/// Adds a bet to `expected` the way the contract should
+fn add(expected: &mut GameStats, stake: u128, paid: u128, win: &str, three_way: bool) {
+    expected.bets += 1;
+    expected.wagered += Uint128::new(stake);
+    expected.fees += Uint128::new(fee(stake));
+    expected.paid_out += Uint128::new(paid);
+    expected.house_pnl = Int128::new(expected.house_pnl.i128() + stake as i128 - fee(stake) as i128 - paid as i128);
+    match (win, three_way) {
+        ("0", _) => {
+            expected.wins += 1;
+            expected.largest_win = expected.largest_win.max(Uint128::new(paid));
+        },
+        ("1", true) => expected.ties += 1,
+        _ => expected.losses += 1
+    }
+}
+
+#[test]
+fn unplayed_games_have_empty_stats() {
+    let deps = setup();
+    assert_eq!(stats(&deps, "flip"), GameStats::default());
+}
+
+#[test]
+fn flips_are_aggregated() {
+    let mut deps = setup();
+
+    let mut expected = GameStats::default();
+    for n in 0..30u64 {
+        let stake = STAKE * (n as u128 % 3 + 1);
+        let res = run(&mut deps, &env_at(n), &format!("player{}", n % 4), stake, ExecuteMsg::Flip { level: 0, from_balance: None }).unwrap();
+        let paid = if attr(&res, "win") == "0" { 2 * stake - fee(stake) } else { 0 };
+        add(&mut expected, stake, paid, attr(&res, "win"), false);
+    }
+    assert!(expected.wins > 0 && expected.losses > 0);
+    assert_eq!(stats(&deps, "flip"), expected);
+    assert_eq!(stats(&deps, "rps"), GameStats::default());
+}
+
+#[test]
+fn rps_ties_are_counted_apart() {
+    let mut deps = setup();
+
+    let mut expected = GameStats::default();
+    for n in 0..30u64 {
+        let res = run(&mut deps, &env_at(n), PLAYER, STAKE, ExecuteMsg::Rps { level: n % 3, from_balance: None }).unwrap();
+        let paid = match attr(&res, "win") {
+            "0" => 2 * STAKE - fee(STAKE),
+            "1" => STAKE - fee(STAKE),
+            _ => 0
+        };
+        add(&mut expected, STAKE, paid, attr(&res, "win"), true);
+    }
+    assert!(expected.wins > 0 && expected.ties > 0 && expected.losses > 0);
+    assert_eq!(stats(&deps, "rps"), expected);
+}
+
+#[test]
+fn player_stats_track_streaks_per_game() {
+    let mut deps = setup();
+
+    let mut flip = PlayerStats::default();
+    let mut rps = PlayerStats::default();
+    for n in 0..40u64 {
+        let (msg, expected) = if n % 2 == 0 {
+            (ExecuteMsg::Flip { level: 0, from_balance: None }, &mut flip)
+        } else {
+            (ExecuteMsg::Rps { level: n % 3, from_balance: None }, &mut rps)
+        };
+        let three_way = n % 2 == 1;
+        let res = run(&mut deps, &env_at(n), PLAYER, STAKE, msg).unwrap();
+        let paid = match (attr(&res, "win"), three_way) {
+            ("0", _) => 2 * STAKE - fee(STAKE),
+            ("1", true) => STAKE - fee(STAKE),
+            _ => 0
+        };
+
+        expected.bets += 1;
+        expected.wagered += Uint128::new(STAKE);
+        expected.won += Uint128::new(paid);
+        expected.net_pnl = Int128::new(expected.net_pnl.i128() + paid as i128 - STAKE as i128);
+        match (attr(&res, "win"), three_way) {
+            ("0", _) => {
+                expected.biggest_win = expected.biggest_win.max(Uint128::new(paid));
+                expected.current_streak += 1;
+                expected.longest_streak = expected.longest_streak.max(expected.current_streak);
+            },
+            // A tie leaves the streak as it is
+            ("1", true) => {},
+            _ => expected.current_streak = 0
+        }
+    }
+    assert!(flip.longest_streak > 1);
+
+    let res: PlayerStatsResponse = query_as(&deps, QueryMsg::PlayerStats { address: Addr::unchecked(PLAYER) });
+    assert_eq!(res.address, PLAYER);
+    let games: Vec<(&str, &PlayerStats)> = res.games.iter().map(|g| (g.game.as_str(), &g.stats)).collect();
+    assert_eq!(games, vec![("flip", &flip), ("rps", &rps)]);
+
+    let res: PlayerStatsResponse = query_as(&deps, QueryMsg::PlayerStats { address: Addr::unchecked("other") });
+    assert!(res.games.is_empty());
+}
+
+#[test]
+fn payouts_below_the_stake_are_losses() {
+    let mut deps = setup();
+    // Every slot pays 1.02x, less than the stake once the fee is taken, or nothing
+    let multipliers = vec![0, 0, 1020000, 1020000, 1020000, 1020000, 1020000, 0, 0];
+    owner(&mut deps, ExecuteMsg::UpdatePlinkoTable { rows: 8, risk: PlinkoRisk::Low, multipliers }).unwrap();
+
+    let mut expected = GameStats::default();
+    for n in 0..10 {
+        let res = run(&mut deps, &env_at(n), PLAYER, STAKE, ExecuteMsg::Plinko { rows: 8, risk: PlinkoRisk::Low, from_balance: None }).unwrap();
+        assert_eq!(attr(&res, "win"), "1");
+        let paid = if attr(&res, "multiplier") == "0" { 0 } else { STAKE * 102 / 100 - fee(STAKE) };
+        add(&mut expected, STAKE, paid, "1", false);
+    }
+    assert!(!expected.paid_out.is_zero());
+    assert_eq!(stats(&deps, "plinko"), expected);
+}
@@ -0,0 +1,107 @@
+use cosmwasm_std::{Addr, Uint128};
+use crate::msg::{BatchGame, ExecuteMsg, JackpotResponse, QueryMsg, VouchersResponse};
+use crate::ContractError;
+use super::*;
+
+const AMOUNT: u128 = 1_000_000;
+
+fn issue(address: &str, game: Option<BatchGame>, expires_at: u64) -> ExecuteMsg {
+    ExecuteMsg::IssueVoucher { address: Addr::unchecked(address), amount: Uint128::new(AMOUNT), game, expires_at }
+}
+
+fn bet(voucher_id: u64, game: BatchGame) -> ExecuteMsg {
+    ExecuteMsg::VoucherBet { voucher_id, game, selection: 0 }
+}
+
+fn expires_at() -> u64 {
+    mock_env().block.time.seconds() + 100
+}
+
+fn vouchers(deps: &TestDeps, address: &str, start_after: Option<u64>) -> Vec<u64> {
+    let res: VouchersResponse = query_as(deps, QueryMsg::Vouchers { address: Addr::unchecked(address), start_after, limit: Some(2) });
+    res.list.iter().map(|v| v.id).collect()
+}
+
+#[test]
+fn vouchers_are_issued_listed_and_revoked() {
+    let mut deps = setup();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, issue(PLAYER, None, expires_at())).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = owner(&mut deps, issue(PLAYER, None, mock_env().block.time.seconds())).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    let zero = ExecuteMsg::IssueVoucher { address: Addr::unchecked(PLAYER), amount: Uint128::zero(), game: None, expires_at: expires_at() };
+    assert_eq!(owner(&mut deps, zero).unwrap_err(), ContractError::InvalidInput {});
+
+    for address in [PLAYER, "other", PLAYER, PLAYER] {
+        owner(&mut deps, issue(address, None, expires_at())).unwrap();
+    }
+    assert_eq!(vouchers(&deps, PLAYER, None), vec![1, 3]);
+    assert_eq!(vouchers(&deps, PLAYER, Some(3)), vec![4]);
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, ExecuteMsg::RevokeVoucher { voucher_id: 3 }).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    owner(&mut deps, ExecuteMsg::RevokeVoucher { voucher_id: 3 }).unwrap();
+    assert_eq!(vouchers(&deps, PLAYER, None), vec![1, 4]);
+    let err = owner(&mut deps, ExecuteMsg::RevokeVoucher { voucher_id: 3 }).unwrap_err();
+    assert_eq!(err, ContractError::VoucherNotFound { voucher_id: 3 });
+}
+
+#[test]
+fn voucher_bets_are_validated() {
+    let mut deps = setup();
+    owner(&mut deps, issue(PLAYER, Some(BatchGame::Dice), expires_at())).unwrap();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, bet(2, BatchGame::Dice)).unwrap_err();
+    assert_eq!(err, ContractError::VoucherNotFound { voucher_id: 2 });
+    let err = run(&mut deps, &mock_env(), "other", 0, bet(1, BatchGame::Dice)).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, bet(1, BatchGame::Flip)).unwrap_err();
+    assert_eq!(err, ContractError::VoucherGameMismatch {});
+    let err = run(&mut deps, &mock_env(), PLAYER, 1000, bet(1, BatchGame::Dice)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidInput {});
+    let err = run(&mut deps, &env_at(100), PLAYER, 0, bet(1, BatchGame::Dice)).unwrap_err();
+    assert_eq!(err, ContractError::VoucherExpired {});
+}
+
+#[test]
+fn wins_pay_only_the_profit_and_vouchers_are_single_use() {
+    let mut deps = setup();
+
+    let (mut wins, mut losses) = (0, 0);
+    for n in 0..20 {
+        owner(&mut deps, issue(PLAYER, None, expires_at())).unwrap();
+        let res = run(&mut deps, &env_at(n), PLAYER, 0, bet(n + 1, BatchGame::Flip)).unwrap();
+        let profit = if attr(&res, "win") == "0" {
+            wins += 1;
+            AMOUNT - fee(AMOUNT)
+        } else {
+            losses += 1;
+            0
+        };
+        assert_eq!(attr(&res, "payout"), profit.to_string());
+        assert_eq!(sent_to(&res, PLAYER), Uint128::new(profit));
+
+        let err = run(&mut deps, &env_at(n), PLAYER, 0, bet(n + 1, BatchGame::Flip)).unwrap_err();
+        assert_eq!(err, ContractError::VoucherNotFound { voucher_id: n + 1 });
+    }
+    assert!(wins > 0 && losses > 0);
+    assert!(vouchers(&deps, PLAYER, None).is_empty());
+}
+
+#[test]
+fn voucher_rounds_leave_the_jackpot_alone() {
+    let mut deps = setup();
+    // Every paid round would add to the pool and win it
+    owner(&mut deps, ExecuteMsg::UpdateJackpot { contribution_bps: 100, odds: 1 }).unwrap();
+    run(&mut deps, &mock_env(), OWNER, 40_000, ExecuteMsg::SeedJackpot {}).unwrap();
+
+    for n in 0..5 {
+        owner(&mut deps, issue(PLAYER, None, expires_at())).unwrap();
+        let res = run(&mut deps, &env_at(n), PLAYER, 0, bet(n + 1, BatchGame::Flip)).unwrap();
+        assert_eq!(sent_to(&res, PLAYER).to_string(), attr(&res, "payout"));
+    }
+    let jackpot: JackpotResponse = query_as(&deps, QueryMsg::Jackpot {});
+    assert_eq!(jackpot.amount, Uint128::new(40_000));
+    assert_eq!(jackpot.win_count, 0);
+}
@@ -0,0 +1,91 @@
+use cosmwasm_std::Uint128;
+use crate::msg::{ExecuteMsg, QueryMsg, WHistoryResponse, WheelPresetResponse, WheelPresetsResponse, WheelSegment};
+use crate::ContractError;
+use super::*;
+
+fn segments() -> Vec<WheelSegment> {
+    vec![
+        WheelSegment { multiplier: 0, weight: 2 },
+        WheelSegment { multiplier: 1500000, weight: 1 },
+        WheelSegment { multiplier: 1000000, weight: 1 },
+    ]
+}
+
+fn update(preset: u8, segments: Vec<WheelSegment>) -> ExecuteMsg {
+    ExecuteMsg::UpdateWheelPreset { preset, segments }
+}
+
+fn wheel(preset: u8) -> ExecuteMsg {
+    ExecuteMsg::Wheel { preset, from_balance: None }
+}
+
+#[test]
+fn presets_are_validated_and_queryable() {
+    let mut deps = setup();
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 0, update(0, segments())).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let invalid = vec![
+        vec![],
+        vec![WheelSegment { multiplier: 0, weight: 1 }; 65],
+        vec![WheelSegment { multiplier: 0, weight: 0 }],
+        vec![WheelSegment { multiplier: 0, weight: u64::MAX }, WheelSegment { multiplier: 0, weight: 1 }],
+    ];
+    for segments in invalid {
+        assert_eq!(owner(&mut deps, update(0, segments)).unwrap_err(), ContractError::InvalidInput {});
+    }
+    let err = owner(&mut deps, update(0, vec![WheelSegment { multiplier: 1200000, weight: 1 }])).unwrap_err();
+    assert_eq!(err, ContractError::InvalidRtp { rtp: 1200000 });
+
+    let res = owner(&mut deps, update(3, segments())).unwrap();
+    assert_eq!(attr(&res, "rtp"), "625000");
+    owner(&mut deps, update(1, vec![WheelSegment { multiplier: 900000, weight: 1 }])).unwrap();
+
+    let preset: WheelPresetResponse = query_as(&deps, QueryMsg::WheelPreset { preset: 3 });
+    assert_eq!(preset, WheelPresetResponse { preset: 3, segments: segments(), rtp: 625000 });
+    let presets: WheelPresetsResponse = query_as(&deps, QueryMsg::WheelPresets {});
+    assert_eq!(presets.list.iter().map(|p| p.preset).collect::<Vec<_>>(), vec![1, 3]);
+}
+
+#[test]
+fn spin_needs_a_preset() {
+    let mut deps = setup();
+    let err = run(&mut deps, &mock_env(), PLAYER, 1000, wheel(2)).unwrap_err();
+    assert_eq!(err, ContractError::WheelPresetNotSet { preset: 2 });
+}
+
+#[test]
+fn spins_pay_the_segment_multiplier() {
+    let mut deps = setup();
+    owner(&mut deps, update(0, segments())).unwrap();
+
+    let amount = 1_000_000u128;
+    let mut seen = [0; 3];
+    for n in 0..60 {
+        let res = run(&mut deps, &env_at(n), PLAYER, amount, wheel(0)).unwrap();
+        let history: WHistoryResponse = query_as(&deps, QueryMsg::WistoryMsg { count: 1 });
+        let record = &history.list[0];
+
+        let segment = segments()[record.segment as usize].clone();
+        assert_eq!(record.multiplier, segment.multiplier);
+        let expected = (amount * segment.multiplier as u128 / 1000000).saturating_sub(fee(amount));
+        assert_eq!(record.payout, Uint128::new(expected));
+        assert_eq!(sent_to(&res, PLAYER), Uint128::new(expected));
+        // Getting the stake back less the fee is not a win
+        assert_eq!(record.win, Some(if segment.multiplier > 1000000 { 0 } else { 1 }));
+        seen[record.segment as usize] += 1;
+    }
+    assert!(seen.iter().all(|s| *s > 0), "{:?}", seen);
+    // Segment 0 carries half the weight
+    assert!(seen[0] > seen[1] && seen[0] > seen[2], "{:?}", seen);
+}
+
+#[test]
+fn bankroll_must_cover_the_top_segment() {
+    let mut deps = setup();
+    owner(&mut deps, update(0, segments())).unwrap();
+    set_bankroll(&mut deps, 1_000_000);
+
+    let err = run(&mut deps, &mock_env(), PLAYER, 1_000_000, wheel(0)).unwrap_err();
+    assert_eq!(err, ContractError::InsufficientFunds {});
+}
@@ -1,11 +1,17 @@
 use cosmwasm_std::{
-    to_binary,  Response, StdResult, Uint128, Coin, BankMsg,
-    WasmMsg, WasmQuery, QueryRequest, Addr, Storage, CosmosMsg,  QuerierWrapper, BalanceResponse as NativeBalanceResponse, BankQuery, Api
+    to_json_binary,  Response, StdResult, Uint128, Coin, BankMsg,
+    WasmMsg, WasmQuery, QueryRequest, Addr, Storage, CosmosMsg,  QuerierWrapper, BalanceResponse as NativeBalanceResponse, BankQuery, Api, Env
 };
 use cw20::{Balance, Cw20ExecuteMsg, Denom, BalanceResponse as CW20BalanceResponse, Cw20QueryMsg};
 use crate::error::ContractError;
-use crate::state::CONFIG;
+use crate::msg::{SlotsPay, WheelSegment};
+use crate::state::{CONFIG, JACKPOT, RESERVED, TOTAL_BALANCE};
 use crate::constants;
+use crate::referral;
+use crate::loyalty;
+use crate::breaker;
+use std::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
 
 pub fn multiple() -> Uint128 { Uint128::from(100u128) }
 pub fn decimal() -> Uint128 { Uint128::from(1000000u128) }
@@ -18,6 +24,7 @@ pub fn check_enabled(
     if !cfg.enabled {
         return Err(ContractError::Disabled {})
     }
+    breaker::check_halted(storage)?;
     Ok(Response::new().add_attribute("action", "check_enabled"))
 }
 
@@ -87,17 +94,17 @@ pub fn get_amount_of_denom(
                     if coin.amount == Uint128::zero() {
                         return Err(ContractError::NativeInputZero {});
                     }
-                    return Ok(coin.amount);
+                    Ok(coin.amount)
                 },
                 Balance::Cw20(_) => {
-                    return Err(ContractError::TokenTypeMismatch {});
+                    Err(ContractError::TokenTypeMismatch {})
                 }
             }
         },
         Denom::Cw20(cw20_address) => {
             match balance {
                 Balance::Native(_) => {
-                    return Err(ContractError::TokenTypeMismatch {});
+                    Err(ContractError::TokenTypeMismatch {})
                 },
                 Balance::Cw20(token) => {
                     if cw20_address != token.address {
@@ -106,7 +113,7 @@ pub fn get_amount_of_denom(
                     if token.amount == Uint128::zero() {
                         return Err(ContractError::Cw20InputZero {});
                     }
-                    return Ok(token.amount);
+                    Ok(token.amount)
                 }
             }
         }
@@ -122,28 +129,44 @@ pub fn transfer_token_message(
 
     match denom.clone() {
         Denom::Native(native_str) => {
-            return Ok(BankMsg::Send {
+            Ok(BankMsg::Send {
                 to_address: receiver.clone().into(),
                 amount: vec![Coin{
                     denom: native_str,
                     amount
                 }]
-            }.into());
+            }.into())
         },
         Denom::Cw20(cw20_address) => {
-            return Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: cw20_address.clone().into(),
                 funds: vec![],
-                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
                     recipient: receiver.clone().into(),
                     amount
                 })?,
-            }));
+            }))
         }
     }
 }
 
 
+/// Treasury transfer of the fee on a bet of `amount`, less the shares accrued to the player's
+/// referrer and to the player's rakeback
+pub fn fee_message(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+    api: &dyn Api,
+    denom: Denom,
+    player: &Addr,
+    amount: Uint128,
+    owner_amount: Uint128
+) -> Result<CosmosMsg, ContractError> {
+    let commission = referral::accrue(storage, player, owner_amount)?;
+    let rakeback = loyalty::accrue(storage, player, amount, owner_amount - commission)?;
+    transfer_token_message(querier, denom, owner_amount - commission - rakeback, api.addr_validate(constants::TREASURY_ADDR)?)
+}
+
 pub fn get_token_amount_of_address(
     querier: QuerierWrapper,
     denom: Denom,
@@ -156,14 +179,200 @@ pub fn get_token_amount_of_address(
                 address: contract_addr.clone().into(),
                 denom: native_str
             }))?;
-            return Ok(native_response.amount.amount);
+            Ok(native_response.amount.amount)
         },
         Denom::Cw20(cw20_address) => {
             let balance_response: CW20BalanceResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
                 contract_addr: cw20_address.clone().into(),
-                msg: to_binary(&Cw20QueryMsg::Balance {address: contract_addr.clone().into()})?,
+                msg: to_json_binary(&Cw20QueryMsg::Balance {address: contract_addr.clone().into()})?,
             }))?;
-            return Ok(balance_response.balance);
+            Ok(balance_response.balance)
+        }
+    }
+}
+
+pub fn get_winner(p1: u64, p2: u64) -> Option<u8> {
+    match (p1, p2) {
+        (0, 1) => Some(0),
+        (2, 0) => Some(0),
+        (1, 2) => Some(0),
+        (0, 2) => Some(2),
+        (2, 1) => Some(2),
+        (1, 0) => Some(2),
+        _ => Some(1),
+    }
+}
+
+/// Whether the roulette bet `level` covers `number`: 0-36 straight up, 37-39 columns,
+/// 40-42 dozens, 43-44 low/high, 45-46 even/odd and 47-48 red/black.
+/// The first two columns wrap like the original checks did, so 0 also hits 37 and 1 also hits 38.
+pub fn roulette_hit(level: u64, number: u64) -> bool {
+    let red = [1, 3, 5, 7, 9, 12, 14, 16, 18, 19, 21, 23, 25, 27, 30, 32, 34, 36];
+    let black = [2, 4, 6, 8, 10, 11, 13, 15, 17, 20, 22, 24, 26, 28, 29, 31, 33, 35];
+    match level {
+        37 => number.wrapping_sub(1) % 3 == 0,
+        38 => number.wrapping_sub(2) % 3 == 0,
+        39 => number % 3 == 0,
+        40 => (1..=12).contains(&number),
+        41 => (13..=24).contains(&number),
+        42 => (25..=36).contains(&number),
+        43 => (1..=18).contains(&number),
+        44 => (19..=36).contains(&number),
+        45 => number % 2 == 0,
+        46 => number % 2 == 1,
+        47 => red.contains(&number),
+        48 => black.contains(&number),
+        _ => level == number
+    }
+}
+
+pub fn binomial(n: u64, k: u64) -> u128 {
+    let k = k.min(n - k);
+    let mut result = 1u128;
+    for i in 0..k {
+        result = result * (n - i) as u128 / (i + 1) as u128;
+    }
+    result
+}
+
+/// Expected return of a plinko table scaled by `MULTIPLY`; slot k is reached with probability C(rows, k) / 2^rows
+pub fn plinko_rtp(multipliers: &[u64]) -> u64 {
+    let rows = multipliers.len() as u64 - 1;
+    let weighted: u128 = multipliers.iter().enumerate()
+        .map(|(k, m)| binomial(rows, k as u64) * *m as u128)
+        .sum();
+    (weighted >> rows) as u64
+}
+
+/// Expected return of a keno table scaled by `MULTIPLY`; k hits out of n picks follow the hypergeometric distribution
+pub fn keno_rtp(multipliers: &[u64]) -> u64 {
+    let picks = multipliers.len() as u64 - 1;
+    let numbers = constants::KENO_NUMBERS as u64;
+    let draws = constants::KENO_DRAWS as u64;
+    let weighted: u128 = multipliers.iter().enumerate()
+        .filter(|(hits, _)| *hits as u64 <= draws && draws - *hits as u64 <= numbers - picks)
+        .map(|(hits, m)| binomial(picks, hits as u64) * binomial(numbers - picks, draws - hits as u64) * *m as u128)
+        .sum();
+    (weighted / binomial(numbers, draws)) as u64
+}
+
+/// Symbol of the leftmost reel and how many reels in a row show it
+pub fn slots_line(symbols: &[u8]) -> (u8, u8) {
+    let count = symbols.iter().take_while(|s| **s == symbols[0]).count();
+    (symbols[0], count as u8)
+}
+
+/// Paytable multiplier for a run, taken from the longest entry not exceeding it
+pub fn slots_pay(paytable: &[SlotsPay], symbol: u8, count: u8) -> u64 {
+    paytable.iter()
+        .filter(|p| p.symbol == symbol && p.count <= count)
+        .max_by_key(|p| p.count)
+        .map(|p| p.multiplier)
+        .unwrap_or_default()
+}
+
+/// Expected return per line bet scaled by `MULTIPLY`.
+/// Every payline sees each reel's strip distribution, so the number of lines does not matter.
+pub fn slots_rtp(reels: &[Vec<u8>], paytable: &[SlotsPay]) -> u64 {
+    let lengths: Vec<u128> = reels.iter().map(|r| r.len() as u128).collect();
+    let total: u128 = lengths.iter().product();
+
+    let mut symbols = reels[0].clone();
+    symbols.sort_unstable();
+    symbols.dedup();
+
+    let mut weighted = 0u128;
+    for symbol in symbols {
+        let hits: Vec<u128> = reels.iter().map(|r| r.iter().filter(|s| **s == symbol).count() as u128).collect();
+        // Stop combinations showing the symbol on the first `run` reels and not on the next one
+        let mut prefix = 1u128;
+        for run in 1..=reels.len() {
+            prefix *= hits[run - 1];
+            let rest: u128 = if run < reels.len() {
+                (lengths[run] - hits[run]) * lengths[run + 1..].iter().product::<u128>()
+            } else {
+                1
+            };
+            weighted += prefix * rest * slots_pay(paytable, symbol, run as u8) as u128;
         }
     }
+    (weighted / total) as u64
+}
+
+/// Expected return of a wheel scaled by `MULTIPLY`
+pub fn wheel_rtp(segments: &[WheelSegment]) -> u64 {
+    let total: u128 = segments.iter().map(|s| s.weight as u128).sum();
+    let weighted: u128 = segments.iter().map(|s| s.weight as u128 * s.multiplier as u128).sum();
+    (weighted / total) as u64
+}
+
+/// Contract balance that backs bets, i.e. excluding the jackpot pool, reserved funds and player balances
+pub fn get_bankroll(
+    storage: &dyn Storage,
+    querier: QuerierWrapper,
+    denom: Denom,
+    contract_addr: Addr
+) -> Result<Uint128, ContractError> {
+    let contract_amount = get_token_amount_of_address(querier, denom, contract_addr)?;
+    let jackpot = JACKPOT.may_load(storage)?.unwrap_or_default();
+    let reserved = RESERVED.may_load(storage)?.unwrap_or_default();
+    let balances = TOTAL_BALANCE.may_load(storage)?.unwrap_or_default();
+    Ok(contract_amount.saturating_sub(jackpot.amount).saturating_sub(reserved).saturating_sub(balances))
+}
+
+pub fn reserve(storage: &mut dyn Storage, amount: Uint128) -> StdResult<Uint128> {
+    let reserved = RESERVED.may_load(storage)?.unwrap_or_default() + amount;
+    RESERVED.save(storage, &reserved)?;
+    Ok(reserved)
+}
+
+pub fn release(storage: &mut dyn Storage, amount: Uint128) -> StdResult<Uint128> {
+    let reserved = RESERVED.may_load(storage)?.unwrap_or_default().saturating_sub(amount);
+    RESERVED.save(storage, &reserved)?;
+    Ok(reserved)
+}
+
+/// Moves a game's reservation to `needed`, failing when the bankroll can't back an increase
+pub fn adjust_reservation(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+    denom: Denom,
+    contract_addr: Addr,
+    reserved: &mut Uint128,
+    needed: Uint128
+) -> Result<(), ContractError> {
+    if needed > *reserved {
+        let bankroll = get_bankroll(storage, querier, denom, contract_addr)?;
+        if bankroll < needed - *reserved {
+            return Err(ContractError::InsufficientFunds {});
+        }
+        reserve(storage, needed - *reserved)?;
+    } else {
+        release(storage, *reserved - needed)?;
+    }
+    *reserved = needed;
+    Ok(())
+}
+
+/// Entropy only known once the block executing an action exists
+pub fn block_entropy(env: &Env) -> u64 {
+    calculate_hash(&(env.block.height, env.block.time.nanos()))
+}
+
+/// Draws a card not dealt yet from a single deck, `drawn` holds every card dealt so far
+pub fn draw_card(seed: u64, drawn: &[u8], entropy: u64) -> u8 {
+    let remaining: Vec<u8> = (0..constants::DECK_SIZE).filter(|c| !drawn.contains(c)).collect();
+    let index = calculate_hash(&(seed, drawn.len(), entropy)) % remaining.len() as u64;
+    remaining[index as usize]
+}
+
+/// 1 for an ace up to 13 for a king
+pub fn card_rank(card: u8) -> u8 {
+    card % 13 + 1
+}
+
+pub fn calculate_hash<T: Hash>(t: &T) -> u64 {
+    let mut s = DefaultHasher::new();
+    t.hash(&mut s);
+    s.finish()
 }
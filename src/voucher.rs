@@ -0,0 +1,171 @@
+use cosmwasm_std::{
+    attr, Addr, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdResult, Uint128
+};
+use cw_storage_plus::Bound;
+use crate::error::ContractError;
+use crate::msg::{BatchGame, Voucher, VouchersResponse};
+use crate::state::{CONFIG, VOUCHERS, PLAYER_VOUCHERS};
+use crate::util;
+use crate::screening;
+use crate::batch;
+use crate::ledger;
+use crate::limits;
+use crate::rate_limit;
+use crate::payout_cap;
+use crate::settlement::{self, Bet};
+use crate::constants;
+
+pub fn execute_issue_voucher(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: Addr,
+    amount: Uint128,
+    game: Option<BatchGame>,
+    expires_at: u64
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    let address = deps.api.addr_validate(address.as_str())?;
+
+    if amount.is_zero() || expires_at <= env.block.time.seconds() {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    cfg.voucher_count += 1;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let voucher = Voucher {
+        id: cfg.voucher_count,
+        address: address.clone(),
+        amount,
+        game,
+        expires_at
+    };
+    VOUCHERS.save(deps.storage, voucher.id, &voucher)?;
+    PLAYER_VOUCHERS.save(deps.storage, (&address, voucher.id), &Empty {})?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "issue_voucher"),
+        attr("voucher_id", voucher.id.to_string()),
+        attr("address", address),
+        attr("amount", amount),
+        attr("game", game.map(|g| g.as_str()).unwrap_or("any")),
+        attr("expires_at", expires_at.to_string()),
+    ]))
+}
+
+pub fn execute_revoke_voucher(
+    deps: DepsMut,
+    info: MessageInfo,
+    voucher_id: u64
+) -> Result<Response, ContractError> {
+    util::check_owner(deps.storage, deps.api, info.sender.clone())?;
+
+    let voucher = VOUCHERS.may_load(deps.storage, voucher_id)?
+        .ok_or(ContractError::VoucherNotFound { voucher_id })?;
+    VOUCHERS.remove(deps.storage, voucher_id);
+    PLAYER_VOUCHERS.remove(deps.storage, (&voucher.address, voucher_id));
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "revoke_voucher"),
+        attr("voucher_id", voucher_id.to_string()),
+        attr("address", voucher.address),
+    ]))
+}
+
+/// Plays one round staked by the voucher, which is used up whatever the outcome.
+/// The stake was never paid, so a win only pays the profit over it and the round
+/// neither feeds nor draws the jackpot.
+pub fn execute_voucher_bet(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    voucher_id: u64,
+    game: BatchGame,
+    selection: u64
+) -> Result<Response, ContractError> {
+
+    util::check_enabled(deps.storage)?;
+    screening::check_address(deps.storage, deps.querier, &info.sender)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    let voucher = VOUCHERS.may_load(deps.storage, voucher_id)?
+        .ok_or(ContractError::VoucherNotFound { voucher_id })?;
+    if voucher.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if env.block.time.seconds() >= voucher.expires_at {
+        return Err(ContractError::VoucherExpired {});
+    }
+    if voucher.game.is_some_and(|g| g != game) {
+        return Err(ContractError::VoucherGameMismatch {});
+    }
+    if !info.funds.is_empty() {
+        return Err(ContractError::InvalidInput {});
+    }
+    batch::check_selection(game, selection)?;
+    // Nothing is staked, but an excluded player can't use a voucher either
+    limits::check_bet(deps.storage, &env, &info.sender, Uint128::zero())?;
+    rate_limit::check(deps.storage, &env, &info.sender, 1)?;
+
+    let amount = voucher.amount;
+    let contract_amount = util::get_bankroll(deps.storage, deps.querier, cfg.denom.clone(), env.contract.address.clone())?;
+    if contract_amount < batch::max_reward(amount) {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    payout_cap::check(deps.storage, &env, &info.sender, batch::max_reward(amount).saturating_sub(amount))?;
+
+    VOUCHERS.remove(deps.storage, voucher_id);
+    PLAYER_VOUCHERS.remove(deps.storage, (&info.sender, voucher_id));
+
+    let round = batch::play_round(deps.storage, &env, &info.sender, &mut cfg, game, selection, amount, false)?;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let profit = round.payout.saturating_sub(amount);
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    ledger::pay(deps.storage, deps.querier, cfg.denom.clone(), &info.sender, profit, false, &mut messages)?;
+    // Nothing was staked, the house only pays the profit
+    let breaker = settlement::settle_bet(deps.storage, deps.querier, &env, Bet {
+        player: &info.sender,
+        game: game.as_str(),
+        count: round.id - 1,
+        win: Some(round.win),
+        wagered: Uint128::zero(),
+        fee: Uint128::zero(),
+        paid: profit,
+        jackpot: Uint128::zero()
+    })?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_events(breaker)
+        .add_attributes(vec![
+            attr("action", "voucher_bet"),
+            attr("address", info.sender.clone()),
+            attr("voucher_id", voucher_id.to_string()),
+            attr("game", game.as_str()),
+            attr("amount", amount),
+            attr("result", round.result.to_string()),
+            attr("win", round.win.to_string()),
+            attr("payout", profit),
+        ]))
+}
+
+pub fn query_vouchers(deps: Deps, address: Addr, start_after: Option<u64>, limit: Option<u32>) -> StdResult<VouchersResponse> {
+    let limit = limit.unwrap_or(constants::DEFAULT_LIMIT).min(constants::MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let list = PLAYER_VOUCHERS.prefix(&address)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|id| id.and_then(|id| VOUCHERS.load(deps.storage, id)))
+        .collect::<StdResult<Vec<Voucher>>>()?;
+
+    Ok(VouchersResponse {
+        list
+    })
+}